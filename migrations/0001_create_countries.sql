@@ -0,0 +1,8 @@
+-- The original countries table
+CREATE TABLE IF NOT EXISTS countries (
+    code TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    capital TEXT NOT NULL,
+    region TEXT NOT NULL,
+    currency TEXT NOT NULL
+);
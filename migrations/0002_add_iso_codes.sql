@@ -0,0 +1,9 @@
+-- ISO 3166-1 alpha-3 and numeric codes
+--
+-- Existing rows get their codes from the seed data; any other country keeps
+-- NULL codes until it is updated.
+ALTER TABLE countries ADD COLUMN alpha3 TEXT;
+ALTER TABLE countries ADD COLUMN numeric TEXT;
+
+CREATE UNIQUE INDEX countries_alpha3 ON countries (alpha3);
+CREATE UNIQUE INDEX countries_numeric ON countries (numeric);
@@ -0,0 +1,62 @@
+-- Full-text search index over country names, capitals and alternative names
+--
+-- Triggers keep countries_fts in sync with countries and country_alt_names.
+CREATE TABLE country_alt_names (
+    code TEXT NOT NULL REFERENCES countries(code) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    PRIMARY KEY (code, name)
+);
+
+CREATE VIRTUAL TABLE countries_fts USING fts5(
+    code UNINDEXED,
+    name,
+    capital,
+    alt_names,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE VIRTUAL TABLE countries_fts_vocab USING fts5vocab(countries_fts, 'row');
+
+CREATE TRIGGER countries_fts_insert AFTER INSERT ON countries BEGIN
+    INSERT INTO countries_fts (code, name, capital, alt_names)
+    VALUES (
+        new.code,
+        new.name,
+        new.capital,
+        (SELECT COALESCE(GROUP_CONCAT(name, ' '), '') FROM country_alt_names WHERE code = new.code)
+    );
+END;
+
+CREATE TRIGGER countries_fts_update AFTER UPDATE ON countries BEGIN
+    DELETE FROM countries_fts WHERE code = old.code;
+    INSERT INTO countries_fts (code, name, capital, alt_names)
+    VALUES (
+        new.code,
+        new.name,
+        new.capital,
+        (SELECT COALESCE(GROUP_CONCAT(name, ' '), '') FROM country_alt_names WHERE code = new.code)
+    );
+END;
+
+CREATE TRIGGER countries_fts_delete AFTER DELETE ON countries BEGIN
+    DELETE FROM countries_fts WHERE code = old.code;
+    DELETE FROM country_alt_names WHERE code = old.code;
+END;
+
+CREATE TRIGGER country_alt_names_insert AFTER INSERT ON country_alt_names BEGIN
+    UPDATE countries_fts
+    SET alt_names = (SELECT GROUP_CONCAT(name, ' ') FROM country_alt_names WHERE code = new.code)
+    WHERE code = new.code;
+END;
+
+CREATE TRIGGER country_alt_names_delete AFTER DELETE ON country_alt_names BEGIN
+    UPDATE countries_fts
+    SET alt_names = (SELECT COALESCE(GROUP_CONCAT(name, ' '), '') FROM country_alt_names WHERE code = old.code)
+    WHERE code = old.code;
+END;
+
+-- Index the countries stored before the search index existed
+INSERT INTO countries_fts (code, name, capital, alt_names)
+SELECT c.code, c.name, c.capital,
+    (SELECT COALESCE(GROUP_CONCAT(a.name, ' '), '') FROM country_alt_names a WHERE a.code = c.code)
+FROM countries c;
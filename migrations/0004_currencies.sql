@@ -0,0 +1,27 @@
+-- ISO 4217 currencies and the legal tenders of every country
+--
+-- The currencies themselves are reference data, loaded from
+-- data/currencies.json when the database is seeded, after migrations have
+-- run; currency codes of countries are validated against the same list, so
+-- country_currencies does not reference the table. countries.currency stays
+-- the main currency and is always listed first.
+CREATE TABLE currencies (
+    code TEXT PRIMARY KEY,
+    numeric TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    minor_units INTEGER NOT NULL
+);
+
+CREATE TABLE country_currencies (
+    country_code TEXT NOT NULL REFERENCES countries(code) ON DELETE CASCADE,
+    currency_code TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (country_code, currency_code)
+);
+
+CREATE INDEX country_currencies_currency ON country_currencies (currency_code, country_code);
+
+-- Link the countries stored before this migration to their main currency
+INSERT INTO country_currencies (country_code, currency_code, position)
+SELECT code, currency, 0 FROM countries WHERE currency <> '';
@@ -0,0 +1,21 @@
+-- ISO 639 languages and the official and recognized languages of every country
+--
+-- Like currencies, the languages are reference data loaded from
+-- data/languages.json when the database is seeded, so country_languages does
+-- not reference the table. position keeps the order languages are listed
+-- in, official ones first.
+CREATE TABLE languages (
+    code TEXT PRIMARY KEY,
+    alpha3 TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL
+);
+
+CREATE TABLE country_languages (
+    country_code TEXT NOT NULL REFERENCES countries(code) ON DELETE CASCADE,
+    language_code TEXT NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('official', 'recognized')),
+    position INTEGER NOT NULL,
+    PRIMARY KEY (country_code, language_code)
+);
+
+CREATE INDEX country_languages_language ON country_languages (language_code, country_code);
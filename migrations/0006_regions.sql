@@ -0,0 +1,18 @@
+-- UN M49 regions and the most specific region of every country
+--
+-- The regions are reference data loaded from data/regions.json when the
+-- database is seeded, parents before children, so parent_code and
+-- countries.region_code do not reference the table. countries.region stays
+-- the continent the country was originally filed under.
+CREATE TABLE regions (
+    code TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    parent_code TEXT,
+    level TEXT NOT NULL CHECK (level IN ('world', 'region', 'subregion', 'intermediate-region'))
+);
+
+CREATE INDEX regions_parent ON regions (parent_code);
+
+ALTER TABLE countries ADD COLUMN region_code TEXT NOT NULL DEFAULT '';
+
+CREATE INDEX countries_region_code ON countries (region_code);
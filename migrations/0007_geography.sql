@@ -0,0 +1,17 @@
+-- Coordinates of the capital and centroid, land area and bounding box of
+-- every country
+--
+-- Coordinates are WGS 84 decimal degrees. The bounding box is stored as
+-- west, south, east and north edges; west is greater than east for
+-- countries spanning the antimeridian. Every column is nullable: the
+-- uninhabited territories have no capital, and countries added through the
+-- API may be stored without any geography.
+ALTER TABLE countries ADD COLUMN capital_latitude REAL;
+ALTER TABLE countries ADD COLUMN capital_longitude REAL;
+ALTER TABLE countries ADD COLUMN latitude REAL;
+ALTER TABLE countries ADD COLUMN longitude REAL;
+ALTER TABLE countries ADD COLUMN area_km2 REAL;
+ALTER TABLE countries ADD COLUMN bbox_west REAL;
+ALTER TABLE countries ADD COLUMN bbox_south REAL;
+ALTER TABLE countries ADD COLUMN bbox_east REAL;
+ALTER TABLE countries ADD COLUMN bbox_north REAL;
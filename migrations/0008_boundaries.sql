@@ -0,0 +1,28 @@
+-- Boundary polygons of countries and a spatial index over them
+--
+-- Simplified boundaries are loaded from data/boundaries.geojson.gz when the
+-- database is seeded; more detailed ones can be loaded from a GeoJSON file
+-- such as Natural Earth's admin 0 countries. Every polygon of a
+-- country is stored on its own, as the GeoJSON coordinates of a Polygon
+-- (an outer ring followed by its holes), and its bounding box is indexed
+-- in the R*Tree, which shares its id. Triggers keep the index in sync.
+CREATE TABLE boundary_polygons (
+    id INTEGER PRIMARY KEY,
+    country_code TEXT NOT NULL REFERENCES countries(code) ON DELETE CASCADE,
+    coordinates TEXT NOT NULL
+);
+
+CREATE INDEX boundary_polygons_country ON boundary_polygons (country_code);
+
+CREATE VIRTUAL TABLE boundary_index USING rtree(id, min_lon, max_lon, min_lat, max_lat);
+
+CREATE TRIGGER boundary_polygons_insert AFTER INSERT ON boundary_polygons BEGIN
+    INSERT INTO boundary_index (id, min_lon, max_lon, min_lat, max_lat)
+    SELECT new.id, MIN(json_extract(point.value, '$[0]')), MAX(json_extract(point.value, '$[0]')),
+           MIN(json_extract(point.value, '$[1]')), MAX(json_extract(point.value, '$[1]'))
+    FROM json_each(new.coordinates, '$[0]') AS point;
+END;
+
+CREATE TRIGGER boundary_polygons_delete AFTER DELETE ON boundary_polygons BEGIN
+    DELETE FROM boundary_index WHERE id = old.id;
+END;
@@ -0,0 +1,14 @@
+-- Land borders between countries
+--
+-- Borders are reference data, loaded from data/borders.json when the
+-- database is seeded, after migrations have run. Every border is stored in
+-- both directions so that the neighbours of a country are a single lookup.
+-- Rows do not reference countries: borders are read joined with the
+-- countries table, so they follow countries that are deleted or imported
+-- again.
+CREATE TABLE borders (
+    country_code TEXT NOT NULL,
+    neighbor_code TEXT NOT NULL,
+    PRIMARY KEY (country_code, neighbor_code),
+    CHECK (country_code <> neighbor_code)
+);
@@ -0,0 +1,8 @@
+-- International calling codes and country code top-level domains
+--
+-- Both are stored as JSON arrays. A calling code is the E.164 prefix of the
+-- country's numbers: the ITU country code, followed after a space by the
+-- area codes that set the country apart when it shares its country code
+-- (e.g. "+1 809" for the Dominican Republic). TLDs include the leading dot.
+ALTER TABLE countries ADD COLUMN calling_codes TEXT NOT NULL DEFAULT '[]';
+ALTER TABLE countries ADD COLUMN tlds TEXT NOT NULL DEFAULT '[]';
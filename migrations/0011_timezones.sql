@@ -0,0 +1,5 @@
+-- IANA time zones of every country
+--
+-- Stored as a JSON array of zone names from the tz database's zone.tab, with
+-- the zone of the capital first (e.g. ["America/New_York", "America/Detroit", ...]).
+ALTER TABLE countries ADD COLUMN timezones TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,19 @@
+-- Public holiday rules of countries
+--
+-- Rules are reference data, loaded from data/holidays.json when the
+-- database is seeded, after migrations have run. `date_rule` holds the JSON
+-- rule giving the date of the holiday in a year, e.g.
+-- {"type": "easter", "offset": -2} for Good Friday; `observed` names the
+-- day off given when the holiday falls on a weekend, and `since` and
+-- `until` the first and last years it is kept. Rows do not reference
+-- countries, so they follow countries that are deleted or imported again.
+CREATE TABLE holiday_rules (
+    country_code TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    date_rule TEXT NOT NULL,
+    observed TEXT CHECK (observed IN ('nearest_weekday', 'next_weekday')),
+    since INTEGER,
+    until INTEGER,
+    PRIMARY KEY (country_code, position)
+);
@@ -0,0 +1,9 @@
+-- Working week and driving side of every country
+--
+-- `weekend` is a JSON array of lowercase weekday names (e.g. ["friday","saturday"]
+-- in most Gulf states), empty where unknown; `first_day_of_week` is the day weeks
+-- start on in calendars, and `driving_side` the side of the road traffic keeps to.
+ALTER TABLE countries ADD COLUMN weekend TEXT NOT NULL DEFAULT '[]';
+ALTER TABLE countries ADD COLUMN first_day_of_week TEXT
+    CHECK (first_day_of_week IN ('monday', 'tuesday', 'wednesday', 'thursday', 'friday', 'saturday', 'sunday'));
+ALTER TABLE countries ADD COLUMN driving_side TEXT CHECK (driving_side IN ('left', 'right'));
@@ -0,0 +1,62 @@
+//! Land borders between countries and routes across them
+//!
+//! The borders are compiled into the binary from `data/borders.json`, a list
+//! of pairs of alpha-2 codes, and loaded into the `borders` table when a
+//! database is seeded. Only land borders count: countries joined by a
+//! bridge or a tunnel alone, such as France and the United Kingdom, are not
+//! neighbours.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::sync::OnceLock;
+
+/// Borders shipped with the API
+const BORDER_DATA: &str = include_str!("../data/borders.json");
+
+/// Every land border shipped with the API, as pairs of alpha-2 codes listed once
+pub fn border_data() -> &'static [[String; 2]] {
+    static BORDERS: OnceLock<Vec<[String; 2]>> = OnceLock::new();
+    BORDERS.get_or_init(|| serde_json::from_str(BORDER_DATA).expect("data/borders.json is a valid border list"))
+}
+
+/// The neighbours of every country, by alpha-2 code
+pub type BorderGraph = BTreeMap<String, BTreeSet<String>>;
+
+/// Builds the border graph from borders listed in either or both directions
+pub fn graph<'a>(borders: impl IntoIterator<Item = (&'a str, &'a str)>) -> BorderGraph {
+    let mut graph = BorderGraph::new();
+    for (a, b) in borders {
+        graph.entry(a.to_string()).or_default().insert(b.to_string());
+        graph.entry(b.to_string()).or_default().insert(a.to_string());
+    }
+    graph
+}
+
+/// Finds a route from `from` to `to` crossing as few borders as possible
+///
+/// Returns the codes of the countries along the route, both ends included,
+/// or `None` if there is no land route. Among equally short routes, the one
+/// through the lowest codes is chosen, so results are stable.
+pub fn shortest_route(graph: &BorderGraph, from: &str, to: &str) -> Option<Vec<String>> {
+    // Breadth-first search, remembering the country each one was reached from
+    let mut reached_from: HashMap<&str, &str> = HashMap::from([(from, from)]);
+    let mut queue = VecDeque::from([from]);
+    while let Some(current) = queue.pop_front() {
+        if current == to {
+            let mut route = vec![to.to_string()];
+            let mut step = to;
+            while step != from {
+                step = reached_from[step];
+                route.push(step.to_string());
+            }
+            route.reverse();
+            return Some(route);
+        }
+        for neighbor in graph.get(current).into_iter().flatten() {
+            if !reached_from.contains_key(neighbor.as_str()) {
+                reached_from.insert(neighbor, current);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,150 @@
+//! Country boundary polygons and point-in-polygon lookups
+//!
+//! Boundaries are read from a GeoJSON FeatureCollection such as Natural
+//! Earth's admin 0 countries, whose features carry the ISO 3166-1 alpha-2
+//! code of their country in an `ISO_A2_EH`, `ISO_A2`, `iso_a2` or `code`
+//! property and a Polygon or MultiPolygon geometry. As RFC 7946 requires,
+//! polygons must be split at the antimeridian rather than cross it.
+//!
+//! Simplified boundaries, from Natural Earth's public domain 1:110m admin 0
+//! countries, are compiled into the binary from `data/boundaries.geojson.gz`
+//! and loaded when a database is seeded. Small countries and territories are
+//! missing at that scale; more detailed boundaries can be loaded on top.
+
+use std::io::Read;
+use std::sync::OnceLock;
+
+use flate2::read::GzDecoder;
+use serde_json::Value;
+
+use crate::error::ApiError;
+use crate::geo::Point;
+
+/// Boundaries shipped with the API, as a gzip-compressed FeatureCollection
+const BOUNDARY_DATA: &[u8] = include_bytes!("../data/boundaries.geojson.gz");
+
+/// Properties that may hold the alpha-2 code of a feature, in order of preference
+///
+/// Natural Earth sets `ISO_A2` to `-99` for a few countries (e.g. France and
+/// Norway), whose code is then only given in `ISO_A2_EH`.
+pub const CODE_PROPERTIES: &[&str] = &["ISO_A2_EH", "ISO_A2", "iso_a2", "code"];
+
+/// A ring of `[longitude, latitude]` positions whose last position repeats the first
+pub type Ring = Vec<[f64; 2]>;
+
+/// An outer ring followed by the rings of its holes, as in GeoJSON
+pub type Polygon = Vec<Ring>;
+
+/// The boundary of a country, made of one or more polygons
+#[derive(Debug, Clone, PartialEq)]
+pub struct Boundary {
+    /// Alpha-2 code of the country
+    pub code: String,
+    /// The polygons of the country, e.g. one per island
+    pub polygons: Vec<Polygon>,
+}
+
+impl Boundary {
+    /// Whether `point` lies inside one of the polygons
+    pub fn contains(&self, point: Point) -> bool {
+        self.polygons.iter().any(|polygon| polygon_contains(polygon, point))
+    }
+}
+
+/// Every boundary shipped with the API, ordered by code
+pub fn boundary_data() -> &'static [Boundary] {
+    static BOUNDARIES: OnceLock<Vec<Boundary>> = OnceLock::new();
+    BOUNDARIES.get_or_init(|| {
+        let mut text = String::new();
+        GzDecoder::new(BOUNDARY_DATA)
+            .read_to_string(&mut text)
+            .expect("data/boundaries.geojson.gz is gzip-compressed UTF-8");
+        let (boundaries, _) = parse_geojson(&text).expect("data/boundaries.geojson.gz is a valid FeatureCollection");
+        boundaries
+    })
+}
+
+/// Bounding box of a polygon's outer ring as `[west, south, east, north]`
+pub fn polygon_bounds(polygon: &Polygon) -> [f64; 4] {
+    let outer = polygon.first().map(Vec::as_slice).unwrap_or_default();
+    outer.iter().fold(
+        [f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY],
+        |[west, south, east, north], [lon, lat]| [west.min(*lon), south.min(*lat), east.max(*lon), north.max(*lat)],
+    )
+}
+
+/// Whether `point` lies inside a polygon, outside of its holes
+///
+/// Uses the even-odd rule: a ray cast from the point crosses the rings an
+/// odd number of times when the point is inside, so holes need no special
+/// treatment.
+pub fn polygon_contains(polygon: &Polygon, point: Point) -> bool {
+    let (x, y) = (point.longitude, point.latitude);
+    let [west, south, east, north] = polygon_bounds(polygon);
+    if x < west || x > east || y < south || y > north {
+        return false;
+    }
+
+    let mut inside = false;
+    for ring in polygon {
+        for edge in ring.windows(2) {
+            let ([x1, y1], [x2, y2]) = (edge[0], edge[1]);
+            if (y1 > y) != (y2 > y) && x < x1 + (y - y1) * (x2 - x1) / (y2 - y1) {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// Reads the boundaries from a GeoJSON FeatureCollection
+///
+/// Features of the same country are merged. Returns the boundaries ordered
+/// by code, and the number of features that were skipped because they have
+/// no alpha-2 code or no polygonal geometry.
+pub fn parse_geojson(text: &str) -> Result<(Vec<Boundary>, usize), ApiError> {
+    let document: Value = serde_json::from_str(text)
+        .map_err(|e| ApiError::InvalidBody(format!("Invalid GeoJSON: {}", e)))?;
+    let features = document
+        .get("features")
+        .and_then(Value::as_array)
+        .filter(|_| document.get("type").and_then(Value::as_str) == Some("FeatureCollection"))
+        .ok_or_else(|| ApiError::InvalidBody(String::from("Boundaries must be a GeoJSON FeatureCollection")))?;
+
+    let mut boundaries: Vec<Boundary> = Vec::new();
+    let mut skipped = 0;
+    for feature in features {
+        let (Some(code), Some(polygons)) = (feature_code(feature), feature_polygons(feature)) else {
+            skipped += 1;
+            continue;
+        };
+        match boundaries.iter_mut().find(|boundary| boundary.code == code) {
+            Some(boundary) => boundary.polygons.extend(polygons),
+            None => boundaries.push(Boundary { code, polygons }),
+        }
+    }
+    boundaries.sort_by(|a, b| a.code.cmp(&b.code));
+    Ok((boundaries, skipped))
+}
+
+fn feature_code(feature: &Value) -> Option<String> {
+    let properties = feature.get("properties")?;
+    CODE_PROPERTIES
+        .iter()
+        .filter_map(|name| properties.get(*name)?.as_str())
+        .map(|code| code.trim().to_uppercase())
+        .find(|code| code.len() == 2 && code.chars().all(|c| c.is_ascii_uppercase()))
+}
+
+fn feature_polygons(feature: &Value) -> Option<Vec<Polygon>> {
+    let geometry = feature.get("geometry")?;
+    let coordinates = geometry.get("coordinates")?;
+    let mut polygons: Vec<Polygon> = match geometry.get("type")?.as_str()? {
+        "Polygon" => vec![serde_json::from_value(coordinates.clone()).ok()?],
+        "MultiPolygon" => serde_json::from_value(coordinates.clone()).ok()?,
+        _ => return None,
+    };
+    // A linear ring has at least four positions
+    polygons.retain(|polygon| polygon.first().is_some_and(|outer| outer.len() >= 4));
+    Some(polygons).filter(|polygons| !polygons.is_empty())
+}
@@ -0,0 +1,455 @@
+//! Public holidays and business days
+//!
+//! Holiday rules are compiled into the binary from `data/holidays.json` and
+//! loaded into the `holiday_rules` table when a database is seeded. A rule
+//! gives the date of a holiday in any year: a fixed date, the nth weekday of
+//! a month, a day relative to Western or Orthodox Easter, or a day relative
+//! to a lunar holiday. Lunar holidays are looked up in the tables of their
+//! Gregorian dates listed with the rules, which cover 2020 to 2035, so the
+//! calendar of a country keeping lunar holidays only covers those years;
+//! they may fall a day off where they depend on the sighting of the moon.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::RangeInclusive;
+use std::sync::OnceLock;
+
+use chrono::{Datelike, Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Earliest year holidays are computed for
+pub const MIN_YEAR: i32 = 1900;
+
+/// Latest year holidays are computed for; the Julian calendar used for
+/// Orthodox Easter runs 13 days behind the Gregorian one until then
+pub const MAX_YEAR: i32 = 2099;
+
+/// Most business days that can be added to or subtracted from a date
+pub const MAX_BUSINESS_DAYS: i64 = 2600;
+
+/// A day of the week
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    /// Every day of the week, starting with Monday
+    pub const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    /// Name of the day as stored and serialized
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Weekday::Monday => "monday",
+            Weekday::Tuesday => "tuesday",
+            Weekday::Wednesday => "wednesday",
+            Weekday::Thursday => "thursday",
+            Weekday::Friday => "friday",
+            Weekday::Saturday => "saturday",
+            Weekday::Sunday => "sunday",
+        }
+    }
+
+    /// Parses the name of a day, or its first three letters, ignoring case
+    /// and surrounding whitespace
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        Weekday::ALL
+            .into_iter()
+            .find(|day| day.as_str() == name || (name.len() == 3 && day.as_str().starts_with(&name)))
+    }
+
+    /// The day of the week of `date`
+    pub fn of(date: NaiveDate) -> Self {
+        match date.weekday() {
+            chrono::Weekday::Mon => Weekday::Monday,
+            chrono::Weekday::Tue => Weekday::Tuesday,
+            chrono::Weekday::Wed => Weekday::Wednesday,
+            chrono::Weekday::Thu => Weekday::Thursday,
+            chrono::Weekday::Fri => Weekday::Friday,
+            chrono::Weekday::Sat => Weekday::Saturday,
+            chrono::Weekday::Sun => Weekday::Sunday,
+        }
+    }
+}
+
+/// Weekend days of countries whose weekend is not otherwise known
+pub const DEFAULT_WEEKEND: &[Weekday] = &[Weekday::Saturday, Weekday::Sunday];
+
+/// How the date of a holiday is found in a year
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DateRule {
+    /// The same date every year, e.g. 25 December
+    Fixed { month: u32, day: u32 },
+    /// The `n`th `weekday` counted from `day` (the 1st by default) of a
+    /// month; a negative `n` counts back from `day` (the last day by
+    /// default), so `-1` is the last such weekday
+    NthWeekday {
+        month: u32,
+        weekday: Weekday,
+        n: i32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        day: Option<u32>,
+    },
+    /// `offset` days after Easter Sunday, Western or Orthodox
+    Easter {
+        offset: i64,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        orthodox: bool,
+    },
+    /// `offset` days after a lunar holiday listed in the lunar tables
+    Lunar {
+        event: String,
+        #[serde(default, skip_serializing_if = "is_zero")]
+        offset: i64,
+    },
+}
+
+fn is_zero(offset: &i64) -> bool {
+    *offset == 0
+}
+
+/// Which day off is given when a holiday falls on a weekend
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Observance {
+    /// The nearest working day, the earlier one if both are as near, such
+    /// as the Friday before a Saturday and the Monday after a Sunday
+    NearestWeekday,
+    /// The next working day that is not already a holiday
+    NextWeekday,
+}
+
+impl Observance {
+    /// Name of the observance as stored and serialized
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Observance::NearestWeekday => "nearest_weekday",
+            Observance::NextWeekday => "next_weekday",
+        }
+    }
+
+    /// Parses a stored observance name
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "nearest_weekday" => Some(Observance::NearestWeekday),
+            "next_weekday" => Some(Observance::NextWeekday),
+            _ => None,
+        }
+    }
+}
+
+/// A public holiday of a country and how its date is found
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HolidayRule {
+    /// The English name of the holiday
+    pub name: String,
+    /// How the date of the holiday is found in a year
+    pub date: DateRule,
+    /// The day off given when the holiday falls on a weekend; none if the
+    /// holiday is not moved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub observed: Option<Observance>,
+    /// First year the holiday is kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<i32>,
+    /// Last year the holiday is kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<i32>,
+}
+
+/// Holiday rules and lunar tables shipped with the API
+#[derive(Debug, Deserialize)]
+pub struct HolidayData {
+    /// Gregorian dates of lunar holidays, by event name, in order
+    pub lunar_dates: BTreeMap<String, Vec<NaiveDate>>,
+    /// Holiday rules of every country with a known calendar, by alpha-2 code
+    pub countries: BTreeMap<String, Vec<HolidayRule>>,
+}
+
+/// Holidays shipped with the API
+const HOLIDAY_DATA: &str = include_str!("../data/holidays.json");
+
+/// Every holiday rule and lunar table shipped with the API
+pub fn holiday_data() -> &'static HolidayData {
+    static HOLIDAYS: OnceLock<HolidayData> = OnceLock::new();
+    HOLIDAYS.get_or_init(|| serde_json::from_str(HOLIDAY_DATA).expect("data/holidays.json is a valid holiday list"))
+}
+
+/// Parses a date in `YYYY-MM-DD` form
+pub fn parse_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d").ok()
+}
+
+/// Easter Sunday of `year` in the Gregorian calendar
+///
+/// Western Easter follows the anonymous Gregorian computus; Orthodox Easter
+/// follows the Julian computus and is moved to the Gregorian calendar.
+pub fn easter(year: i32, orthodox: bool) -> Option<NaiveDate> {
+    if orthodox {
+        let (a, b, c) = (year % 4, year % 7, year % 19);
+        let d = (19 * c + 15) % 30;
+        let e = (2 * a + 4 * b - d + 34) % 7;
+        let (month, day) = ((d + e + 114) / 31, (d + e + 114) % 31 + 1);
+        let julian_to_gregorian = (year / 100 - year / 400 - 2) as u64;
+        NaiveDate::from_ymd_opt(year, month as u32, day as u32)?.checked_add_days(Days::new(julian_to_gregorian))
+    } else {
+        let (a, b, c) = (year % 19, year / 100, year % 100);
+        let (d, e) = (b / 4, b % 4);
+        let f = (b + 8) / 25;
+        let g = (b - f + 1) / 3;
+        let h = (19 * a + b - d - g + 15) % 30;
+        let (i, k) = (c / 4, c % 4);
+        let l = (32 + 2 * e + 2 * i - h - k) % 7;
+        let m = (a + 11 * h + 22 * l) / 451;
+        let (month, day) = ((h + l - 7 * m + 114) / 31, (h + l - 7 * m + 114) % 31 + 1);
+        NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+    }
+}
+
+/// `date` moved by `offset` days
+fn shift(date: NaiveDate, offset: i64) -> Option<NaiveDate> {
+    if offset >= 0 {
+        date.checked_add_days(Days::new(offset as u64))
+    } else {
+        date.checked_sub_days(Days::new(offset.unsigned_abs()))
+    }
+}
+
+impl DateRule {
+    /// The dates the rule gives in `year`: none for a date that does not
+    /// exist that year or a lunar holiday outside its table, and two for a
+    /// lunar holiday falling twice in a Gregorian year
+    pub fn dates_in(&self, year: i32) -> Vec<NaiveDate> {
+        match self {
+            DateRule::Fixed { month, day } => NaiveDate::from_ymd_opt(year, *month, *day).into_iter().collect(),
+            DateRule::NthWeekday { month, weekday, n, day } => nth_weekday(year, *month, *weekday, *n, *day).into_iter().collect(),
+            DateRule::Easter { offset, orthodox } => {
+                easter(year, *orthodox).and_then(|easter| shift(easter, *offset)).into_iter().collect()
+            }
+            DateRule::Lunar { event, offset } => holiday_data()
+                .lunar_dates
+                .get(event)
+                .into_iter()
+                .flatten()
+                .filter_map(|date| shift(*date, *offset))
+                .filter(|date| date.year() == year)
+                .collect(),
+        }
+    }
+}
+
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: i32, day: Option<u32>) -> Option<NaiveDate> {
+    if n > 0 {
+        let first = NaiveDate::from_ymd_opt(year, month, day.unwrap_or(1))?;
+        let first = (0..7).filter_map(|i| shift(first, i)).find(|date| Weekday::of(*date) == weekday)?;
+        shift(first, 7 * (n as i64 - 1))
+    } else if n < 0 {
+        let last = match day {
+            Some(day) => NaiveDate::from_ymd_opt(year, month, day)?,
+            None => NaiveDate::from_ymd_opt(year, month + 1, 1)
+                .or_else(|| NaiveDate::from_ymd_opt(year + 1, 1, 1))?
+                .pred_opt()?,
+        };
+        let last = (0..7).filter_map(|i| shift(last, -i)).find(|date| Weekday::of(*date) == weekday)?;
+        shift(last, 7 * (n as i64 + 1))
+    } else {
+        None
+    }
+}
+
+/// A public holiday in a given year
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct Holiday {
+    /// The date of the holiday, e.g. `2027-12-25`
+    #[schema(value_type = String, format = Date)]
+    pub date: NaiveDate,
+    /// The English name of the holiday
+    pub name: String,
+    /// The day off given for the holiday: the date itself, or a working day
+    /// near it when the holiday falls on a weekend
+    #[schema(value_type = String, format = Date)]
+    pub observed_date: NaiveDate,
+}
+
+/// Whether a date is a holiday or a business day in a country
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct HolidayCheck {
+    /// The date checked
+    #[schema(value_type = String, format = Date)]
+    pub date: NaiveDate,
+    /// Whether the date is a public holiday or the day off given for one
+    pub is_holiday: bool,
+    /// Whether the date is neither a weekend day nor a day off for a holiday
+    pub is_business_day: bool,
+    /// The holidays falling on the date or observed on it
+    pub holidays: Vec<Holiday>,
+}
+
+/// The date a number of business days after another one
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct BusinessDays {
+    /// The date counted from, itself not counted
+    #[schema(value_type = String, format = Date)]
+    pub start: NaiveDate,
+    /// The number of business days added; negative to count back
+    pub days: i64,
+    /// The business day reached
+    #[schema(value_type = String, format = Date)]
+    pub date: NaiveDate,
+}
+
+/// The public holidays and weekend of a country
+pub struct Calendar {
+    rules: Vec<HolidayRule>,
+    weekend: Vec<Weekday>,
+    years: RangeInclusive<i32>,
+}
+
+impl Calendar {
+    /// Creates a calendar from the holiday rules and weekend days of a country
+    pub fn new(rules: Vec<HolidayRule>, weekend: &[Weekday]) -> Self {
+        let mut years = MIN_YEAR..=MAX_YEAR;
+        for rule in &rules {
+            let DateRule::Lunar { event, .. } = &rule.date else {
+                continue;
+            };
+            let table = holiday_data().lunar_dates.get(event).map(Vec::as_slice).unwrap_or_default();
+            let (Some(first), Some(last)) = (table.first(), table.last()) else {
+                continue;
+            };
+            // A holiday kept only in some years needs no dates outside of them
+            let start = if rule.since.is_some_and(|since| since >= first.year()) { MIN_YEAR } else { first.year() };
+            let end = if rule.until.is_some_and(|until| until <= last.year()) { MAX_YEAR } else { last.year() };
+            years = *years.start().max(&start)..=*years.end().min(&end);
+        }
+        Calendar { rules, weekend: weekend.to_vec(), years }
+    }
+
+    /// The years the holidays of the calendar are known in: [`MIN_YEAR`] to
+    /// [`MAX_YEAR`], narrowed to the years covered by the tables of its lunar
+    /// holidays
+    pub fn years(&self) -> RangeInclusive<i32> {
+        self.years.clone()
+    }
+
+    /// Whether `date` falls on a weekend
+    pub fn is_weekend(&self, date: NaiveDate) -> bool {
+        self.weekend.contains(&Weekday::of(date))
+    }
+
+    /// The holidays of `year` ordered by date, with the day off given for each
+    ///
+    /// Holidays falling on a weekend are moved as their rule says, in order,
+    /// so that a holiday moved to the next weekday skips the days off already
+    /// given for others: when Christmas Day and Boxing Day fall on a weekend,
+    /// the days off are the Monday and the Tuesday after.
+    pub fn holidays(&self, year: i32) -> Vec<Holiday> {
+        let mut holidays: Vec<(Holiday, Option<Observance>)> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.since.is_none_or(|since| year >= since) && rule.until.is_none_or(|until| year <= until))
+            .flat_map(|rule| {
+                rule.date.dates_in(year).into_iter().map(|date| {
+                    (Holiday { date, name: rule.name.clone(), observed_date: date }, rule.observed)
+                })
+            })
+            .collect();
+        holidays.sort_by_key(|(holiday, _)| holiday.date);
+
+        let mut days_off: BTreeSet<NaiveDate> = holidays
+            .iter()
+            .map(|(holiday, _)| holiday.date)
+            .filter(|date| !self.is_weekend(*date))
+            .collect();
+        for (holiday, observance) in &mut holidays {
+            let Some(observance) = observance else { continue };
+            if !self.is_weekend(holiday.date) {
+                continue;
+            }
+            if let Some(day_off) = self.day_off(holiday.date, *observance, &days_off) {
+                holiday.observed_date = day_off;
+                days_off.insert(day_off);
+            }
+        }
+        holidays.into_iter().map(|(holiday, _)| holiday).collect()
+    }
+
+    /// The working day given off for a holiday on the weekend day `date`
+    fn day_off(&self, date: NaiveDate, observance: Observance, days_off: &BTreeSet<NaiveDate>) -> Option<NaiveDate> {
+        let working = |offset: i64| shift(date, offset).filter(|day| !self.is_weekend(*day));
+        match observance {
+            Observance::NearestWeekday => (1..7).find_map(|offset| working(-offset).or_else(|| working(offset))),
+            Observance::NextWeekday => (1..=14).filter_map(working).find(|day| !days_off.contains(day)),
+        }
+    }
+
+    /// The holidays falling on `date` or observed on it
+    pub fn holidays_on(&self, date: NaiveDate) -> Vec<Holiday> {
+        // Days off may be given in the year before or after the holiday
+        (date.year() - 1..=date.year() + 1)
+            .flat_map(|year| self.holidays(year))
+            .filter(|holiday| holiday.date == date || holiday.observed_date == date)
+            .collect()
+    }
+
+    /// Checks whether `date` is a holiday or a business day
+    pub fn check(&self, date: NaiveDate) -> HolidayCheck {
+        let holidays = self.holidays_on(date);
+        HolidayCheck {
+            date,
+            is_holiday: !holidays.is_empty(),
+            is_business_day: !self.is_weekend(date) && !holidays.iter().any(|holiday| holiday.observed_date == date),
+            holidays,
+        }
+    }
+
+    /// The business day `days` business days after `start`, or before it if
+    /// `days` is negative, skipping weekends and days off for holidays
+    ///
+    /// `start` itself is not counted, so one business day after a Friday is
+    /// the next Monday in countries with a Saturday and Sunday weekend.
+    /// Returns `None` if the count leaves the [years](Self::years) of the calendar.
+    pub fn add_business_days(&self, start: NaiveDate, days: i64) -> Option<NaiveDate> {
+        let mut days_off: HashMap<i32, BTreeSet<NaiveDate>> = HashMap::new();
+        let mut is_day_off = |date: NaiveDate| {
+            // Days off of a year may come from holidays of the year before or after
+            days_off
+                .entry(date.year())
+                .or_insert_with(|| {
+                    (date.year() - 1..=date.year() + 1)
+                        .flat_map(|year| self.holidays(year))
+                        .map(|holiday| holiday.observed_date)
+                        .collect()
+                })
+                .contains(&date)
+        };
+
+        let step = days.signum();
+        let mut date = start;
+        let mut remaining = days.unsigned_abs();
+        while remaining > 0 {
+            date = shift(date, step).filter(|date| self.years.contains(&date.year()))?;
+            if !self.is_weekend(date) && !is_day_off(date) {
+                remaining -= 1;
+            }
+        }
+        Some(date)
+    }
+}
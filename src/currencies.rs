@@ -0,0 +1,45 @@
+//! ISO 4217 currencies that are legal tender somewhere
+//!
+//! The list is compiled into the binary from `data/currencies.json`. It is
+//! loaded into the `currencies` table when a database is seeded, and country
+//! payloads are validated against it.
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// An ISO 4217 currency
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct Currency {
+    /// The ISO 4217 alphabetic code (three letters)
+    pub code: String,
+    /// The ISO 4217 numeric code (three digits, zero-padded)
+    pub numeric: String,
+    /// The English name of the currency
+    pub name: String,
+    /// The symbol commonly used for amounts in the currency
+    pub symbol: String,
+    /// Number of digits after the decimal separator, e.g. 2 for cents
+    pub minor_units: u8,
+}
+
+/// Currencies shipped with the API
+const CURRENCY_DATA: &str = include_str!("../data/currencies.json");
+
+/// Every currency shipped with the API, ordered by code
+pub fn currency_data() -> &'static [Currency] {
+    static CURRENCIES: OnceLock<Vec<Currency>> = OnceLock::new();
+    CURRENCIES.get_or_init(|| serde_json::from_str(CURRENCY_DATA).expect("data/currencies.json is a valid currency list"))
+}
+
+/// Looks a currency up by its alphabetic or numeric code, as normalized by
+/// [`crate::normalize_code`]
+pub fn find_currency(code: &str) -> Option<&'static Currency> {
+    currency_data().iter().find(|currency| currency.code == code || currency.numeric == code)
+}
+
+/// Whether `code` is the alphabetic code of a known currency
+pub fn is_currency_code(code: &str) -> bool {
+    currency_data().iter().any(|currency| currency.code == code)
+}
@@ -0,0 +1,101 @@
+//! SQLite connection pooling
+//!
+//! File databases are opened in WAL mode so that any number of pooled readers
+//! can run alongside a single writer. `:memory:` is mapped to a named
+//! shared-cache in-memory database, unique per pool, so that every connection
+//! of the pool sees the same data. The manager keeps one extra connection to
+//! that database open for as long as it lives, so the data survives the pool
+//! closing every idle connection. Every connection enforces foreign keys, so
+//! deleting a country cascades to its currencies, languages, alternative
+//! names and boundaries.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use rusqlite::{Connection, OpenFlags};
+
+/// Pool of SQLite connections shared by the request handlers
+pub type DbPool = r2d2::Pool<SqliteConnectionManager>;
+
+/// How long a connection waits for another writer to release its lock
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+static MEMORY_DATABASES: AtomicUsize = AtomicUsize::new(0);
+
+/// `r2d2` connection manager opening SQLite connections to one database
+#[derive(Debug)]
+pub struct SqliteConnectionManager {
+    path: String,
+    flags: OpenFlags,
+    in_memory: bool,
+    /// Keeps a shared-cache in-memory database alive between connections
+    keep_alive: OnceLock<Mutex<Connection>>,
+}
+
+impl SqliteConnectionManager {
+    /// Creates a manager for the database at `path`, or a fresh in-memory
+    /// database when `path` is `:memory:`
+    pub fn new(path: &str) -> Self {
+        let flags = OpenFlags::SQLITE_OPEN_READ_WRITE
+            | OpenFlags::SQLITE_OPEN_CREATE
+            | OpenFlags::SQLITE_OPEN_URI
+            | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+
+        if path == ":memory:" {
+            let id = MEMORY_DATABASES.fetch_add(1, Ordering::Relaxed);
+            SqliteConnectionManager {
+                path: format!("file:countries-memory-{}-{}?mode=memory&cache=shared", std::process::id(), id),
+                flags,
+                in_memory: true,
+                keep_alive: OnceLock::new(),
+            }
+        } else {
+            SqliteConnectionManager {
+                path: path.to_string(),
+                flags,
+                in_memory: false,
+                keep_alive: OnceLock::new(),
+            }
+        }
+    }
+}
+
+impl r2d2::ManageConnection for SqliteConnectionManager {
+    type Connection = Connection;
+    type Error = rusqlite::Error;
+
+    fn connect(&self) -> Result<Connection, rusqlite::Error> {
+        let conn = Connection::open_with_flags(&self.path, self.flags)?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "foreign_keys", "ON")?;
+        if self.in_memory {
+            if self.keep_alive.get().is_none() {
+                let sentinel = Connection::open_with_flags(&self.path, self.flags)?;
+                let _ = self.keep_alive.set(Mutex::new(sentinel));
+            }
+        } else {
+            conn.pragma_update(None, "journal_mode", "WAL")?;
+            conn.pragma_update(None, "synchronous", "NORMAL")?;
+        }
+        Ok(conn)
+    }
+
+    fn is_valid(&self, conn: &mut Connection) -> Result<(), rusqlite::Error> {
+        conn.execute_batch("SELECT 1")
+    }
+
+    fn has_broken(&self, _conn: &mut Connection) -> bool {
+        false
+    }
+}
+
+/// Creates a pool of at most `max_size` connections to the database at `path`
+///
+/// The pool opens its connections eagerly, so an unreachable database is
+/// reported here rather than on the first request.
+pub fn create_pool(path: &str, max_size: u32) -> Result<DbPool, r2d2::Error> {
+    r2d2::Pool::builder()
+        .max_size(max_size)
+        .build(SqliteConnectionManager::new(path))
+}
@@ -0,0 +1,204 @@
+//! Error type shared by all handlers
+//!
+//! Every failure is reported to clients as an RFC 7807 problem details
+//! document (`application/problem+json`) carrying a stable machine-readable
+//! `code`, so clients never have to parse human-readable messages.
+
+use std::fmt;
+
+use actix_web::error::{BlockingError, JsonPayloadError, PathError, QueryPayloadError};
+use actix_web::http::{header, StatusCode};
+use actix_web::{HttpRequest, HttpResponse, ResponseError};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::validation::FieldError;
+
+/// Media type of problem details responses
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// RFC 7807 problem details body returned for every error response
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct ProblemDetails {
+    /// URI identifying the problem type
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    /// Short, human-readable summary of the problem type
+    pub title: String,
+    /// HTTP status code of the response
+    pub status: u16,
+    /// Human-readable explanation specific to this occurrence of the problem
+    pub detail: String,
+    /// Stable machine-readable error code (e.g. `not_found`, `conflict`)
+    pub code: String,
+    /// Invalid fields of the request payload, present for validation failures
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+/// Errors returned by the API handlers
+#[derive(Debug)]
+pub enum ApiError {
+    /// The requested resource does not exist
+    NotFound(String),
+    /// The request conflicts with existing data, e.g. a duplicate country code
+    Conflict(String),
+    /// The request parameters are invalid
+    BadRequest(String),
+    /// The request body could not be parsed
+    InvalidBody(String),
+    /// The request body was parsed but some of its fields are invalid
+    Validation(Vec<FieldError>),
+    /// The request body is in a media type the endpoint does not accept
+    UnsupportedMediaType(String),
+    /// None of the media types the client accepts can be produced
+    NotAcceptable(String),
+    /// No database connection became available in time
+    Unavailable(String),
+    /// An unexpected failure, such as a panic while handling the request;
+    /// details are not exposed to clients
+    Internal(String),
+    /// An unexpected database failure; details are not exposed to clients
+    Database(rusqlite::Error),
+}
+
+impl ApiError {
+    /// Stable machine-readable code identifying the kind of error
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::InvalidBody(_) => "invalid_body",
+            ApiError::Validation(_) => "validation_failed",
+            ApiError::UnsupportedMediaType(_) => "unsupported_media_type",
+            ApiError::NotAcceptable(_) => "not_acceptable",
+            ApiError::Unavailable(_) => "service_unavailable",
+            ApiError::Internal(_) | ApiError::Database(_) => "internal_error",
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            ApiError::NotFound(_) => "Resource not found",
+            ApiError::Conflict(_) => "Conflict with existing data",
+            ApiError::BadRequest(_) => "Invalid request parameters",
+            ApiError::InvalidBody(_) => "Invalid request body",
+            ApiError::Validation(_) => "Request payload failed validation",
+            ApiError::UnsupportedMediaType(_) => "Unsupported media type",
+            ApiError::NotAcceptable(_) => "Not acceptable",
+            ApiError::Unavailable(_) => "Service temporarily unavailable",
+            ApiError::Internal(_) | ApiError::Database(_) => "Internal server error",
+        }
+    }
+
+    fn detail(&self) -> String {
+        match self {
+            ApiError::NotFound(detail)
+            | ApiError::Conflict(detail)
+            | ApiError::BadRequest(detail)
+            | ApiError::InvalidBody(detail)
+            | ApiError::UnsupportedMediaType(detail)
+            | ApiError::NotAcceptable(detail) => detail.clone(),
+            ApiError::Validation(errors) => format!("{} field(s) failed validation", errors.len()),
+            ApiError::Unavailable(_) => String::from("The database is busy, please retry later"),
+            ApiError::Internal(_) => String::from("An unexpected error occurred while handling the request"),
+            ApiError::Database(_) => String::from("An unexpected error occurred while accessing the database"),
+        }
+    }
+
+    /// Builds the problem details document describing this error
+    pub fn problem(&self) -> ProblemDetails {
+        ProblemDetails {
+            problem_type: format!("urn:world-countries-api:problem:{}", self.code()),
+            title: self.title().to_string(),
+            status: self.status_code().as_u16(),
+            detail: self.detail(),
+            code: self.code().to_string(),
+            errors: match self {
+                ApiError::Validation(errors) => errors.clone(),
+                _ => Vec::new(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Database(e) => write!(f, "Database error: {}", e),
+            ApiError::Unavailable(message) | ApiError::Internal(message) => write!(f, "{}", message),
+            _ => write!(f, "{}", self.detail()),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::BadRequest(_) | ApiError::InvalidBody(_) => StatusCode::BAD_REQUEST,
+            ApiError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ApiError::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
+            ApiError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Internal(_) | ApiError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if matches!(self, ApiError::Database(_) | ApiError::Internal(_) | ApiError::Unavailable(_)) {
+            log::error!("{}", self);
+        }
+
+        HttpResponse::build(self.status_code())
+            .insert_header((header::CONTENT_TYPE, PROBLEM_JSON))
+            .json(self.problem())
+    }
+}
+
+impl From<rusqlite::Error> for ApiError {
+    fn from(e: rusqlite::Error) -> Self {
+        match e {
+            rusqlite::Error::QueryReturnedNoRows => {
+                ApiError::NotFound(String::from("The requested resource was not found"))
+            }
+            rusqlite::Error::SqliteFailure(ref failure, _)
+                if failure.code == rusqlite::ErrorCode::ConstraintViolation =>
+            {
+                ApiError::Conflict(String::from("The request violates a uniqueness or integrity constraint"))
+            }
+            e => ApiError::Database(e),
+        }
+    }
+}
+
+impl From<r2d2::Error> for ApiError {
+    fn from(e: r2d2::Error) -> Self {
+        ApiError::Unavailable(format!("Database pool error: {}", e))
+    }
+}
+
+impl From<BlockingError> for ApiError {
+    fn from(e: BlockingError) -> Self {
+        ApiError::Internal(format!("Blocking database task failed: {}", e))
+    }
+}
+
+/// Error handler for `web::Json` extractors, registered through `JsonConfig`
+pub fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    ApiError::InvalidBody(err.to_string()).into()
+}
+
+/// Error handler for `web::Query` extractors, registered through `QueryConfig`
+pub fn query_error_handler(err: QueryPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    ApiError::BadRequest(err.to_string()).into()
+}
+
+/// Error handler for `web::Path` extractors, registered through `PathConfig`
+pub fn path_error_handler(err: PathError, _req: &HttpRequest) -> actix_web::Error {
+    ApiError::BadRequest(err.to_string()).into()
+}
@@ -0,0 +1,405 @@
+//! Rendering of responses in the format a client asks for
+//!
+//! Read endpoints answer in JSON by default. Clients can ask for CSV, NDJSON,
+//! XML or YAML with the `Accept` header, or with a `format` query parameter
+//! that takes precedence over it. Every format is produced from the JSON
+//! representation, so all formats carry the same fields.
+
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse, HttpResponseBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::error::ApiError;
+
+/// Formats read endpoints can answer in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Ndjson,
+    Xml,
+    Yaml,
+}
+
+/// Media types of the export formats, in the order they are preferred when a
+/// client accepts several equally
+pub const MEDIA_TYPES: &[(&str, ExportFormat)] = &[
+    ("application/json", ExportFormat::Json),
+    ("text/csv", ExportFormat::Csv),
+    ("application/x-ndjson", ExportFormat::Ndjson),
+    ("application/xml", ExportFormat::Xml),
+    ("application/yaml", ExportFormat::Yaml),
+];
+
+/// Other names clients commonly use for the same formats
+const MEDIA_TYPE_ALIASES: &[(&str, ExportFormat)] = &[
+    ("application/ndjson", ExportFormat::Ndjson),
+    ("application/jsonl", ExportFormat::Ndjson),
+    ("text/xml", ExportFormat::Xml),
+    ("application/x-yaml", ExportFormat::Yaml),
+    ("text/yaml", ExportFormat::Yaml),
+];
+
+impl ExportFormat {
+    /// Media type sent in the `Content-Type` of responses in this format
+    pub fn media_type(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Xml => "application/xml; charset=utf-8",
+            ExportFormat::Yaml => "application/yaml; charset=utf-8",
+        }
+    }
+
+    fn from_media_range(range: &str) -> Option<Self> {
+        MEDIA_TYPES
+            .iter()
+            .chain(MEDIA_TYPE_ALIASES)
+            .find(|(media_type, _)| media_type.eq_ignore_ascii_case(range))
+            .map(|(_, format)| *format)
+            .or_else(|| match range.to_ascii_lowercase().as_str() {
+                "*/*" | "application/*" => Some(ExportFormat::Json),
+                "text/*" => Some(ExportFormat::Csv),
+                _ => None,
+            })
+    }
+}
+
+/// The `format` query parameter of read endpoints
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FormatQuery {
+    /// Response format, overriding the `Accept` header
+    pub format: Option<ExportFormat>,
+}
+
+/// Picks the response format from the `format` parameter or the `Accept` header
+///
+/// Without either, or when the client accepts anything, the response is JSON.
+/// Media ranges are ranked by their `q` weight; a header naming only
+/// unsupported types is rejected with `406 Not Acceptable`.
+pub fn negotiate(req: &HttpRequest, format: Option<ExportFormat>) -> Result<ExportFormat, ApiError> {
+    if let Some(format) = format {
+        return Ok(format);
+    }
+
+    let Some(accept) = req.headers().get(header::ACCEPT).and_then(|value| value.to_str().ok()) else {
+        return Ok(ExportFormat::Json);
+    };
+    if accept.trim().is_empty() {
+        return Ok(ExportFormat::Json);
+    }
+
+    let mut best: Option<(f32, ExportFormat)> = None;
+    for range in accept.split(',') {
+        let mut parts = range.split(';');
+        let media_range = parts.next().unwrap_or_default().trim();
+        let weight = parts
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+
+        if let Some(format) = ExportFormat::from_media_range(media_range) {
+            if weight > 0.0 && best.is_none_or(|(best_weight, _)| weight > best_weight) {
+                best = Some((weight, format));
+            }
+        }
+    }
+
+    best.map(|(_, format)| format).ok_or_else(|| {
+        let supported: Vec<&str> = MEDIA_TYPES.iter().map(|(media_type, _)| *media_type).collect();
+        ApiError::NotAcceptable(format!("Cannot respond with {}; supported: {}", accept, supported.join(", ")))
+    })
+}
+
+/// Element names used when a resource is rendered as XML, or as the CSV
+/// column of plain values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Names {
+    /// Element wrapping a list
+    pub list: &'static str,
+    /// Element of a single item
+    pub item: &'static str,
+}
+
+/// Names for countries
+pub const COUNTRIES: Names = Names { list: "countries", item: "country" };
+
+/// Names for search results
+pub const SEARCH_RESULTS: Names = Names { list: "results", item: "result" };
+
+/// Names for regions
+pub const REGIONS: Names = Names { list: "regions", item: "region" };
+
+/// Names for currencies
+pub const CURRENCIES: Names = Names { list: "currencies", item: "currency" };
+
+/// Names for distances
+pub const DISTANCES: Names = Names { list: "distances", item: "distance" };
+
+/// Names for languages
+pub const LANGUAGES: Names = Names { list: "languages", item: "language" };
+
+/// Names for time zones
+pub const TIMEZONES: Names = Names { list: "timezones", item: "timezone" };
+
+/// Names for local times
+pub const LOCAL_TIMES: Names = Names { list: "times", item: "time" };
+
+/// Names for holidays
+pub const HOLIDAYS: Names = Names { list: "holidays", item: "holiday" };
+
+/// Names for holiday checks
+pub const HOLIDAY_CHECKS: Names = Names { list: "holiday_checks", item: "holiday_check" };
+
+/// Names for business day counts
+pub const BUSINESS_DAYS: Names = Names { list: "business_days", item: "business_day" };
+
+/// Builds a response with `data` rendered in `format`
+///
+/// `builder` carries the status and any extra headers of the response.
+pub fn respond<T: Serialize>(
+    mut builder: HttpResponseBuilder,
+    format: ExportFormat,
+    names: Names,
+    data: &T,
+) -> Result<HttpResponse, ApiError> {
+    let value = serde_json::to_value(data).map_err(|e| ApiError::Internal(format!("Cannot serialize response: {}", e)))?;
+    let body = render(format, names, &value);
+
+    Ok(builder
+        .insert_header((header::VARY, "Accept"))
+        .insert_header((header::CONTENT_TYPE, format.media_type()))
+        .body(body))
+}
+
+/// Renders a JSON value as a document in `format`
+///
+/// Arrays are rendered as lists (one CSV row, NDJSON line or XML element per
+/// item) and anything else as a single item.
+pub fn render(format: ExportFormat, names: Names, value: &Value) -> String {
+    let single = std::slice::from_ref(value);
+    let (items, is_list) = match value {
+        Value::Array(items) => (items.as_slice(), true),
+        _ => (single, false),
+    };
+
+    match format {
+        ExportFormat::Json => value.to_string(),
+        ExportFormat::Csv => to_csv(names, items),
+        ExportFormat::Ndjson => items.iter().map(|item| format!("{}\n", item)).collect(),
+        ExportFormat::Xml => to_xml(names, value, is_list),
+        ExportFormat::Yaml => {
+            let mut out = String::new();
+            write_yaml(&mut out, value, 0);
+            out
+        }
+    }
+}
+
+fn to_csv(names: Names, items: &[Value]) -> String {
+    // Columns are the keys of the items, in order of first appearance
+    let mut columns: Vec<&str> = Vec::new();
+    for item in items {
+        if let Value::Object(members) = item {
+            for key in members.keys() {
+                if !columns.contains(&key.as_str()) {
+                    columns.push(key);
+                }
+            }
+        }
+    }
+    let plain = columns.is_empty();
+    if plain {
+        columns.push(names.item);
+    }
+
+    let mut out = csv_row(columns.iter().map(|column| column.to_string()));
+    for item in items {
+        let cells = columns.iter().map(|column| match item {
+            Value::Object(members) => members.get(*column).map(csv_cell).unwrap_or_default(),
+            _ => csv_cell(item),
+        });
+        out.push_str(&csv_row(cells));
+    }
+    out
+}
+
+/// Text of a CSV cell; lists of plain values are joined with `;`
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) if items.iter().all(|item| !item.is_array() && !item.is_object()) => {
+            items.iter().map(csv_cell).collect::<Vec<_>>().join(";")
+        }
+        Value::Array(_) | Value::Object(_) => value.to_string(),
+        Value::Bool(_) | Value::Number(_) => value.to_string(),
+    }
+}
+
+fn csv_row(cells: impl Iterator<Item = String>) -> String {
+    let cells: Vec<String> = cells
+        .map(|cell| {
+            if cell.contains([',', '"', '\n', '\r']) || cell.trim() != cell {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell
+            }
+        })
+        .collect();
+    format!("{}\r\n", cells.join(","))
+}
+
+fn to_xml(names: Names, value: &Value, is_list: bool) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    if is_list {
+        let items = value.as_array().map(Vec::as_slice).unwrap_or_default();
+        if items.is_empty() {
+            out.push_str(&format!("<{}/>\n", names.list));
+        } else {
+            out.push_str(&format!("<{}>\n", names.list));
+            for item in items {
+                write_xml_element(&mut out, names.item, item, 1);
+            }
+            out.push_str(&format!("</{}>\n", names.list));
+        }
+    } else {
+        write_xml_element(&mut out, names.item, value, 0);
+    }
+    out
+}
+
+fn write_xml_element(out: &mut String, name: &str, value: &Value, depth: usize) {
+    let indent = "  ".repeat(depth);
+    match value {
+        Value::Null => out.push_str(&format!("{}<{}/>\n", indent, name)),
+        Value::Object(members) if members.is_empty() => out.push_str(&format!("{}<{}/>\n", indent, name)),
+        Value::Array(items) if items.is_empty() => out.push_str(&format!("{}<{}/>\n", indent, name)),
+        Value::Object(members) => {
+            out.push_str(&format!("{}<{}>\n", indent, name));
+            for (key, member) in members {
+                write_xml_element(out, key, member, depth + 1);
+            }
+            out.push_str(&format!("{}</{}>\n", indent, name));
+        }
+        Value::Array(items) => {
+            let child = singular(name);
+            out.push_str(&format!("{}<{}>\n", indent, name));
+            for item in items {
+                write_xml_element(out, &child, item, depth + 1);
+            }
+            out.push_str(&format!("{}</{}>\n", indent, name));
+        }
+        Value::String(text) => out.push_str(&format!("{}<{}>{}</{}>\n", indent, name, xml_escape(text), name)),
+        Value::Bool(_) | Value::Number(_) => out.push_str(&format!("{}<{}>{}</{}>\n", indent, name, value, name)),
+    }
+}
+
+/// Element name of the items of a list element, e.g. `currency` for `currencies`
+/// or `day` for `weekend`
+fn singular(name: &str) -> String {
+    if name == "weekend" {
+        return String::from("day");
+    }
+    if let Some(stem) = name.strip_suffix("ies").filter(|stem| !stem.is_empty()) {
+        return format!("{}y", stem);
+    }
+    name.strip_suffix('s').filter(|stem| !stem.is_empty()).unwrap_or("item").to_string()
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Writes `value` as a YAML block at `indent` spaces
+///
+/// Strings are always double-quoted with JSON escapes, which YAML reads
+/// back unchanged, so values such as `NO` or `1e3` keep their type.
+fn write_yaml(out: &mut String, value: &Value, indent: usize) {
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Array(items) if !items.is_empty() => {
+            for item in items {
+                out.push_str(&pad);
+                out.push('-');
+                write_yaml_nested(out, item, indent + 2);
+            }
+        }
+        Value::Object(members) if !members.is_empty() => write_yaml_mapping(out, members, indent, true),
+        _ => {
+            out.push_str(&pad);
+            out.push_str(&yaml_scalar(value));
+            out.push('\n');
+        }
+    }
+}
+
+/// Writes a value following `-` or `key:` on the same line
+fn write_yaml_nested(out: &mut String, value: &Value, indent: usize) {
+    match value {
+        Value::Object(members) if !members.is_empty() => {
+            out.push(' ');
+            write_yaml_mapping(out, members, indent, false);
+        }
+        Value::Array(items) if !items.is_empty() => {
+            out.push('\n');
+            write_yaml(out, value, indent);
+        }
+        _ => {
+            out.push(' ');
+            out.push_str(&yaml_scalar(value));
+            out.push('\n');
+        }
+    }
+}
+
+fn write_yaml_mapping(out: &mut String, members: &Map<String, Value>, indent: usize, pad_first: bool) {
+    let pad = " ".repeat(indent);
+    for (i, (key, member)) in members.iter().enumerate() {
+        if i > 0 || pad_first {
+            out.push_str(&pad);
+        }
+        out.push_str(key);
+        out.push(':');
+        match member {
+            Value::Object(nested) if !nested.is_empty() => {
+                out.push('\n');
+                write_yaml_mapping(out, nested, indent + 2, true);
+            }
+            Value::Array(items) if !items.is_empty() => {
+                out.push('\n');
+                write_yaml(out, member, indent + 2);
+            }
+            _ => {
+                out.push(' ');
+                out.push_str(&yaml_scalar(member));
+                out.push('\n');
+            }
+        }
+    }
+}
+
+fn yaml_scalar(value: &Value) -> String {
+    match value {
+        Value::Array(_) => String::from("[]"),
+        Value::Object(_) => String::from("{}"),
+        // JSON strings, numbers, booleans and null are valid YAML flow scalars
+        _ => value.to_string(),
+    }
+}
@@ -0,0 +1,121 @@
+//! Great-circle distances between points and countries
+//!
+//! Distances are computed with the haversine formula on a sphere of the
+//! Earth's mean radius. That is within 0.5% of the distance on the WGS 84
+//! ellipsoid, well below the precision of the stored coordinates, so
+//! distances are rounded to a tenth of a unit.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::Country;
+
+/// Mean radius of the Earth in kilometres (IUGG)
+pub const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// Kilometres in an international mile
+pub const KM_PER_MILE: f64 = 1.609344;
+
+/// A position in decimal degrees (WGS 84)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Point {
+    /// Creates a point from its latitude and longitude
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Point { latitude, longitude }
+    }
+
+    /// Whether the latitude is within ±90° and the longitude within ±180°
+    pub fn is_valid(self) -> bool {
+        (-90.0..=90.0).contains(&self.latitude) && (-180.0..=180.0).contains(&self.longitude)
+    }
+
+    /// Great-circle distance to `other` in kilometres
+    pub fn distance_km(self, other: Point) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let half_dlat = (lat2 - lat1) / 2.0;
+        let half_dlon = (other.longitude - self.longitude).to_radians() / 2.0;
+        let h = half_dlat.sin().powi(2) + lat1.cos() * lat2.cos() * half_dlon.sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * h.sqrt().min(1.0).asin()
+    }
+}
+
+/// Position of the capital of a country, if it is known
+pub fn capital_of(country: &Country) -> Option<Point> {
+    Some(Point::new(country.capital_latitude?, country.capital_longitude?))
+}
+
+/// Position of the geographic center of a country, if it is known
+pub fn center_of(country: &Country) -> Option<Point> {
+    Some(Point::new(country.latitude?, country.longitude?))
+}
+
+/// Rounds a distance to a tenth of a unit
+fn round_distance(distance: f64) -> f64 {
+    (distance * 10.0).round() / 10.0
+}
+
+/// A country together with its distance from a point
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct NearbyCountry {
+    #[serde(flatten)]
+    pub country: Country,
+    /// Great-circle distance in kilometres from the point to the geographic
+    /// center of the country
+    pub distance_km: f64,
+}
+
+/// Orders countries by the distance of their center from `point`, nearest
+/// first, and keeps the first `limit`
+///
+/// Countries without a known center are left out; equally distant countries
+/// are ordered by code.
+pub fn nearest(countries: impl IntoIterator<Item = Country>, point: Point, limit: usize) -> Vec<NearbyCountry> {
+    let mut nearby: Vec<(f64, Country)> = countries
+        .into_iter()
+        .filter_map(|country| Some((center_of(&country)?.distance_km(point), country)))
+        .collect();
+    nearby.sort_by(|(a, first), (b, second)| a.total_cmp(b).then_with(|| first.code.cmp(&second.code)));
+    nearby
+        .into_iter()
+        .take(limit)
+        .map(|(distance, country)| NearbyCountry { country, distance_km: round_distance(distance) })
+        .collect()
+}
+
+/// Distance between the capitals of two countries
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct CapitalDistance {
+    /// Alpha-2 code of the country the distance is measured from
+    pub from: String,
+    /// Capital of the `from` country
+    pub from_capital: String,
+    /// Alpha-2 code of the country the distance is measured to
+    pub to: String,
+    /// Capital of the `to` country
+    pub to_capital: String,
+    /// Great-circle distance in kilometres
+    pub km: f64,
+    /// Great-circle distance in international miles
+    pub miles: f64,
+}
+
+impl CapitalDistance {
+    /// Measures the distance between the capitals of two countries, or
+    /// `None` if either capital has no known position
+    pub fn between(from: &Country, to: &Country) -> Option<Self> {
+        let km = capital_of(from)?.distance_km(capital_of(to)?);
+        Some(CapitalDistance {
+            from: from.code.clone(),
+            from_capital: from.capital.clone(),
+            to: to.code.clone(),
+            to_capital: to.capital.clone(),
+            km: round_distance(km),
+            miles: round_distance(km / KM_PER_MILE),
+        })
+    }
+}
@@ -0,0 +1,158 @@
+//! GeoJSON (RFC 7946) representation of countries
+//!
+//! Every country becomes a Feature whose properties are its attributes and
+//! whose geometry is either its boundary or the position of its capital.
+//! Boundaries can be simplified with the Douglas-Peucker algorithm, trading
+//! precision for a smaller payload.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::boundaries::{Boundary, Polygon, Ring};
+use crate::geo;
+use crate::Country;
+
+/// Media type of GeoJSON documents
+pub const MEDIA_TYPE: &str = "application/geo+json";
+
+/// The geometry to give a country's feature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GeometryKind {
+    /// The boundary polygons of the country, or no geometry if none are loaded
+    Boundary,
+    /// The position of the capital, or no geometry if it is unknown
+    Capital,
+}
+
+/// A GeoJSON geometry, with `[longitude, latitude]` positions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "coordinates")]
+pub enum Geometry {
+    Point([f64; 2]),
+    Polygon(Polygon),
+    MultiPolygon(Vec<Polygon>),
+}
+
+impl Geometry {
+    /// Geometry of a boundary: a Polygon, or a MultiPolygon if it has several
+    pub fn from_polygons(mut polygons: Vec<Polygon>) -> Self {
+        if polygons.len() == 1 {
+            Geometry::Polygon(polygons.remove(0))
+        } else {
+            Geometry::MultiPolygon(polygons)
+        }
+    }
+}
+
+/// A country as a GeoJSON Feature
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename = "Feature")]
+pub struct Feature {
+    /// Alpha-2 code of the country
+    pub id: String,
+    /// A Point, Polygon or MultiPolygon, or `null` when the country has no
+    /// known position of the requested kind
+    #[schema(value_type = Option<Object>)]
+    pub geometry: Option<Geometry>,
+    /// The attributes of the country
+    pub properties: Country,
+}
+
+/// Countries as a GeoJSON FeatureCollection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename = "FeatureCollection")]
+pub struct FeatureCollection {
+    pub features: Vec<Feature>,
+}
+
+/// Builds the feature of a country
+///
+/// Without an explicit `kind`, the boundary is used where one is loaded and
+/// the capital otherwise. A `tolerance` in degrees simplifies the boundary.
+pub fn feature(country: Country, boundary: Option<&Boundary>, kind: Option<GeometryKind>, tolerance: Option<f64>) -> Feature {
+    let geometry = match (kind, boundary) {
+        (Some(GeometryKind::Capital), _) | (None, None) => {
+            geo::capital_of(&country).map(|capital| Geometry::Point([capital.longitude, capital.latitude]))
+        }
+        (_, Some(boundary)) => Some(Geometry::from_polygons(match tolerance {
+            Some(tolerance) => simplify(&boundary.polygons, tolerance),
+            None => boundary.polygons.clone(),
+        })),
+        (Some(GeometryKind::Boundary), None) => None,
+    };
+
+    Feature { id: country.code.clone(), geometry, properties: country }
+}
+
+/// Simplifies polygons, dropping the vertices closer than `tolerance`
+/// degrees to the simplified outline
+///
+/// Holes and islands that collapse to fewer than four positions are dropped.
+/// A country never disappears entirely: if every polygon would collapse, its
+/// largest polygon is kept as it is.
+pub fn simplify(polygons: &[Polygon], tolerance: f64) -> Vec<Polygon> {
+    let simplified: Vec<Polygon> = polygons
+        .iter()
+        .filter_map(|polygon| {
+            let mut rings = polygon.iter().map(|ring| simplify_ring(ring, tolerance));
+            let outer = rings.next().filter(|outer| outer.len() >= 4)?;
+            Some(std::iter::once(outer).chain(rings.filter(|hole| hole.len() >= 4)).collect())
+        })
+        .collect();
+
+    if !simplified.is_empty() {
+        return simplified;
+    }
+    polygons
+        .iter()
+        .max_by(|a, b| bounds_area(a).total_cmp(&bounds_area(b)))
+        .map(|largest| vec![largest.clone()])
+        .unwrap_or_default()
+}
+
+fn bounds_area(polygon: &Polygon) -> f64 {
+    let [west, south, east, north] = crate::boundaries::polygon_bounds(polygon);
+    (east - west) * (north - south)
+}
+
+/// Simplifies a ring with the Douglas-Peucker algorithm
+///
+/// The ring is treated as a line from its first position back to itself, so
+/// the first position is always kept and the ring stays closed.
+pub fn simplify_ring(ring: &[[f64; 2]], tolerance: f64) -> Ring {
+    if ring.len() < 3 {
+        return ring.to_vec();
+    }
+
+    let last = ring.len() - 1;
+    let mut keep = vec![false; ring.len()];
+    keep[0] = true;
+    keep[last] = true;
+
+    let mut spans = vec![(0, last)];
+    while let Some((first, last)) = spans.pop() {
+        let (farthest, distance) = (first + 1..last)
+            .map(|index| (index, segment_distance(ring[index], ring[first], ring[last])))
+            .fold((first, 0.0), |best, candidate| if candidate.1 > best.1 { candidate } else { best });
+        if distance > tolerance {
+            keep[farthest] = true;
+            spans.push((first, farthest));
+            spans.push((farthest, last));
+        }
+    }
+
+    ring.iter().zip(keep).filter_map(|(position, keep)| keep.then_some(*position)).collect()
+}
+
+/// Planar distance from `point` to the segment from `start` to `end`
+fn segment_distance([x, y]: [f64; 2], [x1, y1]: [f64; 2], [x2, y2]: [f64; 2]) -> f64 {
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        (((x - x1) * dx + (y - y1) * dy) / length_squared).clamp(0.0, 1.0)
+    };
+    (x - (x1 + t * dx)).hypot(y - (y1 + t * dy))
+}
@@ -0,0 +1,637 @@
+//! Bulk import of country datasets
+//!
+//! A dataset is parsed into rows carrying their line number in the source
+//! document, every row is normalized and validated like a `POST /countries`
+//! body, or like a `PUT` body when it overwrites a stored country, and the
+//! valid rows are then written by the repository in a single
+//! transaction. Rows that cannot be imported are reported rather than
+//! failing the whole import.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::calendar::Weekday;
+use crate::error::ApiError;
+use crate::validation::{self, FieldError};
+use crate::{Country, DrivingSide};
+
+/// Columns a CSV dataset must have, in any order
+pub const CSV_COLUMNS: &[&str] = &["code", "alpha3", "numeric", "name", "capital", "region", "currency"];
+
+/// Optional CSV column listing every legal tender, separated by `;`
+pub const CSV_CURRENCIES_COLUMN: &str = "currencies";
+
+/// Optional CSV column holding the UN M49 code of the country's region
+pub const CSV_REGION_CODE_COLUMN: &str = "region_code";
+
+/// Optional CSV column holding the languages as a JSON array, as exported by
+/// the read endpoints (e.g. `[{"code":"de","status":"official"}]`)
+pub const CSV_LANGUAGES_COLUMN: &str = "languages";
+
+/// Optional CSV columns holding coordinates and the area, in decimal degrees
+/// and square kilometres
+pub const CSV_GEOGRAPHY_COLUMNS: &[&str] = &["capital_latitude", "capital_longitude", "latitude", "longitude", "area_km2"];
+
+/// Optional CSV column holding the bounding box as `west;south;east;north`
+pub const CSV_BBOX_COLUMN: &str = "bbox";
+
+/// Optional CSV column listing the calling codes, separated by `;`
+pub const CSV_CALLING_CODES_COLUMN: &str = "calling_codes";
+
+/// Optional CSV column listing the top-level domains, separated by `;`
+pub const CSV_TLDS_COLUMN: &str = "tlds";
+
+/// Optional CSV column listing the IANA time zones, separated by `;`
+pub const CSV_TIMEZONES_COLUMN: &str = "timezones";
+
+/// Optional CSV column listing the days of the weekend, separated by `;`
+pub const CSV_WEEKEND_COLUMN: &str = "weekend";
+
+/// Optional CSV column holding the day weeks start on, e.g. `monday`
+pub const CSV_FIRST_DAY_OF_WEEK_COLUMN: &str = "first_day_of_week";
+
+/// Optional CSV column holding the driving side, `left` or `right`
+pub const CSV_DRIVING_SIDE_COLUMN: &str = "driving_side";
+
+/// Supported dataset formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    /// Comma-separated values with a header row naming the country fields
+    Csv,
+    /// A JSON array of country objects
+    Json,
+    /// One JSON country object per line
+    Ndjson,
+}
+
+impl ImportFormat {
+    /// Picks the format matching a `Content-Type` header value
+    pub fn from_media_type(media_type: &str) -> Option<Self> {
+        let essence = media_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        match essence.as_str() {
+            "text/csv" => Some(ImportFormat::Csv),
+            "application/json" => Some(ImportFormat::Json),
+            "application/x-ndjson" | "application/ndjson" | "application/jsonl" => Some(ImportFormat::Ndjson),
+            _ => None,
+        }
+    }
+
+    /// Picks the format matching a file extension (`.csv`, `.json`, `.ndjson` or `.jsonl`)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "csv" => Some(ImportFormat::Csv),
+            "json" => Some(ImportFormat::Json),
+            "ndjson" | "jsonl" => Some(ImportFormat::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+/// How imported rows are combined with the countries already stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImportMode {
+    /// Add new countries and skip those whose code already exists
+    #[default]
+    InsertOnly,
+    /// Add new countries and overwrite existing ones
+    Upsert,
+    /// Like `upsert`, and delete every stored country missing from the dataset
+    ReplaceAll,
+}
+
+/// A valid row ready to be written
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRow {
+    /// Line of the source document the row starts on
+    pub line: usize,
+    pub country: Country,
+}
+
+/// A row of the dataset that was written or left unchanged
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct ImportedRow {
+    /// Line of the source document the row starts on
+    pub line: usize,
+    /// Alpha-2 code of the country
+    pub code: String,
+}
+
+/// A row of the dataset that could not be imported
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct FailedRow {
+    /// Line of the source document the row starts on
+    pub line: usize,
+    /// Alpha-2 code of the country, if the row had one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Why the row was rejected
+    pub message: String,
+    /// Invalid fields, present when the row failed validation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+impl FailedRow {
+    fn new(line: usize, code: Option<String>, message: impl Into<String>) -> Self {
+        FailedRow {
+            line,
+            code,
+            message: message.into(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+/// Rows of a dataset, split into valid rows and rows already known to fail
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportBatch {
+    pub mode: ImportMode,
+    pub rows: Vec<ImportRow>,
+    pub failed: Vec<FailedRow>,
+}
+
+/// Outcome of an import, row by row
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct ImportReport {
+    /// Rows stored as new countries
+    pub inserted: Vec<ImportedRow>,
+    /// Rows that overwrote an existing country
+    pub updated: Vec<ImportedRow>,
+    /// Rows left alone because the country exists (`insert-only`) or is unchanged
+    pub skipped: Vec<ImportedRow>,
+    /// Rows that could not be imported, ordered by line
+    pub failed: Vec<FailedRow>,
+    /// Codes of the countries removed by a `replace-all` import
+    pub deleted: Vec<String>,
+}
+
+/// Parses a dataset and validates its rows
+///
+/// Fails only if the document as a whole is unreadable, e.g. a CSV file
+/// without the required header or a JSON document that is not an array.
+pub fn parse(format: ImportFormat, mode: ImportMode, text: &str) -> Result<ImportBatch, ApiError> {
+    let records = match format {
+        ImportFormat::Csv => parse_csv(text)?,
+        ImportFormat::Json => parse_json_array(text)?,
+        ImportFormat::Ndjson => parse_ndjson(text),
+    };
+
+    let mut batch = ImportBatch { mode, rows: Vec::new(), failed: Vec::new() };
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    for (line, record) in records {
+        let country = match record {
+            Ok(country) => validation::normalize_country(country),
+            Err(failure) => {
+                batch.failed.push(FailedRow::new(line, None, failure));
+                continue;
+            }
+        };
+
+        // Whether the capital and currency may be empty depends on the stored
+        // country, so `apply` checks them once it has looked the country up
+        if let Err(e) = validation::validate_seed_country(&country) {
+            let errors = match e {
+                ApiError::Validation(errors) => errors,
+                _ => Vec::new(),
+            };
+            batch.failed.push(FailedRow {
+                line,
+                code: Some(country.code),
+                message: String::from("The country failed validation"),
+                errors,
+            });
+            continue;
+        }
+
+        if let Some(first) = seen.insert(country.code.clone(), line) {
+            let message = format!("Duplicate of the country on line {}", first);
+            batch.failed.push(FailedRow::new(line, Some(country.code), message));
+            continue;
+        }
+
+        batch.rows.push(ImportRow { line, country });
+    }
+
+    Ok(batch)
+}
+
+type Record = (usize, Result<Country, String>);
+
+fn parse_ndjson(text: &str) -> Vec<Record> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, content)| !content.trim().is_empty())
+        .map(|(index, content)| (index + 1, serde_json::from_str(content).map_err(|e| e.to_string())))
+        .collect()
+}
+
+fn parse_json_array(text: &str) -> Result<Vec<Record>, ApiError> {
+    let elements = split_json_array(text).map_err(ApiError::InvalidBody)?;
+    Ok(elements
+        .into_iter()
+        .map(|(line, element)| (line, serde_json::from_str(element).map_err(|e| e.to_string())))
+        .collect())
+}
+
+/// Splits a JSON array into the source text of its elements, each with the
+/// line it starts on, without parsing the elements themselves
+fn split_json_array(text: &str) -> Result<Vec<(usize, &str)>, String> {
+    let not_array = || String::from("A JSON import must be an array of countries");
+
+    let start = text.find(|c: char| !c.is_whitespace()).ok_or_else(not_array)?;
+    if !text[start..].starts_with('[') {
+        return Err(not_array());
+    }
+
+    let mut elements = Vec::new();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut element_start: Option<usize> = None;
+    let mut end = None;
+
+    for (offset, c) in text.char_indices().skip_while(|(offset, _)| *offset < start) {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '[' | '{' => {
+                if depth == 1 && element_start.is_none() {
+                    element_start = Some(offset);
+                }
+                depth += 1;
+            }
+            ']' | '}' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    if let Some(from) = element_start.take() {
+                        elements.push((from, text[from..offset].trim()));
+                    }
+                    end = Some(offset);
+                    break;
+                }
+            }
+            ',' if depth == 1 => match element_start.take() {
+                Some(from) => elements.push((from, text[from..offset].trim())),
+                None => return Err(format!("Unexpected ',' on line {}", line_of(text, offset))),
+            },
+            c if c.is_whitespace() => {}
+            c => {
+                if c == '"' {
+                    in_string = true;
+                }
+                if depth == 1 && element_start.is_none() {
+                    element_start = Some(offset);
+                }
+            }
+        }
+    }
+
+    let end = end.ok_or_else(|| String::from("The JSON array is not terminated"))?;
+    if !text[end + 1..].trim().is_empty() {
+        return Err(format!("Unexpected content after the JSON array on line {}", line_of(text, end + 1)));
+    }
+
+    Ok(elements.into_iter().map(|(from, element)| (line_of(text, from), element)).collect())
+}
+
+/// 1-based line number of a byte offset
+fn line_of(text: &str, offset: usize) -> usize {
+    text[..offset].matches('\n').count() + 1
+}
+
+fn parse_csv(text: &str) -> Result<Vec<Record>, ApiError> {
+    let mut records = split_csv(text).map_err(ApiError::InvalidBody)?.into_iter();
+
+    let (_, header) = records
+        .next()
+        .ok_or_else(|| ApiError::InvalidBody(String::from("A CSV import needs a header row")))?;
+    let header: Vec<String> = header.iter().map(|name| name.trim().to_ascii_lowercase()).collect();
+
+    let missing: Vec<&str> = CSV_COLUMNS
+        .iter()
+        .copied()
+        .filter(|column| !header.iter().any(|name| name == column))
+        .collect();
+    if !missing.is_empty() {
+        return Err(ApiError::InvalidBody(format!("The CSV header is missing the column(s) {}", missing.join(", "))));
+    }
+    let index = |column: &str| header.iter().position(|name| name == column).unwrap();
+    let positions: Vec<usize> = CSV_COLUMNS.iter().map(|column| index(column)).collect();
+    let currencies_position = header.iter().position(|name| name == CSV_CURRENCIES_COLUMN);
+    let languages_position = header.iter().position(|name| name == CSV_LANGUAGES_COLUMN);
+    let region_code_position = header.iter().position(|name| name == CSV_REGION_CODE_COLUMN);
+    let geography_positions: Vec<Option<usize>> = CSV_GEOGRAPHY_COLUMNS
+        .iter()
+        .map(|column| header.iter().position(|name| name == column))
+        .collect();
+    let bbox_position = header.iter().position(|name| name == CSV_BBOX_COLUMN);
+    let calling_codes_position = header.iter().position(|name| name == CSV_CALLING_CODES_COLUMN);
+    let tlds_position = header.iter().position(|name| name == CSV_TLDS_COLUMN);
+    let timezones_position = header.iter().position(|name| name == CSV_TIMEZONES_COLUMN);
+    let weekend_position = header.iter().position(|name| name == CSV_WEEKEND_COLUMN);
+    let first_day_position = header.iter().position(|name| name == CSV_FIRST_DAY_OF_WEEK_COLUMN);
+    let driving_side_position = header.iter().position(|name| name == CSV_DRIVING_SIDE_COLUMN);
+
+    Ok(records
+        .map(|(line, fields)| {
+            if fields.len() != header.len() {
+                let message = format!("Expected {} fields, found {}", header.len(), fields.len());
+                return (line, Err(message));
+            }
+            let field = |i: usize| fields[positions[i]].clone();
+            let list = |position: Option<usize>| position.map(|position| parse_list(&fields[position])).unwrap_or_default();
+            let languages = match languages_position.map(|position| fields[position].trim()) {
+                Some(cell) if !cell.is_empty() => match serde_json::from_str(cell) {
+                    Ok(languages) => languages,
+                    Err(e) => return (line, Err(format!("Invalid languages: {}", e))),
+                },
+                _ => Vec::new(),
+            };
+            let mut geography = [None; 5];
+            for (value, (column, position)) in geography.iter_mut().zip(CSV_GEOGRAPHY_COLUMNS.iter().zip(&geography_positions)) {
+                *value = match position.map(|position| parse_number(&fields[position])) {
+                    Some(Err(e)) => return (line, Err(format!("Invalid {}: {}", column, e))),
+                    Some(Ok(number)) => number,
+                    None => None,
+                };
+            }
+            let bbox = match bbox_position.map(|position| fields[position].trim()) {
+                Some(cell) if !cell.is_empty() => {
+                    match cell.split(';').map(|edge| edge.trim().parse::<f64>()).collect() {
+                        Ok(edges) => Some(edges),
+                        Err(e) => return (line, Err(format!("Invalid bbox: {}", e))),
+                    }
+                }
+                _ => None,
+            };
+            let mut weekend = Vec::new();
+            for day in list(weekend_position) {
+                match Weekday::parse(&day) {
+                    Some(day) => weekend.push(day),
+                    None => return (line, Err(format!("Invalid weekend: unknown day {}", day))),
+                }
+            }
+            let first_day_of_week = match first_day_position.map(|position| fields[position].trim()) {
+                Some(cell) if !cell.is_empty() => match Weekday::parse(cell) {
+                    Some(day) => Some(day),
+                    None => return (line, Err(format!("Invalid first_day_of_week: unknown day {}", cell))),
+                },
+                _ => None,
+            };
+            let driving_side = match driving_side_position.map(|position| fields[position].trim()) {
+                Some(cell) if !cell.is_empty() => match DrivingSide::parse(cell) {
+                    Some(side) => Some(side),
+                    None => return (line, Err(format!("Invalid driving_side: expected left or right, found {}", cell))),
+                },
+                _ => None,
+            };
+            let [capital_latitude, capital_longitude, latitude, longitude, area_km2] = geography;
+            let country = Country {
+                code: field(0),
+                alpha3: field(1),
+                numeric: field(2),
+                name: field(3),
+                capital: field(4),
+                region: field(5),
+                region_code: region_code_position.map(|position| fields[position].clone()).unwrap_or_default(),
+                currency: field(6),
+                currencies: currencies_position
+                    .map(|position| fields[position].split(';').map(String::from).collect())
+                    .unwrap_or_default(),
+                languages,
+                capital_latitude,
+                capital_longitude,
+                latitude,
+                longitude,
+                area_km2,
+                bbox,
+                calling_codes: list(calling_codes_position),
+                tlds: list(tlds_position),
+                timezones: list(timezones_position),
+                weekend,
+                first_day_of_week,
+                driving_side,
+            };
+            (line, Ok(country))
+        })
+        .collect())
+}
+
+/// Splits a CSV cell listing values separated by `;`; an empty cell is an empty list
+fn parse_list(cell: &str) -> Vec<String> {
+    cell.split(';').map(str::trim).filter(|value| !value.is_empty()).map(String::from).collect()
+}
+
+/// Parses an optional number from a CSV cell; an empty cell is `None`
+fn parse_number(cell: &str) -> Result<Option<f64>, std::num::ParseFloatError> {
+    match cell.trim() {
+        "" => Ok(None),
+        number => number.parse().map(Some),
+    }
+}
+
+/// Splits RFC 4180 CSV into records, each with the line it starts on
+///
+/// Quoted fields may contain commas, doubled quotes and line breaks. Blank
+/// lines are ignored.
+fn split_csv(text: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut in_quotes = false;
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() && !quoted => {
+                in_quotes = true;
+                quoted = true;
+            }
+            '"' => return Err(format!("Unexpected quote in an unquoted CSV field on line {}", line)),
+            ',' => {
+                fields.push(std::mem::take(&mut field));
+                quoted = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                if quoted || !field.is_empty() || !fields.is_empty() {
+                    fields.push(std::mem::take(&mut field));
+                    records.push((record_line, std::mem::take(&mut fields)));
+                }
+                quoted = false;
+                line += 1;
+                record_line = line;
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(format!("Unterminated quoted CSV field starting on line {}", record_line));
+    }
+    if quoted || !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((record_line, fields));
+    }
+
+    Ok(records)
+}
+
+/// Storage operations an import is made of, run inside one transaction
+pub(crate) trait ImportTarget {
+    /// Returns the stored country with alpha-2 code `code`
+    fn find(&mut self, code: &str) -> Result<Option<Country>, ApiError>;
+
+    /// Returns the alpha-2 codes of every stored country
+    fn codes(&mut self) -> Result<Vec<String>, ApiError>;
+
+    /// Stores a new country; [`ApiError::Conflict`] only fails this row
+    fn insert(&mut self, country: &Country) -> Result<(), ApiError>;
+
+    /// Overwrites a country; [`ApiError::Conflict`] only fails this row
+    fn update(&mut self, country: &Country) -> Result<(), ApiError>;
+
+    /// Removes the country with alpha-2 code `code`
+    fn delete(&mut self, code: &str) -> Result<(), ApiError>;
+}
+
+/// Writes a batch to `target` and reports what happened to every row
+///
+/// For `replace-all`, countries missing from the dataset are deleted first so
+/// that their alpha-3 and numeric codes can be reused by the imported rows.
+/// Countries named by rows that failed are kept.
+pub(crate) fn apply(target: &mut impl ImportTarget, batch: ImportBatch) -> Result<ImportReport, ApiError> {
+    let mut report = ImportReport {
+        failed: batch.failed,
+        ..ImportReport::default()
+    };
+
+    if batch.mode == ImportMode::ReplaceAll {
+        let keep: HashSet<&str> = batch
+            .rows
+            .iter()
+            .map(|row| row.country.code.as_str())
+            .chain(report.failed.iter().filter_map(|row| row.code.as_deref()))
+            .collect();
+        for code in target.codes()? {
+            if !keep.contains(code.as_str()) {
+                target.delete(&code)?;
+                report.deleted.push(code);
+            }
+        }
+    }
+
+    for ImportRow { line, mut country } in batch.rows {
+        let imported = ImportedRow { line, code: country.code.clone() };
+        let written = match target.find(&country.code)? {
+            None => validation::validate_country(&country)
+                .and_then(|_| target.insert(&country))
+                .map(|_| &mut report.inserted),
+            Some(_) if batch.mode == ImportMode::InsertOnly => Ok(&mut report.skipped),
+            Some(existing) => {
+                keep_stored_fields(&mut country, &existing);
+                if let Err(e) = validation::validate_country_update(&country, &existing) {
+                    Err(e)
+                } else if existing == country {
+                    Ok(&mut report.skipped)
+                } else {
+                    target.update(&country).map(|_| &mut report.updated)
+                }
+            }
+        };
+
+        match written {
+            Ok(list) => list.push(imported),
+            Err(ApiError::Validation(errors)) => report.failed.push(FailedRow {
+                line,
+                code: Some(country.code),
+                message: String::from("The country failed validation"),
+                errors,
+            }),
+            Err(ApiError::Conflict(_)) => {
+                // The alpha-2 code is free or already this country's, so the clash is elsewhere
+                let message = format!(
+                    "Another country already uses the alpha-3 code {} or the numeric code {}",
+                    country.alpha3, country.numeric
+                );
+                report.failed.push(FailedRow::new(line, Some(country.code), message));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    report.failed.sort_by_key(|row| row.line);
+    Ok(report)
+}
+
+/// Gives the optional fields a row leaves empty the values of the stored country
+///
+/// Languages, calling codes, TLDs, time zones, the working week and the
+/// driving side are kept one by one; the coordinates, area and bounding box
+/// are kept together, and only if the row has none of them.
+fn keep_stored_fields(country: &mut Country, stored: &Country) {
+    fn keep<T: Clone>(value: &mut Vec<T>, stored: &[T]) {
+        if value.is_empty() {
+            *value = stored.to_vec();
+        }
+    }
+
+    keep(&mut country.languages, &stored.languages);
+    keep(&mut country.calling_codes, &stored.calling_codes);
+    keep(&mut country.tlds, &stored.tlds);
+    keep(&mut country.timezones, &stored.timezones);
+    keep(&mut country.weekend, &stored.weekend);
+    country.first_day_of_week = country.first_day_of_week.or(stored.first_day_of_week);
+    country.driving_side = country.driving_side.or(stored.driving_side);
+    if !has_geography(country) {
+        country.capital_latitude = stored.capital_latitude;
+        country.capital_longitude = stored.capital_longitude;
+        country.latitude = stored.latitude;
+        country.longitude = stored.longitude;
+        country.area_km2 = stored.area_km2;
+        country.bbox = stored.bbox.clone();
+    }
+}
+
+/// Whether a country carries any coordinates, area or bounding box
+fn has_geography(country: &Country) -> bool {
+    [country.capital_latitude, country.capital_longitude, country.latitude, country.longitude, country.area_km2]
+        .iter()
+        .any(Option::is_some)
+        || country.bbox.is_some()
+}
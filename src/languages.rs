@@ -0,0 +1,72 @@
+//! ISO 639 languages spoken officially somewhere
+//!
+//! The list is compiled into the binary from `data/languages.json`. It is
+//! loaded into the `languages` table when a database is seeded, and the
+//! languages of country payloads are validated against it.
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// An ISO 639 language
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct Language {
+    /// The ISO 639-1 code (two letters), or the ISO 639-3 code (three letters)
+    /// of languages without one
+    pub code: String,
+    /// The ISO 639-3 code (three letters), or the ISO 639-2 code of language
+    /// collections such as `ber`
+    pub alpha3: String,
+    /// The English name of the language
+    pub name: String,
+}
+
+/// How a language is acknowledged by a country
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LanguageStatus {
+    /// An official or national language used by the state
+    Official,
+    /// A recognized regional or minority language
+    Recognized,
+}
+
+impl LanguageStatus {
+    /// Name of the status as stored and serialized
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LanguageStatus::Official => "official",
+            LanguageStatus::Recognized => "recognized",
+        }
+    }
+}
+
+/// A language of a country
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct CountryLanguage {
+    /// The code of the language, as in [`Language::code`]
+    pub code: String,
+    /// Whether the language is official or only recognized
+    pub status: LanguageStatus,
+}
+
+/// Languages shipped with the API
+const LANGUAGE_DATA: &str = include_str!("../data/languages.json");
+
+/// Every language shipped with the API, ordered by code
+pub fn language_data() -> &'static [Language] {
+    static LANGUAGES: OnceLock<Vec<Language>> = OnceLock::new();
+    LANGUAGES.get_or_init(|| serde_json::from_str(LANGUAGE_DATA).expect("data/languages.json is a valid language list"))
+}
+
+/// Looks a language up by its code or alpha-3 code, as normalized by
+/// [`normalize_language_code`]
+pub fn find_language(code: &str) -> Option<&'static Language> {
+    language_data().iter().find(|language| language.code == code || language.alpha3 == code)
+}
+
+/// Normalizes a language code supplied by a client: trims it and lowercases it
+pub fn normalize_language_code(code: &str) -> String {
+    code.trim().to_lowercase()
+}
@@ -1,534 +1,2631 @@
-use actix_web::{get, post, put, delete, web, HttpResponse, Responder};
+use std::sync::Arc;
+
+use actix_web::http::header;
+use actix_web::{get, post, put, patch, delete, web, HttpRequest, HttpResponse};
+use chrono::Datelike;
 use rusqlite::{params, Connection, Result as SqliteResult};
 use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
-use utoipa::{OpenApi, ToSchema};
+use utoipa::openapi::{ContentBuilder, ObjectBuilder, SchemaType};
+use utoipa::{IntoParams, Modify, OpenApi, ToSchema};
 use utoipa_swagger_ui::SwaggerUi;
 
+pub mod borders;
+pub mod boundaries;
+pub mod calendar;
+pub mod currencies;
+pub mod db;
+pub mod error;
+pub mod export;
+pub mod geo;
+pub mod geojson;
+pub mod import;
+pub mod languages;
+pub mod migrations;
+pub mod phone;
+pub mod regions;
+pub mod repository;
+pub mod search;
+pub mod settings;
+pub mod timezones;
+pub mod validation;
+
+pub use boundaries::Boundary;
+pub use calendar::{BusinessDays, Holiday, HolidayCheck, Weekday};
+pub use currencies::Currency;
+pub use db::{create_pool, DbPool};
+pub use error::{ApiError, ProblemDetails};
+pub use export::{ExportFormat, FormatQuery};
+pub use geo::{CapitalDistance, NearbyCountry};
+pub use geojson::{Feature, FeatureCollection, GeometryKind};
+pub use import::{FailedRow, ImportFormat, ImportMode, ImportReport, ImportedRow};
+pub use languages::{CountryLanguage, Language, LanguageStatus};
+pub use migrations::MigrationError;
+pub use phone::{PhoneNumberRequest, PhoneValidation};
+pub use regions::{Region, RegionDetails, RegionLevel};
+pub use repository::{CountryRepository, InMemoryRepository, SqliteRepository};
+
+use repository::{CountryFilter, ListOptions, Page, SortField, SortKey};
+pub use search::SearchResult;
+pub use settings::Settings;
+pub use timezones::{LocalTime, TimeZoneInfo};
+pub use validation::FieldError;
+
 /// Represents a country with its basic information
 /// 
 /// This struct contains the essential information about a country including
-/// its name, country code, capital city, geographical region, and currency.
+/// its name, country code, capital city, geographical region, currencies, languages,
+/// coordinates, area, bounding box, calling codes, top-level domains, time zones,
+/// working week and driving side.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
 pub struct Country {
     /// The full name of the country
     pub name: String,
     /// The ISO 3166-1 alpha-2 country code (two letters)
     pub code: String,
+    /// The ISO 3166-1 alpha-3 country code (three letters)
+    pub alpha3: String,
+    /// The ISO 3166-1 numeric country code (three digits, zero-padded)
+    pub numeric: String,
     /// The name of the capital city
     pub capital: String,
     /// The geographical region where the country is located
     pub region: String,
-    /// The currency code used in the country
+    /// The UN M49 code of the most specific region the country belongs to,
+    /// e.g. `155` (Western Europe); defaults to the region listed for the
+    /// country in `data/regions.json`, and is empty for countries in none
+    #[serde(default)]
+    pub region_code: String,
+    /// The ISO 4217 code of the main currency used in the country
     pub currency: String,
+    /// The ISO 4217 codes of every legal tender, starting with `currency`;
+    /// defaults to just `currency`
+    #[serde(default)]
+    pub currencies: Vec<String>,
+    /// The ISO 639 languages of the country, official ones first
+    #[serde(default)]
+    pub languages: Vec<CountryLanguage>,
+    /// Latitude of the capital in decimal degrees (WGS 84)
+    #[serde(default)]
+    pub capital_latitude: Option<f64>,
+    /// Longitude of the capital in decimal degrees (WGS 84)
+    #[serde(default)]
+    pub capital_longitude: Option<f64>,
+    /// Latitude of the geographic center of the country in decimal degrees
+    #[serde(default)]
+    pub latitude: Option<f64>,
+    /// Longitude of the geographic center of the country in decimal degrees
+    #[serde(default)]
+    pub longitude: Option<f64>,
+    /// Total area in square kilometres
+    #[serde(default)]
+    pub area_km2: Option<f64>,
+    /// Bounding box as `[west, south, east, north]` in decimal degrees, as in
+    /// GeoJSON; west is greater than east when the country spans the antimeridian
+    #[serde(default)]
+    pub bbox: Option<Vec<f64>>,
+    /// International calling codes in E.164 form, e.g. `+49`; countries
+    /// sharing a country code are told apart by area code, e.g. `+1 809`
+    #[serde(default)]
+    pub calling_codes: Vec<String>,
+    /// Country code top-level domains, with the leading dot, e.g. `.de`
+    #[serde(default)]
+    pub tlds: Vec<String>,
+    /// IANA time zones in use, e.g. `Europe/Berlin`, starting with the zone
+    /// of the capital
+    #[serde(default)]
+    pub timezones: Vec<String>,
+    /// The days of the weekend in week order, e.g. `["friday", "saturday"]`
+    /// in most Gulf states; empty if unknown, in which case business days
+    /// skip Saturdays and Sundays
+    #[serde(default)]
+    pub weekend: Vec<Weekday>,
+    /// The day weeks start on in calendars
+    #[serde(default)]
+    pub first_day_of_week: Option<Weekday>,
+    /// The side of the road traffic keeps to
+    #[serde(default)]
+    pub driving_side: Option<DrivingSide>,
+}
+
+/// The side of the road traffic keeps to
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DrivingSide {
+    Left,
+    Right,
+}
+
+impl DrivingSide {
+    /// Name of the side as stored and serialized
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DrivingSide::Left => "left",
+            DrivingSide::Right => "right",
+        }
+    }
+
+    /// Parses the name of a side, ignoring case and surrounding whitespace
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "left" => Some(DrivingSide::Left),
+            "right" => Some(DrivingSide::Right),
+            _ => None,
+        }
+    }
 }
 
-/// Shared state for database connection
+/// Shared state holding the country repository
 pub struct AppState {
-    pub db: Mutex<Connection>,
+    pub repo: Arc<dyn CountryRepository>,
 }
 
-/// Initialize the SQLite database
-pub fn init_db() -> SqliteResult<Connection> {
-    let conn = Connection::open("countries.db")?;
-    
-    // Create countries table if it doesn't exist
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS countries (
-            code TEXT PRIMARY KEY,
-            name TEXT NOT NULL,
-            capital TEXT NOT NULL,
-            region TEXT NOT NULL,
-            currency TEXT NOT NULL
-        )",
-        [],
-    )?;
+impl AppState {
+    /// Creates the state around a country repository
+    pub fn new<R: CountryRepository + 'static>(repo: R) -> Self {
+        AppState { repo: Arc::new(repo) }
+    }
     
+    /// Runs `f` with the repository on actix's blocking thread pool
+    ///
+    /// Storage calls never block the async workers, and a panic inside `f`
+    /// only fails the current request: a pooled connection goes back to the
+    /// pool (rolling back any open transaction) instead of poisoning shared state.
+    pub async fn with_repo<T, F>(&self, f: F) -> Result<T, ApiError>
+    where
+        F: FnOnce(&dyn CountryRepository) -> Result<T, ApiError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let repo = Arc::clone(&self.repo);
+        web::block(move || f(repo.as_ref())).await?
+    }
+}
+
+/// Initialize the SQLite database
+///
+/// Opens (creating if needed) the database at `path`; pass `":memory:"` for a
+/// private in-memory database.
+pub fn init_db(path: &str) -> Result<Connection, MigrationError> {
+    let conn = Connection::open(path)?;
+    init_schema(&conn)?;
     Ok(conn)
 }
 
+/// Brings the database schema up to date by applying pending migrations
+pub fn init_schema(conn: &Connection) -> Result<(), MigrationError> {
+    migrations::migrate(conn)?;
+    Ok(())
+}
+
+/// ISO 3166-1 countries shipped with the API, compiled into the binary
+const SEED_DATA: &str = include_str!("../data/countries.json");
+
+/// One entry of the embedded country list
+#[derive(Debug, Deserialize)]
+pub struct SeedCountry {
+    #[serde(flatten)]
+    pub country: Country,
+    /// Other names the country is known by, indexed for search
+    pub alt_names: Vec<String>,
+}
+
+/// Parses the embedded list of every ISO 3166-1 country
+pub fn seed_data() -> Vec<SeedCountry> {
+    let mut entries: Vec<SeedCountry> = serde_json::from_str(SEED_DATA).expect("data/countries.json is a valid country list");
+    // The M49 region of every country is listed with the regions
+    for entry in &mut entries {
+        entry.country.region_code = regions::region_of(&entry.country.code).unwrap_or_default().to_string();
+    }
+    entries
+}
+
+/// Countries inserted by [`seed_countries`] into an empty database
+pub fn default_countries() -> Vec<Country> {
+    seed_data().into_iter().map(|entry| entry.country).collect()
+}
+
 /// Seeds the database with initial country data
 ///
-/// Populates the database with predefined country data if it's empty.
+/// Refreshes the `currencies`, `languages`, `regions`, `borders` and `holiday_rules` reference tables on every call,
+/// and populates the database with every ISO 3166-1 country, its legal
+/// tenders, its languages and its alternative names if it's empty. Stored
+/// countries without a boundary get the bundled one, so boundaries loaded
+/// from another dataset are kept.
 pub fn seed_countries(conn: &mut Connection) -> SqliteResult<()> {
+    let tx = conn.transaction()?;
+    
+    for currency in currencies::currency_data() {
+        tx.execute(
+            "INSERT INTO currencies (code, numeric, name, symbol, minor_units) VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (code) DO UPDATE SET
+                 numeric = excluded.numeric, name = excluded.name,
+                 symbol = excluded.symbol, minor_units = excluded.minor_units",
+            params![currency.code, currency.numeric, currency.name, currency.symbol, currency.minor_units],
+        )?;
+    }
+    for language in languages::language_data() {
+        tx.execute(
+            "INSERT INTO languages (code, alpha3, name) VALUES (?1, ?2, ?3)
+             ON CONFLICT (code) DO UPDATE SET alpha3 = excluded.alpha3, name = excluded.name",
+            params![language.code, language.alpha3, language.name],
+        )?;
+    }
+    tx.execute("DELETE FROM borders", [])?;
+    for [a, b] in borders::border_data() {
+        tx.execute(
+            "INSERT INTO borders (country_code, neighbor_code) VALUES (?1, ?2), (?2, ?1)",
+            params![a, b],
+        )?;
+    }
+    tx.execute("DELETE FROM holiday_rules", [])?;
+    for (code, rules) in &calendar::holiday_data().countries {
+        for (position, rule) in rules.iter().enumerate() {
+            let date_rule = serde_json::to_string(&rule.date).expect("holiday rules serialize to JSON");
+            tx.execute(
+                "INSERT INTO holiday_rules (country_code, position, name, date_rule, observed, since, until)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![code, position, rule.name, date_rule, rule.observed.map(|o| o.as_str()), rule.since, rule.until],
+            )?;
+        }
+    }
+    for region in regions::region_data() {
+        tx.execute(
+            "INSERT INTO regions (code, name, parent_code, level) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (code) DO UPDATE SET
+                 name = excluded.name, parent_code = excluded.parent_code, level = excluded.level",
+            params![region.code, region.name, region.parent, region.level.as_str()],
+        )?;
+    }
+    
     // Check if the table is empty
-    let count: i64 = conn.query_row("SELECT COUNT(*) FROM countries", [], |row| row.get(0))?;
+    let count: i64 = tx.query_row("SELECT COUNT(*) FROM countries", [], |row| row.get(0))?;
     
     if count == 0 {
-        let countries = vec![
-            Country {
-                name: String::from("United States"),
-                code: String::from("US"),
-                capital: String::from("Washington, D.C."),
-                region: String::from("North America"),
-                currency: String::from("USD"),
-            },
-            Country {
-                name: String::from("Canada"),
-                code: String::from("CA"),
-                capital: String::from("Ottawa"),
-                region: String::from("North America"),
-                currency: String::from("CAD"),
-            },
-            Country {
-                name: String::from("United Kingdom"),
-                code: String::from("GB"),
-                capital: String::from("London"),
-                region: String::from("Europe"),
-                currency: String::from("GBP"),
-            },
-            Country {
-                name: String::from("Germany"),
-                code: String::from("DE"),
-                capital: String::from("Berlin"),
-                region: String::from("Europe"),
-                currency: String::from("EUR"),
-            },
-            Country {
-                name: String::from("France"),
-                code: String::from("FR"),
-                capital: String::from("Paris"),
-                region: String::from("Europe"),
-                currency: String::from("EUR"),
-            },
-            Country {
-                name: String::from("Japan"),
-                code: String::from("JP"),
-                capital: String::from("Tokyo"),
-                region: String::from("Asia"),
-                currency: String::from("JPY"),
-            },
-            Country {
-                name: String::from("Australia"),
-                code: String::from("AU"),
-                capital: String::from("Canberra"),
-                region: String::from("Oceania"),
-                currency: String::from("AUD"),
-            },
-            Country {
-                name: String::from("Brazil"),
-                code: String::from("BR"),
-                capital: String::from("Brasília"),
-                region: String::from("South America"),
-                currency: String::from("BRL"),
-            },
-            Country {
-                name: String::from("South Africa"),
-                code: String::from("ZA"),
-                capital: String::from("Pretoria"),
-                region: String::from("Africa"),
-                currency: String::from("ZAR"),
-            },
-            Country {
-                name: String::from("India"),
-                code: String::from("IN"),
-                capital: String::from("New Delhi"),
-                region: String::from("Asia"),
-                currency: String::from("INR"),
-            },
-        ];
-        
-        let tx = conn.transaction()?;
-        for country in countries {
+        for SeedCountry { country, alt_names } in seed_data() {
+            repository::sqlite::insert_country(&tx, &country)?;
+            for name in alt_names {
+                tx.execute(
+                    "INSERT INTO country_alt_names (code, name) VALUES (?1, ?2)",
+                    params![country.code, name],
+                )?;
+            }
+        }
+    }
+    
+    for boundary in boundaries::boundary_data() {
+        let missing: bool = tx.query_row(
+            "SELECT EXISTS (SELECT 1 FROM countries WHERE code = ?1)
+                 AND NOT EXISTS (SELECT 1 FROM boundary_polygons WHERE country_code = ?1)",
+            params![boundary.code],
+            |row| row.get(0),
+        )?;
+        if !missing {
+            continue;
+        }
+        for polygon in &boundary.polygons {
+            let coordinates = serde_json::to_string(polygon).expect("boundaries serialize to JSON");
             tx.execute(
-                "INSERT INTO countries (code, name, capital, region, currency) VALUES (?1, ?2, ?3, ?4, ?5)",
-                params![country.code, country.name, country.capital, country.region, country.currency],
+                "INSERT INTO boundary_polygons (country_code, coordinates) VALUES (?1, ?2)",
+                params![boundary.code, coordinates],
             )?;
         }
-        tx.commit()?;
     }
     
-    Ok(())
+    tx.commit()
 }
 
-/// Endpoint handler that returns all countries
+/// Default number of countries returned by `GET /countries` when no `limit` is given
+pub const DEFAULT_PAGE_LIMIT: u32 = 50;
+
+/// Largest `limit` accepted by `GET /countries`
+pub const MAX_PAGE_LIMIT: u32 = 250;
+
+/// Query parameters accepted by `GET /countries`
+///
+/// All filters are optional and combined with AND. Filters and sorting are
+/// applied before paging, so `X-Total-Count` reflects the filtered set.
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CountryListQuery {
+    /// Maximum number of countries to return (1 to 250, defaults to 50)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    /// Number of countries to skip before the first returned one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u32>,
+    /// Comma-separated sort fields, each optionally prefixed with `-` for
+    /// descending order (e.g. `region,-name`). Defaults to `code`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+    /// Only return countries in this region (case-insensitive)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// Only return countries using this currency code (case-insensitive)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    /// Only return countries whose name contains this text (case-insensitive)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_contains: Option<String>,
+    /// Only return countries whose weekend includes every one of these
+    /// comma-separated days (e.g. `friday,saturday`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weekend: Option<String>,
+    /// Only return countries whose weeks start on this day (e.g. `sunday`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_day_of_week: Option<String>,
+    /// Only return countries driving on this side of the road, `left` or `right`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driving_side: Option<String>,
+    /// Response format, overriding the `Accept` header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<ExportFormat>,
+}
+
+/// Builds the `Link` header value pointing at the neighbouring pages of a listing
+fn pagination_links(path: &str, query: &CountryListQuery, limit: u32, offset: u32, total: u32) -> String {
+    let page_url = |page_offset: u32| {
+        let page_query = CountryListQuery {
+            limit: Some(limit),
+            offset: Some(page_offset),
+            sort: query.sort.clone(),
+            region: query.region.clone(),
+            currency: query.currency.clone(),
+            name_contains: query.name_contains.clone(),
+            weekend: query.weekend.clone(),
+            first_day_of_week: query.first_day_of_week.clone(),
+            driving_side: query.driving_side.clone(),
+            format: query.format,
+        };
+        format!("{}?{}", path, serde_urlencoded::to_string(&page_query).unwrap_or_default())
+    };
+    
+    let last_offset = if total == 0 { 0 } else { (total - 1) / limit * limit };
+    let mut links = vec![format!("<{}>; rel=\"first\"", page_url(0))];
+    
+    if offset > 0 {
+        links.push(format!("<{}>; rel=\"prev\"", page_url(offset.saturating_sub(limit))));
+    }
+    if offset.saturating_add(limit) < total {
+        links.push(format!("<{}>; rel=\"next\"", page_url(offset + limit)));
+    }
+    links.push(format!("<{}>; rel=\"last\"", page_url(last_offset)));
+    
+    links.join(", ")
+}
+
+/// Endpoint handler that returns a page of countries
 ///
 /// # Route
 /// `GET /countries`
 ///
+/// # Parameters
+/// * `query` - Paging (`limit`, `offset`), ordering (`sort`) and filtering
+///   (`region`, `currency`, `name_contains`, `weekend`, `first_day_of_week`,
+///   `driving_side`) options, and the response `format`
+///
 /// # Returns
-/// A JSON array containing all countries in the database
+/// * `200 OK` with an array of countries in the negotiated format, plus
+///   `X-Total-Count` and `Link` headers describing the full result set
+/// * `400 Bad Request` if `limit` is out of range, `sort` names an unknown
+///   field or a weekday or driving side filter is not recognized
+/// * `406 Not Acceptable` if the `Accept` header names no supported format
 #[utoipa::path(
     get,
     path = "/countries",
+    params(CountryListQuery),
     responses(
-        (status = 200, description = "List of all countries", body = [Country]),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "Page of countries", body = [Country],
+            headers(
+                ("X-Total-Count" = u32, description = "Number of countries matching the filters"),
+                ("Link" = String, description = "RFC 8288 links to the first, previous, next and last pages")
+            )
+        ),
+        (status = 400, description = "Invalid paging, sorting or filtering parameters",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
     )
 )]
 #[get("/countries")]
-pub async fn all_countries(data: web::Data<AppState>) -> impl Responder {
-    let conn = data.db.lock().unwrap();
+pub async fn all_countries(
+    req: HttpRequest,
+    query: web::Query<CountryListQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let query = query.into_inner();
+    let format = export::negotiate(&req, query.format)?;
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+    let offset = query.offset.unwrap_or(0);
+    
+    if limit == 0 || limit > MAX_PAGE_LIMIT {
+        return Err(ApiError::BadRequest(format!("limit must be between 1 and {}", MAX_PAGE_LIMIT)));
+    }
     
-    let mut stmt = match conn.prepare("SELECT code, name, capital, region, currency FROM countries") {
-        Ok(stmt) => stmt,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    let weekday = |name: &str| {
+        Weekday::parse(name).ok_or_else(|| ApiError::BadRequest(format!("{} is not a day of the week", name.trim())))
+    };
+    let weekend = match &query.weekend {
+        Some(days) => days.split(',').map(weekday).collect::<Result<Vec<_>, _>>()?,
+        None => Vec::new(),
     };
+    let first_day_of_week = query.first_day_of_week.as_deref().map(weekday).transpose()?;
+    let driving_side = query
+        .driving_side
+        .as_deref()
+        .map(|side| DrivingSide::parse(side).ok_or_else(|| ApiError::BadRequest(String::from("driving_side must be left or right"))))
+        .transpose()?;
     
-    let country_iter = match stmt.query_map([], |row| {
-        Ok(Country {
-            code: row.get(0)?,
-            name: row.get(1)?,
-            capital: row.get(2)?,
-            region: row.get(3)?,
-            currency: row.get(4)?,
-        })
-    }) {
-        Ok(countries) => countries,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    let options = ListOptions {
+        filter: CountryFilter {
+            region: query.region.clone(),
+            currency: query.currency.clone(),
+            name_contains: query.name_contains.clone(),
+            weekend,
+            first_day_of_week,
+            driving_side,
+        },
+        sort: repository::parse_sort(query.sort.as_deref()).map_err(ApiError::BadRequest)?,
+        limit,
+        offset,
     };
     
-    let mut countries = Vec::new();
-    for country in country_iter {
-        match country {
-            Ok(c) => countries.push(c),
-            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
-        }
+    let Page { total, countries } = data.with_repo(move |repo| repo.list(&options)).await?;
+    
+    let mut response = HttpResponse::Ok();
+    response
+        .insert_header(("X-Total-Count", total.to_string()))
+        .insert_header(("Link", pagination_links(req.path(), &query, limit, offset, total)));
+    export::respond(response, format, export::COUNTRIES, &countries)
+}
+
+/// Default number of results returned by `GET /countries/search`
+pub const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// Largest `limit` accepted by `GET /countries/search`
+pub const MAX_SEARCH_LIMIT: usize = 50;
+
+/// Query parameters accepted by `GET /countries/search`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchQuery {
+    /// Free-text query matched against names, capitals and alternative names
+    pub q: String,
+    /// Maximum number of results to return (1 to 50, defaults to 10)
+    pub limit: Option<usize>,
+    /// Response format, overriding the `Accept` header
+    pub format: Option<ExportFormat>,
+}
+
+/// Endpoint handler that searches countries by free text
+///
+/// # Route
+/// `GET /countries/search`
+///
+/// # Parameters
+/// * `query` - The search text (`q`), an optional result `limit` and the
+///   response `format`
+///
+/// # Returns
+/// * `200 OK` with an array of matching countries, each with a relevance
+///   `score`, best match first. Misspelled words such as "germny" still match.
+/// * `400 Bad Request` if `q` is blank or `limit` is out of range
+/// * `406 Not Acceptable` if the `Accept` header names no supported format
+#[utoipa::path(
+    get,
+    path = "/countries/search",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Matching countries ordered by relevance", body = [SearchResult]),
+        (status = 400, description = "Missing query or invalid limit",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/countries/search")]
+pub async fn country_search(
+    req: HttpRequest,
+    query: web::Query<SearchQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let query = query.into_inner();
+    let format = export::negotiate(&req, query.format)?;
+    let limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+    
+    if query.q.trim().is_empty() {
+        return Err(ApiError::BadRequest(String::from("Query parameter q must not be empty")));
+    }
+    if limit == 0 || limit > MAX_SEARCH_LIMIT {
+        return Err(ApiError::BadRequest(format!("limit must be between 1 and {}", MAX_SEARCH_LIMIT)));
     }
     
-    HttpResponse::Ok().json(countries)
+    let results = data.with_repo(move |repo| repo.search(&query.q, limit)).await?;
+    
+    export::respond(HttpResponse::Ok(), format, export::SEARCH_RESULTS, &results)
 }
 
-/// Endpoint handler that returns a specific country by its code
+/// Default number of countries returned by `GET /countries/nearest`
+pub const DEFAULT_NEAREST_LIMIT: usize = 10;
+
+/// Largest `limit` accepted by `GET /countries/nearest`
+pub const MAX_NEAREST_LIMIT: usize = 250;
+
+/// Query parameters accepted by `GET /countries/nearest`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NearestQuery {
+    /// Latitude of the point in decimal degrees (-90 to 90)
+    pub lat: f64,
+    /// Longitude of the point in decimal degrees (-180 to 180)
+    pub lon: f64,
+    /// Maximum number of countries to return (1 to 250, defaults to 10)
+    pub limit: Option<usize>,
+    /// Response format, overriding the `Accept` header
+    pub format: Option<ExportFormat>,
+}
+
+/// Endpoint handler that returns the countries nearest to a point
 ///
 /// # Route
-/// `GET /countries/{code}`
+/// `GET /countries/nearest`
 ///
 /// # Parameters
-/// * `path` - The country code (e.g., "US", "GB") extracted from the URL path
+/// * `query` - The point (`lat` and `lon`), an optional result `limit` and
+///   the response `format`
 ///
 /// # Returns
-/// * `200 OK` with JSON data if the country is found
-/// * `404 Not Found` with an error message if the country code doesn't exist
+/// * `200 OK` with an array of countries, each with the great-circle
+///   `distance_km` from the point to its geographic center, nearest first
+/// * `400 Bad Request` if the point is missing or out of range, or `limit`
+///   is out of range
+/// * `406 Not Acceptable` if the `Accept` header names no supported format
 #[utoipa::path(
     get,
-    path = "/countries/{code}",
-    params(
-        ("code" = String, Path, description = "ISO 3166-1 alpha-2 country code")
-    ),
+    path = "/countries/nearest",
+    params(NearestQuery),
     responses(
-        (status = 200, description = "Country found", body = Country),
-        (status = 404, description = "Country not found"),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "Countries ordered by distance from the point", body = [NearbyCountry]),
+        (status = 400, description = "Missing or invalid point, or invalid limit",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
     )
 )]
-#[get("/countries/{code}")]
-pub async fn country_by_code(path: web::Path<String>, data: web::Data<AppState>) -> impl Responder {
-    let code = path.into_inner().to_uppercase();
-    let conn = data.db.lock().unwrap();
-    
-    let result = conn.query_row(
-        "SELECT code, name, capital, region, currency FROM countries WHERE code = ?1",
-        params![code],
-        |row| {
-            Ok(Country {
-                code: row.get(0)?,
-                name: row.get(1)?,
-                capital: row.get(2)?,
-                region: row.get(3)?,
-                currency: row.get(4)?,
-            })
-        },
-    );
+#[get("/countries/nearest")]
+pub async fn countries_nearest(
+    req: HttpRequest,
+    query: web::Query<NearestQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let query = query.into_inner();
+    let format = export::negotiate(&req, query.format)?;
+    let limit = query.limit.unwrap_or(DEFAULT_NEAREST_LIMIT);
+    let point = geo::Point::new(query.lat, query.lon);
     
-    match result {
-        Ok(country) => HttpResponse::Ok().json(country),
-        Err(rusqlite::Error::QueryReturnedNoRows) => {
-            HttpResponse::NotFound().body(format!("Country with code {} not found", code))
-        },
-        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    if !point.is_valid() {
+        return Err(ApiError::BadRequest(String::from(
+            "lat must be between -90 and 90 and lon between -180 and 180",
+        )));
+    }
+    if limit == 0 || limit > MAX_NEAREST_LIMIT {
+        return Err(ApiError::BadRequest(format!("limit must be between 1 and {}", MAX_NEAREST_LIMIT)));
     }
+    
+    let countries = data.with_repo(move |repo| repo.nearest(point, limit)).await?;
+    
+    export::respond(HttpResponse::Ok(), format, export::COUNTRIES, &countries)
 }
 
-/// Endpoint handler that returns all unique geographical regions
+/// Query parameters accepted by `GET /countries/at`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PointQuery {
+    /// Latitude of the point in decimal degrees (-90 to 90)
+    pub lat: f64,
+    /// Longitude of the point in decimal degrees (-180 to 180)
+    pub lon: f64,
+    /// Response format, overriding the `Accept` header
+    pub format: Option<ExportFormat>,
+}
+
+/// Endpoint handler that returns the country containing a point
 ///
 /// # Route
-/// `GET /regions`
+/// `GET /countries/at`
+///
+/// # Parameters
+/// * `query` - The point (`lat` and `lon`) and the response `format`
 ///
 /// # Returns
-/// A JSON array containing all unique regions from the countries database
-/// (e.g., "Europe", "Asia", "North America")
+/// * `200 OK` with the country whose boundary contains the point
+/// * `400 Bad Request` if the point is missing or out of range
+/// * `404 Not Found` if no loaded boundary contains the point, e.g. at sea
+/// * `406 Not Acceptable` if the `Accept` header names no supported format
 #[utoipa::path(
     get,
-    path = "/regions",
+    path = "/countries/at",
+    params(PointQuery),
     responses(
-        (status = 200, description = "List of all geographical regions", body = [String]),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "Country containing the point", body = Country),
+        (status = 400, description = "Missing or invalid point",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No country contains the point",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
     )
 )]
-#[get("/regions")]
-pub async fn get_regions(data: web::Data<AppState>) -> impl Responder {
-    let conn = data.db.lock().unwrap();
+#[get("/countries/at")]
+pub async fn country_at(
+    req: HttpRequest,
+    query: web::Query<PointQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let point = geo::Point::new(query.lat, query.lon);
     
-    let mut stmt = match conn.prepare("SELECT DISTINCT region FROM countries") {
-        Ok(stmt) => stmt,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
-    };
+    if !point.is_valid() {
+        return Err(ApiError::BadRequest(String::from(
+            "lat must be between -90 and 90 and lon between -180 and 180",
+        )));
+    }
     
-    let region_iter = match stmt.query_map([], |row| row.get::<_, String>(0)) {
-        Ok(regions) => regions,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
-    };
+    let result = data.with_repo(move |repo| repo.country_at(point)).await?;
     
-    let mut regions = Vec::new();
-    for region in region_iter {
-        match region {
-            Ok(r) => regions.push(r),
-            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
-        }
+    match result {
+        Some(country) => export::respond(HttpResponse::Ok(), format, export::COUNTRIES, &country),
+        None => Err(ApiError::NotFound(format!(
+            "No country boundary contains the point {}, {}",
+            point.latitude, point.longitude
+        ))),
+    }
+}
+
+/// Query parameters accepted by `GET /countries.geojson`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GeoJsonCollectionQuery {
+    /// Only include the countries in this continent, or in this UN M49
+    /// region given by name or code
+    pub region: Option<String>,
+    /// Geometry of the features: `boundary` or `capital`; defaults to the
+    /// boundary where one is loaded and the capital otherwise
+    pub geometry: Option<GeometryKind>,
+    /// Tolerance in degrees for simplifying boundaries; larger values give
+    /// smaller payloads
+    pub simplify: Option<f64>,
+}
+
+/// Query parameters accepted by `GET /countries/{code}.geojson`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GeoJsonQuery {
+    /// Geometry of the feature: `boundary` or `capital`; defaults to the
+    /// boundary if one is loaded and the capital otherwise
+    pub geometry: Option<GeometryKind>,
+    /// Tolerance in degrees for simplifying the boundary; larger values give
+    /// smaller payloads
+    pub simplify: Option<f64>,
+}
+
+/// Checks the `simplify` tolerance of a GeoJSON request
+fn simplify_tolerance(simplify: Option<f64>) -> Result<Option<f64>, ApiError> {
+    match simplify {
+        Some(tolerance) if !(tolerance.is_finite() && tolerance >= 0.0) => Err(ApiError::BadRequest(String::from(
+            "simplify must be a tolerance of zero or more degrees",
+        ))),
+        tolerance => Ok(tolerance),
+    }
+}
+
+/// Serializes a GeoJSON document into a response
+fn geojson_response<T: Serialize>(document: &T) -> Result<HttpResponse, ApiError> {
+    let body = serde_json::to_string(document)
+        .map_err(|e| ApiError::Internal(format!("Cannot serialize response: {}", e)))?;
+    Ok(HttpResponse::Ok().insert_header((header::CONTENT_TYPE, geojson::MEDIA_TYPE)).body(body))
+}
+
+/// Endpoint handler that returns countries as a GeoJSON FeatureCollection
+///
+/// # Route
+/// `GET /countries.geojson`
+///
+/// # Parameters
+/// * `query` - An optional `region`, the `geometry` of the features and a
+///   `simplify` tolerance
+///
+/// # Returns
+/// * `200 OK` with a FeatureCollection holding a Feature per country,
+///   ordered by code, with the country's attributes as properties
+/// * `400 Bad Request` if `geometry` or `simplify` is invalid
+/// * `404 Not Found` if no countries exist in the requested region
+#[utoipa::path(
+    get,
+    path = "/countries.geojson",
+    params(GeoJsonCollectionQuery),
+    responses(
+        (status = 200, description = "Countries as GeoJSON features",
+            body = FeatureCollection, content_type = "application/geo+json"),
+        (status = 400, description = "Invalid geometry or simplify tolerance",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No countries found in the region",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/countries.geojson")]
+pub async fn countries_geojson(
+    query: web::Query<GeoJsonCollectionQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let query = query.into_inner();
+    let tolerance = simplify_tolerance(query.simplify)?;
+    let region = query.region.clone();
+    
+    let (countries, boundaries) = data
+        .with_repo(move |repo| {
+            let countries = match &region {
+                Some(region) => countries_in_region(repo, region)?,
+                None => {
+                    let options = ListOptions {
+                        filter: CountryFilter::default(),
+                        sort: vec![SortKey { field: SortField::Code, descending: false }],
+                        limit: u32::MAX,
+                        offset: 0,
+                    };
+                    repo.list(&options)?.countries
+                }
+            };
+            let codes: Vec<String> = countries.iter().map(|country| country.code.clone()).collect();
+            let boundaries = repo.boundaries(&codes)?;
+            Ok((countries, boundaries))
+        })
+        .await?;
+    
+    if let (Some(region), true) = (&query.region, countries.is_empty()) {
+        return Err(ApiError::NotFound(format!("No countries found in region {}", region)));
     }
     
-    HttpResponse::Ok().json(regions)
+    let features = countries
+        .into_iter()
+        .map(|country| {
+            let boundary = boundaries.iter().find(|boundary| boundary.code == country.code);
+            geojson::feature(country, boundary, query.geometry, tolerance)
+        })
+        .collect();
+    
+    geojson_response(&FeatureCollection { features })
 }
 
-/// Endpoint handler that returns all countries in a specific region
+/// Endpoint handler that returns a country as a GeoJSON Feature
 ///
 /// # Route
-/// `GET /countries/region/{region}`
+/// `GET /countries/{code}.geojson`
 ///
 /// # Parameters
-/// * `path` - The region name (e.g., "Europe", "Asia") extracted from the URL path
+/// * `path` - The country code in ISO 3166-1 alpha-2, alpha-3 or numeric form
+/// * `query` - The `geometry` of the feature and a `simplify` tolerance
 ///
 /// # Returns
-/// * `200 OK` with JSON array of countries if countries are found in the region
-/// * `404 Not Found` with an error message if no countries exist in the specified region
+/// * `200 OK` with a Feature holding the country's attributes as properties
+/// * `400 Bad Request` if the country code is malformed, or `geometry` or
+///   `simplify` is invalid
+/// * `404 Not Found` if the country code doesn't exist
 #[utoipa::path(
     get,
-    path = "/countries/region/{region}",
+    path = "/countries/{code}.geojson",
     params(
-        ("region" = String, Path, description = "Geographical region name")
+        ("code" = String, Path, description = "ISO 3166-1 alpha-2, alpha-3 or numeric country code"),
+        GeoJsonQuery
     ),
     responses(
-        (status = 200, description = "List of countries in the region", body = [Country]),
-        (status = 404, description = "No countries found in the region"),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "Country as a GeoJSON feature",
+            body = Feature, content_type = "application/geo+json"),
+        (status = 400, description = "Malformed country code, or invalid geometry or simplify tolerance",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Country not found",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
     )
 )]
-#[get("/countries/region/{region}")]
-pub async fn countries_by_region(path: web::Path<String>, data: web::Data<AppState>) -> impl Responder {
-    let region = path.into_inner();
-    let conn = data.db.lock().unwrap();
-    
-    let mut stmt = match conn.prepare(
-        "SELECT code, name, capital, region, currency FROM countries WHERE LOWER(region) = LOWER(?1)"
-    ) {
-        Ok(stmt) => stmt,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
-    };
+#[get("/countries/{code}.geojson")]
+pub async fn country_geojson(
+    path: web::Path<String>,
+    query: web::Query<GeoJsonQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let tolerance = simplify_tolerance(query.simplify)?;
+    let code = country_code_param(&path.into_inner())?;
+    let lookup = code.clone();
     
-    let country_iter = match stmt.query_map(params![region], |row| {
-        Ok(Country {
-            code: row.get(0)?,
-            name: row.get(1)?,
-            capital: row.get(2)?,
-            region: row.get(3)?,
-            currency: row.get(4)?,
+    let result = data
+        .with_repo(move |repo| {
+            let Some(country) = repo.get(&lookup)? else {
+                return Ok(None);
+            };
+            let boundary = repo.boundaries(std::slice::from_ref(&country.code))?.pop();
+            Ok(Some((country, boundary)))
         })
-    }) {
-        Ok(countries) => countries,
-        Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
-    };
+        .await?;
     
-    let mut countries = Vec::new();
-    for country in country_iter {
-        match country {
-            Ok(c) => countries.push(c),
-            Err(e) => return HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+    match result {
+        Some((country, boundary)) => {
+            geojson_response(&geojson::feature(country, boundary.as_ref(), query.geometry, tolerance))
         }
+        None => Err(ApiError::NotFound(format!("Country with code {} not found", code))),
     }
-    
-    if countries.is_empty() {
-        HttpResponse::NotFound().body(format!("No countries found in region {}", region))
+}
+
+/// Normalizes a country code received from a client
+///
+/// Alphabetic codes are uppercased and numeric codes are zero-padded to three
+/// digits, so "deu", "DEU", "76" and "076" match the stored forms.
+pub fn normalize_code(code: &str) -> String {
+    let code = code.trim();
+    if !code.is_empty() && code.len() <= 3 && code.chars().all(|c| c.is_ascii_digit()) {
+        format!("{:0>3}", code)
     } else {
-        HttpResponse::Ok().json(countries)
+        code.to_uppercase()
     }
 }
 
-/// Endpoint handler to add a new country
+/// Normalizes a country code taken from a request path
+///
+/// Fails with `400 Bad Request` if the normalized code has neither the shape
+/// of an alpha-2 or alpha-3 code nor that of a numeric one.
+fn country_code_param(code: &str) -> Result<String, ApiError> {
+    let code = normalize_code(code);
+    let alphabetic = (2..=3).contains(&code.len()) && code.chars().all(|c| c.is_ascii_uppercase());
+    let numeric = code.len() == 3 && code.chars().all(|c| c.is_ascii_digit());
+    if alphabetic || numeric {
+        Ok(code)
+    } else {
+        Err(ApiError::BadRequest(format!("{} is not an ISO 3166-1 alpha-2, alpha-3 or numeric country code", code)))
+    }
+}
+
+/// Normalizes a currency code taken from a request path
+///
+/// Fails with `400 Bad Request` if the normalized code is neither three
+/// letters nor three digits.
+fn currency_code_param(code: &str) -> Result<String, ApiError> {
+    let code = normalize_code(code);
+    let shaped = code.len() == 3
+        && (code.chars().all(|c| c.is_ascii_uppercase()) || code.chars().all(|c| c.is_ascii_digit()));
+    if shaped {
+        Ok(code)
+    } else {
+        Err(ApiError::BadRequest(format!("{} is not an ISO 4217 alphabetic or numeric currency code", code)))
+    }
+}
+
+/// Normalizes a top-level domain received from a client
+///
+/// The domain is lowercased and given a leading dot, so "DE", "de" and ".de"
+/// match the stored form.
+pub fn normalize_tld(tld: &str) -> String {
+    format!(".{}", tld.trim().trim_start_matches('.').to_lowercase())
+}
+
+/// Endpoint handler that returns a specific country by its code
 ///
 /// # Route
-/// `POST /countries`
+/// `GET /countries/{code}`
 ///
-/// # Request Body
-/// JSON object representing a country
+/// # Parameters
+/// * `path` - The country code in ISO 3166-1 alpha-2, alpha-3 or numeric form
+///   (e.g., "DE", "DEU" or "276") extracted from the URL path
+/// * `query` - The response `format`
 ///
 /// # Returns
-/// * `201 Created` with the created country data if successful
-/// * `400 Bad Request` if the country code already exists
+/// * `200 OK` with the country in the negotiated format if it is found
+/// * `400 Bad Request` if the code is not shaped like an ISO 3166-1 code
+/// * `404 Not Found` with a problem details body if the country code doesn't exist
+/// * `406 Not Acceptable` if the `Accept` header names no supported format
 #[utoipa::path(
-    post,
-    path = "/countries",
-    request_body = Country,
+    get,
+    path = "/countries/{code}",
+    params(
+        ("code" = String, Path, description = "ISO 3166-1 alpha-2, alpha-3 or numeric country code"),
+        FormatQuery
+    ),
     responses(
-        (status = 201, description = "Country created successfully", body = Country),
-        (status = 400, description = "Country with this code already exists"),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "Country found", body = Country),
+        (status = 400, description = "Malformed country code",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Country not found",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
     )
 )]
-#[post("/countries")]
-pub async fn add_country(country: web::Json<Country>, data: web::Data<AppState>) -> impl Responder {
-    let conn = data.db.lock().unwrap();
-    let new_country = country.into_inner();
-    
-    // Check if country with this code already exists
-    let exists: Result<bool, rusqlite::Error> = conn.query_row(
-        "SELECT 1 FROM countries WHERE code = ?1",
-        params![new_country.code],
-        |_| Ok(true),
-    ).or_else(|e| {
-        if let rusqlite::Error::QueryReturnedNoRows = e {
-            Ok(false)
-        } else {
-            Err(e)
-        }
-    });
-    
-    match exists {
-        Ok(true) => {
-            return HttpResponse::BadRequest()
-                .body(format!("Country with code {} already exists", new_country.code));
-        },
-        Ok(false) => {},
-        Err(e) => {
-            return HttpResponse::InternalServerError().body(format!("Database error: {}", e));
-        }
-    }
+#[get("/countries/{code}")]
+pub async fn country_by_code(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<FormatQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let code = country_code_param(&path.into_inner())?;
+    let lookup = code.clone();
     
-    // Add the new country
-    let result = conn.execute(
-        "INSERT INTO countries (code, name, capital, region, currency) VALUES (?1, ?2, ?3, ?4, ?5)",
-        params![
-            new_country.code,
-            new_country.name,
-            new_country.capital,
-            new_country.region,
-            new_country.currency
-        ],
-    );
+    let result = data.with_repo(move |repo| repo.get(&lookup)).await?;
     
     match result {
-        Ok(_) => HttpResponse::Created().json(new_country),
-        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        Some(country) => export::respond(HttpResponse::Ok(), format, export::COUNTRIES, &country),
+        None => Err(ApiError::NotFound(format!("Country with code {} not found", code))),
     }
 }
 
-/// Endpoint handler to update an existing country
+/// Endpoint handler that returns the distance between the capitals of two countries
 ///
 /// # Route
-/// `PUT /countries/{code}`
+/// `GET /countries/{from}/distance/{to}`
 ///
 /// # Parameters
-/// * `path` - The country code (e.g., "US", "GB") extracted from the URL path
-///
-/// # Request Body
-/// JSON object representing the updated country data
+/// * `path` - The codes of the two countries in ISO 3166-1 alpha-2, alpha-3
+///   or numeric form (e.g., "DE" and "FRA")
+/// * `query` - The response `format`
 ///
 /// # Returns
-/// * `200 OK` with the updated country data if successful
-/// * `404 Not Found` if the country code doesn't exist
+/// * `200 OK` with the great-circle distance between the capitals in
+///   kilometres and miles
+/// * `400 Bad Request` if either country code is malformed
+/// * `404 Not Found` if either country doesn't exist or has no capital with
+///   known coordinates
+/// * `406 Not Acceptable` if the `Accept` header names no supported format
 #[utoipa::path(
-    put,
-    path = "/countries/{code}",
+    get,
+    path = "/countries/{from}/distance/{to}",
     params(
-        ("code" = String, Path, description = "ISO 3166-1 alpha-2 country code")
+        ("from" = String, Path, description = "ISO 3166-1 code of the country to measure from"),
+        ("to" = String, Path, description = "ISO 3166-1 code of the country to measure to"),
+        FormatQuery
     ),
-    request_body = Country,
     responses(
-        (status = 200, description = "Country updated successfully", body = Country),
-        (status = 404, description = "Country not found"),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "Distance between the capitals", body = CapitalDistance),
+        (status = 400, description = "Malformed country code",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Country or capital coordinates not found",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
     )
 )]
-#[put("/countries/{code}")]
-pub async fn update_country(
-    path: web::Path<String>,
-    country: web::Json<Country>,
+#[get("/countries/{from}/distance/{to}")]
+pub async fn capital_distance(
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+    query: web::Query<FormatQuery>,
     data: web::Data<AppState>
-) -> impl Responder {
-    let code = path.into_inner().to_uppercase();
-    let conn = data.db.lock().unwrap();
-    let updated_country = country.into_inner();
-    
-    // Update the country
-    let result = conn.execute(
-        "UPDATE countries SET name = ?1, capital = ?2, region = ?3, currency = ?4 WHERE code = ?5",
-        params![
-            updated_country.name,
-            updated_country.capital,
-            updated_country.region,
-            updated_country.currency,
-            code
-        ],
-    );
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let (from, to) = path.into_inner();
+    let codes = (country_code_param(&from)?, country_code_param(&to)?);
+    let lookup = codes.clone();
     
-    match result {
-        Ok(rows) if rows > 0 => {
-            let country_with_code = Country {
-                code,
-                name: updated_country.name,
-                capital: updated_country.capital,
-                region: updated_country.region,
-                currency: updated_country.currency,
-            };
-            HttpResponse::Ok().json(country_with_code)
-        },
-        Ok(_) => HttpResponse::NotFound().body(format!("Country with code {} not found", code)),
-        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
-    }
+    let (from, to) = data
+        .with_repo(move |repo| Ok((repo.get(&lookup.0)?, repo.get(&lookup.1)?)))
+        .await?;
+    
+    let found = |country: Option<Country>, code: &str| {
+        country.ok_or_else(|| ApiError::NotFound(format!("Country with code {} not found", code)))
+    };
+    let (from, to) = (found(from, &codes.0)?, found(to, &codes.1)?);
+    let distance = geo::CapitalDistance::between(&from, &to).ok_or_else(|| {
+        let unplaced = if geo::capital_of(&from).is_none() { &from } else { &to };
+        ApiError::NotFound(format!("The capital of {} has no known coordinates", unplaced.name))
+    })?;
+    
+    export::respond(HttpResponse::Ok(), format, export::DISTANCES, &distance)
 }
 
-/// Endpoint handler to delete a country
+/// Endpoint handler that returns the neighbours of a country
 ///
 /// # Route
-/// `DELETE /countries/{code}`
+/// `GET /countries/{code}/neighbors`
 ///
 /// # Parameters
-/// * `path` - The country code (e.g., "US", "GB") extracted from the URL path
+/// * `path` - The country code in ISO 3166-1 alpha-2, alpha-3 or numeric form
+/// * `query` - The response `format`
 ///
 /// # Returns
-/// * `204 No Content` if the country was successfully deleted
+/// * `200 OK` with an array of the countries sharing a land border with the
+///   country, ordered by code; empty for island countries such as Japan
+/// * `400 Bad Request` if the country code is malformed
 /// * `404 Not Found` if the country code doesn't exist
+/// * `406 Not Acceptable` if the `Accept` header names no supported format
 #[utoipa::path(
-    delete,
-    path = "/countries/{code}",
+    get,
+    path = "/countries/{code}/neighbors",
     params(
-        ("code" = String, Path, description = "ISO 3166-1 alpha-2 country code")
+        ("code" = String, Path, description = "ISO 3166-1 alpha-2, alpha-3 or numeric country code"),
+        FormatQuery
     ),
     responses(
-        (status = 204, description = "Country deleted successfully"),
-        (status = 404, description = "Country not found"),
-        (status = 500, description = "Internal server error")
+        (status = 200, description = "Countries sharing a land border with the country", body = [Country]),
+        (status = 400, description = "Malformed country code",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Country not found",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
     )
 )]
-#[delete("/countries/{code}")]
-pub async fn delete_country(path: web::Path<String>, data: web::Data<AppState>) -> impl Responder {
-    let code = path.into_inner().to_uppercase();
-    let conn = data.db.lock().unwrap();
+#[get("/countries/{code}/neighbors")]
+pub async fn country_neighbors(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<FormatQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let code = country_code_param(&path.into_inner())?;
+    let lookup = code.clone();
     
-    // Delete the country
-    let result = conn.execute("DELETE FROM countries WHERE code = ?1", params![code]);
+    let result = data
+        .with_repo(move |repo| match repo.get(&lookup)? {
+            Some(country) => repo.neighbors(&country.code).map(Some),
+            None => Ok(None),
+        })
+        .await?;
     
     match result {
-        Ok(rows) if rows > 0 => HttpResponse::NoContent().finish(),
-        Ok(_) => HttpResponse::NotFound().body(format!("Country with code {} not found", code)),
-        Err(e) => HttpResponse::InternalServerError().body(format!("Database error: {}", e)),
+        Some(neighbors) => export::respond(HttpResponse::Ok(), format, export::COUNTRIES, &neighbors),
+        None => Err(ApiError::NotFound(format!("Country with code {} not found", code))),
     }
 }
 
-/// Configures the web service by registering all API endpoints
+/// Endpoint handler that returns a land route between two countries
 ///
-/// This function is used in the main application to set up all the routes.
+/// # Route
+/// `GET /countries/{from}/route/{to}`
 ///
 /// # Parameters
-/// * `cfg` - Service configuration object provided by Actix web
-pub fn config(cfg: &mut web::ServiceConfig) {
+/// * `path` - The codes of the two countries in ISO 3166-1 alpha-2, alpha-3
+///   or numeric form (e.g., "PT" and "CHN")
+/// * `query` - The response `format`
+///
+/// # Returns
+/// * `200 OK` with an array of the countries along a route crossing as few
+///   land borders as possible, from the first country to the second
+/// * `400 Bad Request` if either country code is malformed
+/// * `404 Not Found` if either country doesn't exist or no land route joins
+///   them, e.g. from Japan
+/// * `406 Not Acceptable` if the `Accept` header names no supported format
+#[utoipa::path(
+    get,
+    path = "/countries/{from}/route/{to}",
+    params(
+        ("from" = String, Path, description = "ISO 3166-1 code of the country to start from"),
+        ("to" = String, Path, description = "ISO 3166-1 code of the country to reach"),
+        FormatQuery
+    ),
+    responses(
+        (status = 200, description = "Countries along the route, in order", body = [Country]),
+        (status = 400, description = "Malformed country code",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Country or land route not found",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/countries/{from}/route/{to}")]
+pub async fn land_route(
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+    query: web::Query<FormatQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let (from, to) = path.into_inner();
+    let codes = (country_code_param(&from)?, country_code_param(&to)?);
+    let lookup = codes.clone();
+    
+    let (from, to) = data
+        .with_repo(move |repo| Ok((repo.get(&lookup.0)?, repo.get(&lookup.1)?)))
+        .await?;
+    
+    let found = |country: Option<Country>, code: &str| {
+        country.ok_or_else(|| ApiError::NotFound(format!("Country with code {} not found", code)))
+    };
+    let (from, to) = (found(from, &codes.0)?, found(to, &codes.1)?);
+    let ends = (from.code.clone(), to.code.clone());
+    let route = data.with_repo(move |repo| repo.route(&ends.0, &ends.1)).await?;
+    
+    match route {
+        Some(route) => export::respond(HttpResponse::Ok(), format, export::COUNTRIES, &route),
+        None => Err(ApiError::NotFound(format!("There is no land route from {} to {}", from.name, to.name))),
+    }
+}
+
+/// Endpoint handler that returns the time zones of a country
+///
+/// # Route
+/// `GET /countries/{code}/timezones`
+///
+/// # Parameters
+/// * `path` - The country code in ISO 3166-1 alpha-2, alpha-3 or numeric form
+/// * `query` - The response `format`
+///
+/// # Returns
+/// * `200 OK` with an array of the country's IANA time zones, the zone of its
+///   capital first, each with its current UTC offset and daylight saving
+///   time state; empty for uninhabited territories without a zone
+/// * `400 Bad Request` if the country code is malformed
+/// * `404 Not Found` if the country code doesn't exist
+/// * `406 Not Acceptable` if the `Accept` header names no supported format
+#[utoipa::path(
+    get,
+    path = "/countries/{code}/timezones",
+    params(
+        ("code" = String, Path, description = "ISO 3166-1 alpha-2, alpha-3 or numeric country code"),
+        FormatQuery
+    ),
+    responses(
+        (status = 200, description = "Time zones of the country and their current offsets", body = [TimeZoneInfo]),
+        (status = 400, description = "Malformed country code",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Country not found",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/countries/{code}/timezones")]
+pub async fn country_timezones(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<FormatQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let code = country_code_param(&path.into_inner())?;
+    let lookup = code.clone();
+    
+    let country = data
+        .with_repo(move |repo| repo.get(&lookup))
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Country with code {} not found", code)))?;
+    
+    let now = chrono::Utc::now();
+    let zones: Vec<TimeZoneInfo> = country
+        .timezones
+        .iter()
+        .filter_map(|name| TimeZoneInfo::at(name, now))
+        .collect();
+    export::respond(HttpResponse::Ok(), format, export::TIMEZONES, &zones)
+}
+
+/// Endpoint handler that returns the local time in the capital of a country
+///
+/// # Route
+/// `GET /countries/{code}/time`
+///
+/// # Parameters
+/// * `path` - The country code in ISO 3166-1 alpha-2, alpha-3 or numeric form
+/// * `query` - The response `format`
+///
+/// # Returns
+/// * `200 OK` with the current local time in the capital, its time zone, UTC
+///   offset and daylight saving time state
+/// * `400 Bad Request` if the country code is malformed
+/// * `404 Not Found` if the country code doesn't exist or the country has no
+///   time zone
+/// * `406 Not Acceptable` if the `Accept` header names no supported format
+#[utoipa::path(
+    get,
+    path = "/countries/{code}/time",
+    params(
+        ("code" = String, Path, description = "ISO 3166-1 alpha-2, alpha-3 or numeric country code"),
+        FormatQuery
+    ),
+    responses(
+        (status = 200, description = "Local time in the capital of the country", body = LocalTime),
+        (status = 400, description = "Malformed country code",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Country not found or without a time zone",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/countries/{code}/time")]
+pub async fn country_time(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<FormatQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let code = country_code_param(&path.into_inner())?;
+    let lookup = code.clone();
+    
+    let country = data
+        .with_repo(move |repo| repo.get(&lookup))
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Country with code {} not found", code)))?;
+    
+    let time = LocalTime::at(&country, chrono::Utc::now())
+        .ok_or_else(|| ApiError::NotFound(format!("{} has no known time zone", country.name)))?;
+    export::respond(HttpResponse::Ok(), format, export::LOCAL_TIMES, &time)
+}
+
+/// Query parameters accepted by `GET /countries/{code}/holidays`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct HolidaysQuery {
+    /// Year of the holidays (1900 to 2099, defaults to the current year)
+    pub year: Option<i32>,
+    /// Response format, overriding the `Accept` header
+    pub format: Option<ExportFormat>,
+}
+
+/// Query parameters accepted by `GET /countries/{code}/is-holiday`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct HolidayDateQuery {
+    /// Date to check in `YYYY-MM-DD` form
+    pub date: String,
+    /// Response format, overriding the `Accept` header
+    pub format: Option<ExportFormat>,
+}
+
+/// Query parameters accepted by `GET /countries/{code}/business-days`
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BusinessDaysQuery {
+    /// Date to count from in `YYYY-MM-DD` form, itself not counted (defaults to today)
+    pub start: Option<String>,
+    /// Number of business days to add; negative to count back (-2600 to 2600)
+    pub days: i64,
+    /// Response format, overriding the `Accept` header
+    pub format: Option<ExportFormat>,
+}
+
+/// Parses a date query parameter in `YYYY-MM-DD` form within the years
+/// holidays are computed for
+fn date_param(name: &str, value: &str) -> Result<chrono::NaiveDate, ApiError> {
+    calendar::parse_date(value)
+        .filter(|date| (calendar::MIN_YEAR..=calendar::MAX_YEAR).contains(&date.year()))
+        .ok_or_else(|| ApiError::BadRequest(format!(
+            "{} must be a date in YYYY-MM-DD form between {} and {}",
+            name, calendar::MIN_YEAR, calendar::MAX_YEAR
+        )))
+}
+
+/// Fails with `400 Bad Request` if `year`, taken from the `name` parameter,
+/// is outside the years the holidays of `calendar` are known in
+fn check_calendar_year(calendar: &calendar::Calendar, name: &str, year: i32) -> Result<(), ApiError> {
+    let years = calendar.years();
+    if years.contains(&year) {
+        return Ok(());
+    }
+    Err(ApiError::BadRequest(format!(
+        "{} must be between {} and {}, the years the lunar holidays of the country are known in",
+        name, years.start(), years.end()
+    )))
+}
+
+/// Looks up a country and its holiday calendar, with the country's weekend
+///
+/// Fails with `400 Bad Request` if the code is malformed, and with
+/// `404 Not Found` if the country doesn't exist or its public holidays are
+/// not known.
+async fn country_calendar(data: &AppState, code: String) -> Result<calendar::Calendar, ApiError> {
+    let code = country_code_param(&code)?;
+    let lookup = code.clone();
+    
+    let result = data
+        .with_repo(move |repo| match repo.get(&lookup)? {
+            Some(country) => {
+                let rules = repo.holiday_rules(&country.code)?;
+                Ok(Some((country, rules)))
+            }
+            None => Ok(None),
+        })
+        .await?;
+    
+    match result {
+        None => Err(ApiError::NotFound(format!("Country with code {} not found", code))),
+        Some((country, rules)) if rules.is_empty() => {
+            Err(ApiError::NotFound(format!("The public holidays of {} are not known", country.name)))
+        }
+        Some((country, rules)) if country.weekend.is_empty() => Ok(calendar::Calendar::new(rules, calendar::DEFAULT_WEEKEND)),
+        Some((country, rules)) => Ok(calendar::Calendar::new(rules, &country.weekend)),
+    }
+}
+
+/// Endpoint handler that returns the public holidays of a country in a year
+///
+/// # Route
+/// `GET /countries/{code}/holidays`
+///
+/// # Parameters
+/// * `path` - The country code in ISO 3166-1 alpha-2, alpha-3 or numeric form
+/// * `query` - The `year` and the response `format`
+///
+/// # Returns
+/// * `200 OK` with an array of the national public holidays of the year,
+///   ordered by date, each with the day off given for it
+/// * `400 Bad Request` if the country code is malformed, or `year` is out of
+///   range or outside the years the lunar holidays of the country are known in
+/// * `404 Not Found` if the country code doesn't exist or the country's
+///   holidays are not known
+/// * `406 Not Acceptable` if the `Accept` header names no supported format
+#[utoipa::path(
+    get,
+    path = "/countries/{code}/holidays",
+    params(
+        ("code" = String, Path, description = "ISO 3166-1 alpha-2, alpha-3 or numeric country code"),
+        HolidaysQuery
+    ),
+    responses(
+        (status = 200, description = "Public holidays of the country in the year", body = [Holiday]),
+        (status = 400, description = "Malformed country code or invalid year",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Country not found or its holidays not known",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/countries/{code}/holidays")]
+pub async fn country_holidays(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<HolidaysQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let query = query.into_inner();
+    let format = export::negotiate(&req, query.format)?;
+    let year = query.year.unwrap_or_else(|| chrono::Utc::now().year());
+    
+    if !(calendar::MIN_YEAR..=calendar::MAX_YEAR).contains(&year) {
+        return Err(ApiError::BadRequest(format!(
+            "year must be between {} and {}",
+            calendar::MIN_YEAR, calendar::MAX_YEAR
+        )));
+    }
+    
+    let calendar = country_calendar(&data, path.into_inner()).await?;
+    check_calendar_year(&calendar, "year", year)?;
+    export::respond(HttpResponse::Ok(), format, export::HOLIDAYS, &calendar.holidays(year))
+}
+
+/// Endpoint handler that checks whether a date is a holiday in a country
+///
+/// # Route
+/// `GET /countries/{code}/is-holiday`
+///
+/// # Parameters
+/// * `path` - The country code in ISO 3166-1 alpha-2, alpha-3 or numeric form
+/// * `query` - The `date` and the response `format`
+///
+/// # Returns
+/// * `200 OK` with whether the date is a public holiday or the day off given
+///   for one, whether it is a business day, and the holidays on it
+/// * `400 Bad Request` if the country code is malformed, or `date` is
+///   missing, malformed or out of range
+/// * `404 Not Found` if the country code doesn't exist or the country's
+///   holidays are not known
+/// * `406 Not Acceptable` if the `Accept` header names no supported format
+#[utoipa::path(
+    get,
+    path = "/countries/{code}/is-holiday",
+    params(
+        ("code" = String, Path, description = "ISO 3166-1 alpha-2, alpha-3 or numeric country code"),
+        HolidayDateQuery
+    ),
+    responses(
+        (status = 200, description = "Whether the date is a holiday in the country", body = HolidayCheck),
+        (status = 400, description = "Malformed country code, or missing or invalid date",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Country not found or its holidays not known",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/countries/{code}/is-holiday")]
+pub async fn country_is_holiday(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<HolidayDateQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let query = query.into_inner();
+    let format = export::negotiate(&req, query.format)?;
+    let date = date_param("date", &query.date)?;
+    
+    let calendar = country_calendar(&data, path.into_inner()).await?;
+    check_calendar_year(&calendar, "date", date.year())?;
+    export::respond(HttpResponse::Ok(), format, export::HOLIDAY_CHECKS, &calendar.check(date))
+}
+
+/// Endpoint handler that adds business days to a date in a country
+///
+/// # Route
+/// `GET /countries/{code}/business-days`
+///
+/// # Parameters
+/// * `path` - The country code in ISO 3166-1 alpha-2, alpha-3 or numeric form
+/// * `query` - The `start` date, the number of business `days` and the
+///   response `format`
+///
+/// # Returns
+/// * `200 OK` with the business day reached by counting `days` business
+///   days from `start`, skipping weekends and days off for public holidays
+/// * `400 Bad Request` if the country code is malformed, or `start` or `days`
+///   is missing, malformed or out of range
+/// * `404 Not Found` if the country code doesn't exist or the country's
+///   holidays are not known
+/// * `406 Not Acceptable` if the `Accept` header names no supported format
+#[utoipa::path(
+    get,
+    path = "/countries/{code}/business-days",
+    params(
+        ("code" = String, Path, description = "ISO 3166-1 alpha-2, alpha-3 or numeric country code"),
+        BusinessDaysQuery
+    ),
+    responses(
+        (status = 200, description = "Business day reached from the start date", body = BusinessDays),
+        (status = 400, description = "Malformed country code, or missing or invalid start date or number of days",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Country not found or its holidays not known",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/countries/{code}/business-days")]
+pub async fn country_business_days(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<BusinessDaysQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let query = query.into_inner();
+    let format = export::negotiate(&req, query.format)?;
+    let start = match &query.start {
+        Some(start) => date_param("start", start)?,
+        None => chrono::Utc::now().date_naive(),
+    };
+    
+    if !(-calendar::MAX_BUSINESS_DAYS..=calendar::MAX_BUSINESS_DAYS).contains(&query.days) {
+        return Err(ApiError::BadRequest(format!(
+            "days must be between -{} and {}",
+            calendar::MAX_BUSINESS_DAYS, calendar::MAX_BUSINESS_DAYS
+        )));
+    }
+    
+    let calendar = country_calendar(&data, path.into_inner()).await?;
+    check_calendar_year(&calendar, "start", start.year())?;
+    let date = calendar.add_business_days(start, query.days).ok_or_else(|| {
+        let years = calendar.years();
+        ApiError::BadRequest(format!(
+            "The business day reached must be between {} and {}",
+            years.start(), years.end()
+        ))
+    })?;
+    
+    let result = BusinessDays { start, days: query.days, date };
+    export::respond(HttpResponse::Ok(), format, export::BUSINESS_DAYS, &result)
+}
+
+/// Endpoint handler that returns the countries reached through a calling code
+///
+/// # Route
+/// `GET /countries/by-calling-code/{code}`
+///
+/// # Parameters
+/// * `path` - The calling code, with or without its `+`: an ITU country code
+///   (e.g., "44") or a full calling code including an area code (e.g., "1809")
+/// * `query` - The response `format`
+///
+/// # Returns
+/// * `200 OK` with an array of countries ordered by code; an ITU country
+///   code shared by several countries returns all of them
+/// * `400 Bad Request` if the code is not made of digits
+/// * `404 Not Found` if no country uses the calling code
+/// * `406 Not Acceptable` if the `Accept` header names no supported format
+#[utoipa::path(
+    get,
+    path = "/countries/by-calling-code/{code}",
+    params(
+        ("code" = String, Path, description = "Calling code, e.g. 44, +44 or 1809"),
+        FormatQuery
+    ),
+    responses(
+        (status = 200, description = "Countries using the calling code", body = [Country]),
+        (status = 400, description = "Malformed calling code",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No country uses the calling code",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/countries/by-calling-code/{code}")]
+pub async fn countries_by_calling_code(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<FormatQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let code = path.into_inner();
+    let digits: String = code.trim().trim_start_matches('+').split_whitespace().collect();
+    
+    if digits.is_empty() || digits.len() > 9 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(ApiError::BadRequest(format!("{} is not a calling code", code.trim())));
+    }
+    
+    let lookup = digits.clone();
+    let countries = data.with_repo(move |repo| repo.by_calling_code(&lookup)).await?;
+    
+    if countries.is_empty() {
+        Err(ApiError::NotFound(format!("No country uses the calling code +{}", digits)))
+    } else {
+        export::respond(HttpResponse::Ok(), format, export::COUNTRIES, &countries)
+    }
+}
+
+/// Endpoint handler that returns the countries using a top-level domain
+///
+/// # Route
+/// `GET /countries/by-tld/{tld}`
+///
+/// # Parameters
+/// * `path` - The country code top-level domain, with or without its leading
+///   dot (e.g., ".de" or "uk")
+/// * `query` - The response `format`
+///
+/// # Returns
+/// * `200 OK` with an array of countries ordered by code
+/// * `404 Not Found` if no country uses the domain
+/// * `406 Not Acceptable` if the `Accept` header names no supported format
+#[utoipa::path(
+    get,
+    path = "/countries/by-tld/{tld}",
+    params(
+        ("tld" = String, Path, description = "Top-level domain, e.g. .de"),
+        FormatQuery
+    ),
+    responses(
+        (status = 200, description = "Countries using the top-level domain", body = [Country]),
+        (status = 404, description = "No country uses the top-level domain",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/countries/by-tld/{tld}")]
+pub async fn countries_by_tld(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<FormatQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let tld = normalize_tld(&path.into_inner());
+    let lookup = tld.clone();
+    
+    let countries = data.with_repo(move |repo| repo.by_tld(&lookup)).await?;
+    
+    if countries.is_empty() {
+        Err(ApiError::NotFound(format!("No country uses the top-level domain {}", tld)))
+    } else {
+        export::respond(HttpResponse::Ok(), format, export::COUNTRIES, &countries)
+    }
+}
+
+/// Endpoint handler that returns all unique geographical regions
+///
+/// # Route
+/// `GET /regions`
+///
+/// # Parameters
+/// * `query` - The response `format`
+///
+/// # Returns
+/// An array containing all unique regions from the countries database
+/// (e.g., "Europe", "Asia", "North America") in the negotiated format
+#[utoipa::path(
+    get,
+    path = "/regions",
+    params(FormatQuery),
+    responses(
+        (status = 200, description = "List of all geographical regions", body = [String]),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/regions")]
+pub async fn get_regions(
+    req: HttpRequest,
+    query: web::Query<FormatQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let regions = data.with_repo(|repo| repo.regions()).await?;
+    
+    export::respond(HttpResponse::Ok(), format, export::REGIONS, &regions)
+}
+
+/// Endpoint handler that returns a UN M49 region with its subregions and countries
+///
+/// # Route
+/// `GET /regions/{code}`
+///
+/// # Parameters
+/// * `path` - The M49 code (e.g., "155") or name (e.g., "Western Europe") of the region;
+///   `001` is the whole world
+/// * `query` - The response `format`
+///
+/// # Returns
+/// * `200 OK` with the region, the regions directly below it and every country
+///   in it or in one of its descendants, in the negotiated format
+/// * `404 Not Found` with a problem details body if there is no such region
+#[utoipa::path(
+    get,
+    path = "/regions/{code}",
+    params(
+        ("code" = String, Path, description = "UN M49 region code or name"),
+        FormatQuery
+    ),
+    responses(
+        (status = 200, description = "Region found", body = RegionDetails),
+        (status = 404, description = "Region not found",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/regions/{code}")]
+pub async fn region_by_code(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<FormatQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let key = path.into_inner();
+    let lookup = key.clone();
+    
+    let details = data
+        .with_repo(move |repo| {
+            let Some(region) = repo.region(&lookup)? else {
+                return Ok(None);
+            };
+            let children = repo.subregions(&region.code)?;
+            let countries = repo.in_region(&region.code)?;
+            Ok(Some(RegionDetails::new(region, children, countries)))
+        })
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Region {} not found", key)))?;
+    
+    export::respond(HttpResponse::Ok(), format, export::REGIONS, &details)
+}
+
+/// Endpoint handler that returns all countries in a specific region
+///
+/// # Route
+/// `GET /countries/region/{region}`
+///
+/// # Parameters
+/// * `path` - The continent the countries are filed under (e.g., "Europe",
+///   "North America"), or the name or M49 code of a region at any level of
+///   the UN M49 hierarchy (e.g., "Western Europe", "419")
+/// * `query` - The response `format`
+///
+/// # Returns
+/// * `200 OK` with an array of countries in the negotiated format if countries
+///   are found in the region
+/// * `404 Not Found` with a problem details body if no countries exist in the specified region
+/// * `406 Not Acceptable` if the `Accept` header names no supported format
+#[utoipa::path(
+    get,
+    path = "/countries/region/{region}",
+    params(
+        ("region" = String, Path, description = "Continent, or UN M49 region name or code"),
+        FormatQuery
+    ),
+    responses(
+        (status = 200, description = "List of countries in the region", body = [Country]),
+        (status = 404, description = "No countries found in the region",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/countries/region/{region}")]
+pub async fn countries_by_region(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<FormatQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let region = path.into_inner();
+    let lookup = region.clone();
+    
+    let countries = data.with_repo(move |repo| countries_in_region(repo, &lookup)).await?;
+    
+    if countries.is_empty() {
+        Err(ApiError::NotFound(format!("No countries found in region {}", region)))
+    } else {
+        export::respond(HttpResponse::Ok(), format, export::COUNTRIES, &countries)
+    }
+}
+
+/// Countries in a continent, or in a UN M49 region given by name or code
+fn countries_in_region(repo: &dyn CountryRepository, region: &str) -> Result<Vec<Country>, ApiError> {
+    // Continent names keep matching the `region` countries are filed under
+    if validation::REGIONS.iter().any(|continent| continent.eq_ignore_ascii_case(region.trim())) {
+        return repo.by_region(region.trim());
+    }
+    match repo.region(region)? {
+        Some(found) => repo.in_region(&found.code),
+        None => repo.by_region(region),
+    }
+}
+
+/// Endpoint handler that returns every ISO 4217 currency
+///
+/// # Route
+/// `GET /currencies`
+///
+/// # Parameters
+/// * `query` - The response `format`
+///
+/// # Returns
+/// An array of currencies ordered by code, in the negotiated format
+#[utoipa::path(
+    get,
+    path = "/currencies",
+    params(FormatQuery),
+    responses(
+        (status = 200, description = "List of all currencies", body = [Currency]),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/currencies")]
+pub async fn all_currencies(
+    req: HttpRequest,
+    query: web::Query<FormatQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let currencies = data.with_repo(|repo| repo.currencies()).await?;
+    
+    export::respond(HttpResponse::Ok(), format, export::CURRENCIES, &currencies)
+}
+
+/// Endpoint handler that returns a specific currency by its code
+///
+/// # Route
+/// `GET /currencies/{code}`
+///
+/// # Parameters
+/// * `path` - The ISO 4217 alphabetic or numeric currency code (e.g., "EUR" or "978")
+/// * `query` - The response `format`
+///
+/// # Returns
+/// * `200 OK` with the currency in the negotiated format if it is found
+/// * `400 Bad Request` if the currency code is malformed
+/// * `404 Not Found` with a problem details body if the currency code doesn't exist
+#[utoipa::path(
+    get,
+    path = "/currencies/{code}",
+    params(
+        ("code" = String, Path, description = "ISO 4217 alphabetic or numeric currency code"),
+        FormatQuery
+    ),
+    responses(
+        (status = 200, description = "Currency found", body = Currency),
+        (status = 400, description = "Malformed currency code",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Currency not found",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/currencies/{code}")]
+pub async fn currency_by_code(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<FormatQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let code = currency_code_param(&path.into_inner())?;
+    let lookup = code.clone();
+    
+    match data.with_repo(move |repo| repo.currency(&lookup)).await? {
+        Some(currency) => export::respond(HttpResponse::Ok(), format, export::CURRENCIES, &currency),
+        None => Err(ApiError::NotFound(format!("Currency with code {} not found", code))),
+    }
+}
+
+/// Endpoint handler that returns the countries where a currency is legal tender
+///
+/// # Route
+/// `GET /currencies/{code}/countries`
+///
+/// # Parameters
+/// * `path` - The ISO 4217 alphabetic or numeric currency code (e.g., "USD" or "840")
+/// * `query` - The response `format`
+///
+/// # Returns
+/// * `200 OK` with an array of countries ordered by code, in the negotiated
+///   format; empty if the currency is not used anywhere
+/// * `400 Bad Request` if the currency code is malformed
+/// * `404 Not Found` with a problem details body if the currency code doesn't exist
+#[utoipa::path(
+    get,
+    path = "/currencies/{code}/countries",
+    params(
+        ("code" = String, Path, description = "ISO 4217 alphabetic or numeric currency code"),
+        FormatQuery
+    ),
+    responses(
+        (status = 200, description = "Countries using the currency", body = [Country]),
+        (status = 400, description = "Malformed currency code",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Currency not found",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/currencies/{code}/countries")]
+pub async fn countries_by_currency(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<FormatQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let code = currency_code_param(&path.into_inner())?;
+    let lookup = code.clone();
+    
+    let countries = data
+        .with_repo(move |repo| match repo.currency(&lookup)? {
+            Some(currency) => repo.by_currency(&currency.code).map(Some),
+            None => Ok(None),
+        })
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Currency with code {} not found", code)))?;
+    
+    export::respond(HttpResponse::Ok(), format, export::COUNTRIES, &countries)
+}
+
+/// Endpoint handler that returns every ISO 639 language spoken officially somewhere
+///
+/// # Route
+/// `GET /languages`
+///
+/// # Parameters
+/// * `query` - The response `format`
+///
+/// # Returns
+/// An array of languages ordered by code, in the negotiated format
+#[utoipa::path(
+    get,
+    path = "/languages",
+    params(FormatQuery),
+    responses(
+        (status = 200, description = "List of all languages", body = [Language]),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/languages")]
+pub async fn all_languages(
+    req: HttpRequest,
+    query: web::Query<FormatQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let languages = data.with_repo(|repo| repo.languages()).await?;
+    
+    export::respond(HttpResponse::Ok(), format, export::LANGUAGES, &languages)
+}
+
+/// Endpoint handler that returns a specific language by its code
+///
+/// # Route
+/// `GET /languages/{code}`
+///
+/// # Parameters
+/// * `path` - The ISO 639-1 or ISO 639-3 language code (e.g., "de" or "deu")
+/// * `query` - The response `format`
+///
+/// # Returns
+/// * `200 OK` with the language in the negotiated format if it is found
+/// * `404 Not Found` with a problem details body if the language code doesn't exist
+#[utoipa::path(
+    get,
+    path = "/languages/{code}",
+    params(
+        ("code" = String, Path, description = "ISO 639-1 or ISO 639-3 language code"),
+        FormatQuery
+    ),
+    responses(
+        (status = 200, description = "Language found", body = Language),
+        (status = 404, description = "Language not found",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/languages/{code}")]
+pub async fn language_by_code(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<FormatQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let code = languages::normalize_language_code(&path.into_inner());
+    let lookup = code.clone();
+    
+    match data.with_repo(move |repo| repo.language(&lookup)).await? {
+        Some(language) => export::respond(HttpResponse::Ok(), format, export::LANGUAGES, &language),
+        None => Err(ApiError::NotFound(format!("Language with code {} not found", code))),
+    }
+}
+
+/// Endpoint handler that returns the countries where a language is official or recognized
+///
+/// # Route
+/// `GET /languages/{code}/countries`
+///
+/// # Parameters
+/// * `path` - The ISO 639-1 or ISO 639-3 language code (e.g., "fr" or "fra")
+/// * `query` - The response `format`
+///
+/// # Returns
+/// * `200 OK` with an array of countries ordered by code, in the negotiated
+///   format; each country lists the status of the language in `languages`
+/// * `404 Not Found` with a problem details body if the language code doesn't exist
+#[utoipa::path(
+    get,
+    path = "/languages/{code}/countries",
+    params(
+        ("code" = String, Path, description = "ISO 639-1 or ISO 639-3 language code"),
+        FormatQuery
+    ),
+    responses(
+        (status = 200, description = "Countries speaking the language", body = [Country]),
+        (status = 404, description = "Language not found",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 406, description = "No acceptable response format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[get("/languages/{code}/countries")]
+pub async fn countries_by_language(
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<FormatQuery>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let format = export::negotiate(&req, query.format)?;
+    let code = languages::normalize_language_code(&path.into_inner());
+    let lookup = code.clone();
+    
+    let countries = data
+        .with_repo(move |repo| match repo.language(&lookup)? {
+            Some(language) => repo.by_language(&language.code).map(Some),
+            None => Ok(None),
+        })
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Language with code {} not found", code)))?;
+    
+    export::respond(HttpResponse::Ok(), format, export::COUNTRIES, &countries)
+}
+
+/// Endpoint handler that validates a phone number in international format
+///
+/// # Route
+/// `POST /validate/phone`
+///
+/// # Request Body
+/// JSON object with the `number`, e.g. `{"number": "+49 30 901820"}`
+///
+/// # Returns
+/// * `200 OK` with the number in E.164 format, its country and whether the
+///   length of its national number is plausible for that country
+/// * `400 Bad Request` if the payload is malformed
+/// * `404 Not Found` if no country uses the number's calling code
+/// * `422 Unprocessable Entity` if the number is not in international format
+#[utoipa::path(
+    post,
+    path = "/validate/phone",
+    request_body = PhoneNumberRequest,
+    responses(
+        (status = 200, description = "Country of the number and plausibility of its length", body = PhoneValidation),
+        (status = 400, description = "Malformed payload",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "No country uses the calling code",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Number not in international format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[post("/validate/phone")]
+pub async fn validate_phone(
+    request: web::Json<PhoneNumberRequest>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let digits = phone::parse_number(&request.number).map_err(|message| {
+        ApiError::Validation(vec![FieldError { field: String::from("number"), message }])
+    })?;
+    let lookup = digits.clone();
+    
+    let validation = data
+        .with_repo(move |repo| {
+            // ITU country codes are prefix-free, so at most one of these is in use
+            for len in 1..=3 {
+                let countries = repo.by_calling_code(&lookup[..len])?;
+                if !countries.is_empty() {
+                    return Ok(PhoneValidation::of(&lookup, &countries));
+                }
+            }
+            Ok(None)
+        })
+        .await?;
+    
+    match validation {
+        Some(validation) => Ok(HttpResponse::Ok().json(validation)),
+        None => Err(ApiError::NotFound(format!("No country uses the calling code of +{}", digits))),
+    }
+}
+
+/// Endpoint handler to add a new country
+///
+/// # Route
+/// `POST /countries`
+///
+/// # Request Body
+/// JSON object representing a country
+///
+/// # Returns
+/// * `201 Created` with the created country data if successful
+/// * `400 Bad Request` if the payload is not a valid country
+/// * `409 Conflict` if the country code already exists
+/// * `422 Unprocessable Entity` with per-field errors if the payload is invalid
+#[utoipa::path(
+    post,
+    path = "/countries",
+    request_body = Country,
+    responses(
+        (status = 201, description = "Country created successfully", body = Country),
+        (status = 400, description = "Malformed country payload",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Country with this code already exists",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Country payload failed validation",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[post("/countries")]
+pub async fn add_country(
+    country: web::Json<Country>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let new_country = validation::normalize_country(country.into_inner());
+    validation::validate_country(&new_country)?;
+    
+    let stored = new_country.clone();
+    data.with_repo(move |repo| repo.insert(&stored)).await?;
+    
+    Ok(HttpResponse::Created().json(new_country))
+}
+
+/// Endpoint handler to update an existing country
+///
+/// # Route
+/// `PUT /countries/{code}`
+///
+/// # Parameters
+/// * `path` - The country code in ISO 3166-1 alpha-2, alpha-3 or numeric form
+///   (e.g., "US", "USA" or "840") extracted from the URL path
+///
+/// # Request Body
+/// JSON object representing the updated country data
+///
+/// # Returns
+/// * `200 OK` with the updated country data if successful
+/// * `400 Bad Request` if the path code is malformed or the body is for
+///   another country than the path
+/// * `404 Not Found` if the country code doesn't exist
+/// * `422 Unprocessable Entity` with per-field errors if the payload is invalid
+#[utoipa::path(
+    put,
+    path = "/countries/{code}",
+    params(
+        ("code" = String, Path, description = "ISO 3166-1 alpha-2, alpha-3 or numeric country code")
+    ),
+    request_body = Country,
+    responses(
+        (status = 200, description = "Country updated successfully", body = Country),
+        (status = 400, description = "Malformed country code or payload, or mismatching country code",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Country payload failed validation",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Country not found",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[put("/countries/{code}")]
+pub async fn update_country(
+    path: web::Path<String>,
+    country: web::Json<Country>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let code = country_code_param(&path.into_inner())?;
+    let updated_country = validation::normalize_country(country.into_inner());
+    
+    let lookup = code.clone();
+    let stored = updated_country.clone();
+    let updated = data
+        .with_repo(move |repo| {
+            let Some(existing) = repo.get(&lookup)? else {
+                return Ok(false);
+            };
+            if stored.code != existing.code {
+                return Err(ApiError::BadRequest(format!(
+                    "Country code {} in the body does not match {} in the path",
+                    stored.code, lookup
+                )));
+            }
+            validation::validate_country_update(&stored, &existing)?;
+            repo.update(&stored)
+        })
+        .await?;
+    if !updated {
+        return Err(ApiError::NotFound(format!("Country with code {} not found", code)));
+    }
+    
+    Ok(HttpResponse::Ok().json(updated_country))
+}
+
+/// Applies an RFC 7396 JSON Merge Patch to `target`
+///
+/// Object members of `patch` are merged recursively, `null` members remove
+/// the corresponding member and any other value replaces the target outright.
+pub fn merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    let serde_json::Value::Object(patch_members) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    
+    if !target.is_object() {
+        *target = serde_json::Value::Object(serde_json::Map::new());
+    }
+    if let serde_json::Value::Object(target_members) = target {
+        for (name, value) in patch_members {
+            if value.is_null() {
+                target_members.remove(name);
+            } else {
+                merge_patch(target_members.entry(name.clone()).or_insert(serde_json::Value::Null), value);
+            }
+        }
+    }
+}
+
+/// Applies a JSON Merge Patch to a stored country and checks the result
+///
+/// The merged country is normalized and validated like a `PUT` body; its
+/// alpha-2 code must stay the same.
+pub fn apply_country_patch(current: &Country, patch: &serde_json::Value) -> Result<Country, ApiError> {
+    // Merge the patch into the stored representation and read it back
+    let mut document = serde_json::to_value(current).expect("Country always serializes to JSON");
+    if patch.get("currency").is_some() && patch.get("currencies").is_none() {
+        // A new main currency replaces the old one among the legal tenders
+        let others: Vec<&String> = current.currencies.iter().filter(|code| **code != current.currency).collect();
+        document["currencies"] = serde_json::json!(others);
+    }
+    merge_patch(&mut document, patch);
+    let patched: Country = serde_json::from_value(document)
+        .map_err(|e| ApiError::InvalidBody(format!("Patched country is incomplete: {}", e)))?;
+    let patched = validation::normalize_country(patched);
+    
+    if patched.code != current.code {
+        return Err(ApiError::BadRequest(format!(
+            "The country code cannot be changed from {} to {}",
+            current.code, patched.code
+        )));
+    }
+    validation::validate_country_update(&patched, current)?;
+    
+    Ok(patched)
+}
+
+/// Endpoint handler to partially update an existing country
+///
+/// # Route
+/// `PATCH /countries/{code}`
+///
+/// # Parameters
+/// * `path` - The country code in ISO 3166-1 alpha-2, alpha-3 or numeric form
+///   (e.g., "US", "USA" or "840") extracted from the URL path
+///
+/// # Request Body
+/// JSON Merge Patch (RFC 7396) document holding only the fields to change,
+/// e.g. `{"capital": "Bonn"}`
+///
+/// # Returns
+/// * `200 OK` with the merged country data if successful
+/// * `400 Bad Request` if the path code is malformed, or the patch is not an
+///   object, removes a required field or changes the country code
+/// * `404 Not Found` if the country code doesn't exist
+/// * `422 Unprocessable Entity` with per-field errors if the merged country is invalid
+#[utoipa::path(
+    patch,
+    path = "/countries/{code}",
+    params(
+        ("code" = String, Path, description = "ISO 3166-1 alpha-2, alpha-3 or numeric country code")
+    ),
+    request_body(
+        content = Object,
+        description = "JSON Merge Patch with the country fields to change",
+        content_type = "application/merge-patch+json"
+    ),
+    responses(
+        (status = 200, description = "Country updated successfully", body = Country),
+        (status = 400, description = "Malformed country code or patch, or attempt to change the country code",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Country not found",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 422, description = "Patched country failed validation",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[patch("/countries/{code}")]
+pub async fn patch_country(
+    path: web::Path<String>,
+    patch: web::Json<serde_json::Value>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let code = country_code_param(&path.into_inner())?;
+    let patch = patch.into_inner();
+    
+    if !patch.is_object() {
+        return Err(ApiError::InvalidBody(String::from("A merge patch must be a JSON object")));
+    }
+    
+    let lookup = code.clone();
+    let patched = data
+        .with_repo(move |repo| match repo.get(&lookup)? {
+            Some(existing) => repo.patch(&existing.code, &patch),
+            None => Ok(None),
+        })
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Country with code {} not found", code)))?;
+    
+    Ok(HttpResponse::Ok().json(patched))
+}
+
+/// Endpoint handler to delete a country
+///
+/// # Route
+/// `DELETE /countries/{code}`
+///
+/// # Parameters
+/// * `path` - The country code in ISO 3166-1 alpha-2, alpha-3 or numeric form
+///   (e.g., "US", "USA" or "840") extracted from the URL path
+///
+/// # Returns
+/// * `204 No Content` if the country was successfully deleted
+/// * `400 Bad Request` if the code is not shaped like an ISO 3166-1 code
+/// * `404 Not Found` if the country code doesn't exist
+#[utoipa::path(
+    delete,
+    path = "/countries/{code}",
+    params(
+        ("code" = String, Path, description = "ISO 3166-1 alpha-2, alpha-3 or numeric country code")
+    ),
+    responses(
+        (status = 204, description = "Country deleted successfully"),
+        (status = 400, description = "Malformed country code",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Country not found",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[delete("/countries/{code}")]
+pub async fn delete_country(
+    path: web::Path<String>,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let code = country_code_param(&path.into_inner())?;
+    let lookup = code.clone();
+    
+    let deleted = data
+        .with_repo(move |repo| match repo.get(&lookup)? {
+            Some(existing) => repo.delete(&existing.code),
+            None => Ok(false),
+        })
+        .await?;
+    
+    if !deleted {
+        return Err(ApiError::NotFound(format!("Country with code {} not found", code)));
+    }
+    
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Largest dataset accepted by `POST /countries/import`, in bytes
+pub const MAX_IMPORT_BYTES: usize = 16 * 1024 * 1024;
+
+/// Query parameters accepted by `POST /countries/import`
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportQuery {
+    /// How imported rows are combined with stored countries (defaults to `insert-only`)
+    pub mode: Option<ImportMode>,
+    /// Format of the dataset; defaults to the one named by `Content-Type`
+    pub format: Option<ImportFormat>,
+}
+
+/// Endpoint handler that imports many countries at once
+///
+/// # Route
+/// `POST /countries/import`
+///
+/// # Parameters
+/// * `query` - The import `mode` and, optionally, the dataset `format`
+///
+/// # Request Body
+/// A CSV file with a header row (`text/csv`), a JSON array of countries
+/// (`application/json`) or one JSON country per line (`application/x-ndjson`)
+///
+/// # Returns
+/// * `200 OK` with a report listing the inserted, updated, skipped and failed
+///   rows by line number. Valid rows are written in a single transaction even
+///   if other rows fail.
+/// * `400 Bad Request` if the dataset as a whole cannot be read
+/// * `415 Unsupported Media Type` if the format cannot be determined
+#[utoipa::path(
+    post,
+    path = "/countries/import",
+    params(ImportQuery),
+    request_body(
+        content = String,
+        description = "Dataset as CSV, a JSON array or NDJSON",
+        content_type = "text/csv"
+    ),
+    responses(
+        (status = 200, description = "Import report", body = ImportReport),
+        (status = 400, description = "Unreadable dataset or invalid parameters",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 415, description = "Unsupported dataset format",
+            body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 500, description = "Internal server error",
+            body = ProblemDetails, content_type = "application/problem+json")
+    )
+)]
+#[post("/countries/import")]
+pub async fn import_countries(
+    req: HttpRequest,
+    query: web::Query<ImportQuery>,
+    body: web::Bytes,
+    data: web::Data<AppState>
+) -> Result<HttpResponse, ApiError> {
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let format = query
+        .format
+        .or_else(|| ImportFormat::from_media_type(content_type))
+        .ok_or_else(|| ApiError::UnsupportedMediaType(format!(
+            "Cannot import {:?}; send text/csv, application/json or application/x-ndjson, or pass format",
+            content_type
+        )))?;
+    
+    let text = std::str::from_utf8(&body)
+        .map_err(|_| ApiError::InvalidBody(String::from("The dataset must be UTF-8 encoded")))?;
+    let batch = import::parse(format, query.mode.unwrap_or_default(), text)?;
+    
+    let report = data.with_repo(move |repo| repo.import(batch)).await?;
+    
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// Documents the JSON and NDJSON bodies of `POST /countries/import`
+///
+/// `utoipa` only describes one media type per request body, so the others
+/// are added after the fact.
+struct ImportMediaTypes;
+
+impl Modify for ImportMediaTypes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let body = openapi
+            .paths
+            .paths
+            .get_mut("/countries/import")
+            .and_then(|path| path.operations.values_mut().next())
+            .and_then(|operation| operation.request_body.as_mut());
+        
+        if let Some(body) = body {
+            let array = utoipa::openapi::ArrayBuilder::new().items(utoipa::openapi::Ref::from_schema_name("Country"));
+            let text = ObjectBuilder::new().schema_type(SchemaType::String);
+            body.content.insert(String::from("application/json"), ContentBuilder::new().schema(array).build());
+            body.content.insert(String::from("application/x-ndjson"), ContentBuilder::new().schema(text).build());
+        }
+    }
+}
+
+/// Lists the export media types on the `200` responses of the read endpoints
+///
+/// Every format carries the fields of the JSON schema, so that schema is
+/// reused for the other media types.
+struct ExportMediaTypes;
+
+impl Modify for ExportMediaTypes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let paths = [
+            "/countries",
+            "/countries/search",
+            "/countries/nearest",
+            "/countries/at",
+            "/countries/{code}",
+            "/countries/{from}/distance/{to}",
+            "/countries/{code}/neighbors",
+            "/countries/{from}/route/{to}",
+            "/countries/{code}/timezones",
+            "/countries/{code}/time",
+            "/countries/{code}/holidays",
+            "/countries/{code}/is-holiday",
+            "/countries/{code}/business-days",
+            "/countries/by-calling-code/{code}",
+            "/countries/by-tld/{tld}",
+            "/regions",
+            "/regions/{code}",
+            "/countries/region/{region}",
+            "/currencies",
+            "/currencies/{code}",
+            "/currencies/{code}/countries",
+            "/languages",
+            "/languages/{code}",
+            "/languages/{code}/countries",
+        ];
+        
+        for path in paths {
+            let response = openapi
+                .paths
+                .paths
+                .get_mut(path)
+                .and_then(|item| item.operations.get_mut(&utoipa::openapi::PathItemType::Get))
+                .and_then(|operation| operation.responses.responses.get_mut("200"));
+            let Some(utoipa::openapi::RefOr::T(response)) = response else {
+                continue;
+            };
+            let Some(schema) = response.content.get("application/json").map(|content| content.schema.clone()) else {
+                continue;
+            };
+            for (media_type, _) in export::MEDIA_TYPES.iter().skip(1) {
+                response.content.insert(media_type.to_string(), ContentBuilder::new().schema(schema.clone()).build());
+            }
+        }
+    }
+}
+
+/// Configures the web service by registering all API endpoints
+///
+/// This function is used in the main application to set up all the routes.
+///
+/// # Parameters
+/// * `cfg` - Service configuration object provided by Actix web
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(web::JsonConfig::default().error_handler(error::json_error_handler))
+       .app_data(web::QueryConfig::default().error_handler(error::query_error_handler))
+       .app_data(web::PathConfig::default().error_handler(error::path_error_handler))
+       .app_data(web::PayloadConfig::new(MAX_IMPORT_BYTES));
+    
+    // Literal `/countries/...` paths go before the `{code}` routes, which
+    // would otherwise take e.g. `/countries/by-tld/time` for a country's time
     cfg.service(all_countries)
+       .service(country_search)
+       .service(countries_nearest)
+       .service(country_at)
+       .service(countries_geojson)
+       .service(countries_by_calling_code)
+       .service(countries_by_tld)
+       .service(countries_by_region)
+       .service(country_geojson)
        .service(country_by_code)
+       .service(capital_distance)
+       .service(country_neighbors)
+       .service(land_route)
+       .service(country_timezones)
+       .service(country_time)
+       .service(country_holidays)
+       .service(country_is_holiday)
+       .service(country_business_days)
        .service(get_regions)
-       .service(countries_by_region)
+       .service(region_by_code)
+       .service(all_currencies)
+       .service(currency_by_code)
+       .service(countries_by_currency)
+       .service(all_languages)
+       .service(language_by_code)
+       .service(countries_by_language)
        .service(add_country)
        .service(update_country)
-       .service(delete_country);
+       .service(patch_country)
+       .service(delete_country)
+       .service(import_countries)
+       .service(validate_phone);
 }
 
 /// API documentation with OpenAPI
@@ -536,16 +2633,46 @@ pub fn config(cfg: &mut web::ServiceConfig) {
 #[openapi(
     paths(
         all_countries,
+        country_search,
+        countries_nearest,
+        country_at,
+        countries_geojson,
+        country_geojson,
         country_by_code,
+        capital_distance,
+        country_neighbors,
+        land_route,
+        country_timezones,
+        country_time,
+        country_holidays,
+        country_is_holiday,
+        country_business_days,
+        countries_by_calling_code,
+        countries_by_tld,
         get_regions,
+        region_by_code,
         countries_by_region,
+        all_currencies,
+        currency_by_code,
+        countries_by_currency,
+        all_languages,
+        language_by_code,
+        countries_by_language,
         add_country,
         update_country,
-        delete_country
+        patch_country,
+        delete_country,
+        import_countries,
+        validate_phone
     ),
     components(
-        schemas(Country)
+        schemas(Country, SearchResult, ProblemDetails, FieldError, ImportMode, ImportFormat,
+            ImportReport, ImportedRow, FailedRow, ExportFormat, Currency, Language, CountryLanguage,
+            LanguageStatus, Region, RegionDetails, RegionLevel, NearbyCountry, CapitalDistance,
+            Feature, FeatureCollection, GeometryKind, PhoneNumberRequest, PhoneValidation,
+            TimeZoneInfo, LocalTime, Holiday, HolidayCheck, BusinessDays, Weekday, DrivingSide)
     ),
+    modifiers(&ImportMediaTypes, &ExportMediaTypes),
     tags(
         (name = "World Countries API", description = "API for accessing country information")
     ),
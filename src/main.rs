@@ -1,47 +1,179 @@
+use std::path::Path;
+
 use actix_cors::Cors;
-use actix_web::{web, App, HttpServer};
+use actix_web::{middleware::Logger, web, App, HttpServer};
+use clap::Parser;
 
 // Re-export the module from lib.rs
 pub use world_countries_api::*;
+use world_countries_api::settings::{Cli, Command};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    println!("Starting World Countries API server at http://127.0.0.1:8080");
-    println!("API documentation available at http://127.0.0.1:8080/swagger-ui/");
+    let cli = Cli::parse();
     
-    // Initialize the database
-    let mut conn = match init_db() {
-        Ok(conn) => conn,
+    let settings = match Settings::load(&cli.settings) {
+        Ok(settings) => settings,
         Err(e) => {
-            eprintln!("Failed to initialize database: {}", e);
-            return Err(std::io::Error::new(std::io::ErrorKind::Other, "Database initialization failed"));
+            eprintln!("Failed to load settings: {}", e);
+            return Err(std::io::Error::other("Invalid configuration"));
         }
     };
     
-    // Seed the database with initial data
-    if let Err(e) = seed_countries(&mut conn) {
-        eprintln!("Failed to seed database: {}", e);
-        return Err(std::io::Error::new(std::io::ErrorKind::Other, "Database seeding failed"));
+    env_logger::Builder::new().parse_filters(&settings.log_level).init();
+    
+    match &cli.command {
+        Some(Command::Migrate) => return migrate(&settings),
+        Some(Command::Import { file, mode, format }) => return import_dataset(&settings, file, *mode, *format),
+        Some(Command::Boundaries { file }) => return load_boundaries(&settings, file),
+        Some(Command::Serve) | None => {}
     }
     
-    // Create app state with database connection
-    let app_state = web::Data::new(AppState {
-        db: std::sync::Mutex::new(conn),
-    });
+    let (host, port) = settings.bind_address();
+    log::info!("Starting World Countries API server at http://{}:{}", host, port);
+    log::info!("API documentation available at http://{}:{}/swagger-ui/", host, port);
+    
+    // Open the connection pool
+    let pool = match create_pool(&settings.database, settings.pool_size) {
+        Ok(pool) => pool,
+        Err(e) => {
+            log::error!("Failed to open database {}: {}", settings.database, e);
+            return Err(std::io::Error::other("Database initialization failed"));
+        }
+    };
+    
+    // Initialize and seed the database
+    let initialized = pool
+        .get()
+        .map_err(|e| e.to_string())
+        .and_then(|mut conn| {
+            init_schema(&conn).map_err(|e| e.to_string())?;
+            seed_countries(&mut conn).map_err(|e| e.to_string())
+        });
+    if let Err(e) = initialized {
+        log::error!("Failed to initialize database {}: {}", settings.database, e);
+        return Err(std::io::Error::other("Database initialization failed"));
+    }
+    
+    // Create app state with the connection pool
+    let app_state = web::Data::new(AppState::new(SqliteRepository::new(pool)));
     
-    HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_origin()
+    let cors_settings = settings.clone();
+    let mut server = HttpServer::new(move || {
+        let mut cors = Cors::default()
             .allow_any_method()
             .allow_any_header();
+        if cors_settings.allows_any_origin() {
+            cors = cors.allow_any_origin();
+        } else {
+            for origin in &cors_settings.cors_origins {
+                cors = cors.allowed_origin(origin);
+            }
+        }
             
         App::new()
             .wrap(cors)
+            .wrap(Logger::default())
             .app_data(app_state.clone())
             .configure(config)
             .configure(configure_api_docs)
-    })
-    .bind("127.0.0.1:8080")?
-    .run()
-    .await
+    });
+    
+    if let Some(workers) = settings.workers {
+        server = server.workers(workers);
+    }
+    
+    server
+        .bind((host, port))?
+        .run()
+        .await
+}
+
+/// Applies pending migrations to the configured database and reports its version
+fn migrate(settings: &Settings) -> std::io::Result<()> {
+    let conn = rusqlite::Connection::open(&settings.database).map_err(std::io::Error::other)?;
+    match migrations::migrate(&conn) {
+        Ok(applied) => {
+            println!(
+                "Applied {} migration(s); {} is at schema version {}",
+                applied.len(),
+                settings.database,
+                migrations::latest_version()
+            );
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("Failed to migrate {}: {}", settings.database, e);
+            Err(std::io::Error::other("Migration failed"))
+        }
+    }
+}
+
+/// Imports a dataset file into the configured database and prints the report
+fn import_dataset(settings: &Settings, file: &Path, mode: ImportMode, format: Option<ImportFormat>) -> std::io::Result<()> {
+    let Some(format) = format.or_else(|| ImportFormat::from_path(file)) else {
+        eprintln!("Cannot tell the format of {}; pass --format", file.display());
+        return Err(std::io::Error::other("Unknown dataset format"));
+    };
+    let text = std::fs::read_to_string(file)?;
+    
+    let result = create_pool(&settings.database, 1)
+        .map_err(ApiError::from)
+        .and_then(|pool| {
+            let conn = pool.get()?;
+            init_schema(&conn).map_err(|e| ApiError::Internal(e.to_string()))?;
+            drop(conn);
+            let batch = import::parse(format, mode, &text)?;
+            SqliteRepository::new(pool).import(batch)
+        });
+    
+    match result {
+        Ok(report) => {
+            println!("{}", serde_json::to_string_pretty(&report).map_err(std::io::Error::other)?);
+            if report.failed.is_empty() {
+                Ok(())
+            } else {
+                Err(std::io::Error::other(format!("{} row(s) failed to import", report.failed.len())))
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to import {}: {}", file.display(), e);
+            Err(std::io::Error::other("Import failed"))
+        }
+    }
+}
+
+/// Loads the country boundaries of a GeoJSON file into the configured database
+fn load_boundaries(settings: &Settings, file: &Path) -> std::io::Result<()> {
+    let text = std::fs::read_to_string(file)?;
+    
+    let result = create_pool(&settings.database, 1)
+        .map_err(ApiError::from)
+        .and_then(|pool| {
+            let mut conn = pool.get()?;
+            init_schema(&conn).map_err(|e| ApiError::Internal(e.to_string()))?;
+            seed_countries(&mut conn)?;
+            drop(conn);
+            let (boundaries, skipped) = boundaries::parse_geojson(&text)?;
+            let found = boundaries.len();
+            let loaded = SqliteRepository::new(pool).load_boundaries(boundaries)?;
+            let unknown = found - loaded.len();
+            Ok((loaded, unknown, skipped))
+        });
+    
+    match result {
+        Ok((loaded, unknown, skipped)) => {
+            println!(
+                "Loaded the boundaries of {} countries; ignored {} unknown countries and {} features without a code or polygon",
+                loaded.len(),
+                unknown,
+                skipped
+            );
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("Failed to load boundaries from {}: {}", file.display(), e);
+            Err(std::io::Error::other("Loading boundaries failed"))
+        }
+    }
 }
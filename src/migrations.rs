@@ -0,0 +1,361 @@
+//! Versioned schema migrations for the countries database
+//!
+//! Migrations are plain SQL files under `migrations/`, embedded in the binary
+//! and applied in order. The schema version of a database is stored in
+//! `PRAGMA user_version`; each migration runs in its own transaction together
+//! with the version bump, so a failed migration leaves the database at the
+//! previous version.
+//!
+//! Databases created before migrations existed have a `user_version` of 0.
+//! Their version is inferred from the tables and columns they already have,
+//! so that only the missing migrations are applied.
+//!
+//! The SQL files only change the schema. Migrations adding per-country data
+//! have a backfill, run in the same transaction, that fills in the new
+//! columns of the countries already stored from the embedded seed data.
+
+use std::fmt;
+
+use rusqlite::{params, Connection, Transaction, TransactionBehavior};
+
+use crate::{seed_data, Country, DrivingSide, Weekday};
+
+/// A schema change taking the database from `version - 1` to `version`
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    pub sql: &'static str,
+    /// Fills in the data the migration adds for the countries stored before it
+    pub backfill: Option<fn(&Connection) -> rusqlite::Result<()>>,
+}
+
+/// Every migration, ordered by version
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_countries",
+        sql: include_str!("../migrations/0001_create_countries.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 2,
+        name: "add_iso_codes",
+        sql: include_str!("../migrations/0002_add_iso_codes.sql"),
+        backfill: Some(backfill_iso_codes),
+    },
+    Migration {
+        version: 3,
+        name: "search_index",
+        sql: include_str!("../migrations/0003_search_index.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 4,
+        name: "currencies",
+        sql: include_str!("../migrations/0004_currencies.sql"),
+        backfill: Some(backfill_currencies),
+    },
+    Migration {
+        version: 5,
+        name: "languages",
+        sql: include_str!("../migrations/0005_languages.sql"),
+        backfill: Some(backfill_languages),
+    },
+    Migration {
+        version: 6,
+        name: "regions",
+        sql: include_str!("../migrations/0006_regions.sql"),
+        backfill: Some(backfill_regions),
+    },
+    Migration {
+        version: 7,
+        name: "geography",
+        sql: include_str!("../migrations/0007_geography.sql"),
+        backfill: Some(backfill_geography),
+    },
+    Migration {
+        version: 8,
+        name: "boundaries",
+        sql: include_str!("../migrations/0008_boundaries.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 9,
+        name: "borders",
+        sql: include_str!("../migrations/0009_borders.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 10,
+        name: "calling_codes",
+        sql: include_str!("../migrations/0010_calling_codes.sql"),
+        backfill: Some(backfill_calling_codes),
+    },
+    Migration {
+        version: 11,
+        name: "timezones",
+        sql: include_str!("../migrations/0011_timezones.sql"),
+        backfill: Some(backfill_timezones),
+    },
+    Migration {
+        version: 12,
+        name: "holidays",
+        sql: include_str!("../migrations/0012_holidays.sql"),
+        backfill: None,
+    },
+    Migration {
+        version: 13,
+        name: "working_week",
+        sql: include_str!("../migrations/0013_working_week.sql"),
+        backfill: Some(backfill_working_week),
+    },
+];
+
+/// Runs `update` for every stored country that is part of the seed data
+fn backfill(conn: &Connection, sql: &str, update: impl Fn(&Country) -> Vec<rusqlite::types::Value>) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(sql)?;
+    for entry in seed_data() {
+        let mut values = vec![rusqlite::types::Value::Text(entry.country.code.clone())];
+        values.extend(update(&entry.country));
+        stmt.execute(rusqlite::params_from_iter(values))?;
+    }
+    Ok(())
+}
+
+fn json_list<T: serde::Serialize>(values: &[T]) -> String {
+    serde_json::json!(values).to_string()
+}
+
+fn backfill_iso_codes(conn: &Connection) -> rusqlite::Result<()> {
+    backfill(conn, "UPDATE countries SET alpha3 = ?2, numeric = ?3 WHERE code = ?1", |country| {
+        vec![country.alpha3.clone().into(), country.numeric.clone().into()]
+    })
+}
+
+/// Links the countries to the legal tenders they use besides their main currency
+fn backfill_currencies(conn: &Connection) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(
+        "INSERT OR IGNORE INTO country_currencies (country_code, currency_code, position)
+         SELECT code, ?2, ?3 FROM countries WHERE code = ?1 AND currency <> ''",
+    )?;
+    for entry in seed_data() {
+        for (position, currency) in entry.country.currencies.iter().enumerate().skip(1) {
+            stmt.execute(params![entry.country.code, currency, position])?;
+        }
+    }
+    Ok(())
+}
+
+fn backfill_languages(conn: &Connection) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(
+        "INSERT INTO country_languages (country_code, language_code, status, position)
+         SELECT code, ?2, ?3, ?4 FROM countries WHERE code = ?1",
+    )?;
+    for entry in seed_data() {
+        for (position, language) in entry.country.languages.iter().enumerate() {
+            stmt.execute(params![entry.country.code, language.code, language.status.as_str(), position])?;
+        }
+    }
+    Ok(())
+}
+
+fn backfill_regions(conn: &Connection) -> rusqlite::Result<()> {
+    backfill(conn, "UPDATE countries SET region_code = ?2 WHERE code = ?1", |country| {
+        vec![country.region_code.clone().into()]
+    })
+}
+
+fn backfill_geography(conn: &Connection) -> rusqlite::Result<()> {
+    backfill(
+        conn,
+        "UPDATE countries SET capital_latitude = ?2, capital_longitude = ?3, latitude = ?4, longitude = ?5,
+             area_km2 = ?6, bbox_west = ?7, bbox_south = ?8, bbox_east = ?9, bbox_north = ?10
+         WHERE code = ?1",
+        |country| {
+            let edge = |index: usize| country.bbox.as_ref().and_then(|bbox| bbox.get(index).copied());
+            [
+                country.capital_latitude,
+                country.capital_longitude,
+                country.latitude,
+                country.longitude,
+                country.area_km2,
+                edge(0),
+                edge(1),
+                edge(2),
+                edge(3),
+            ]
+            .into_iter()
+            .map(Into::into)
+            .collect()
+        },
+    )
+}
+
+fn backfill_calling_codes(conn: &Connection) -> rusqlite::Result<()> {
+    backfill(conn, "UPDATE countries SET calling_codes = ?2, tlds = ?3 WHERE code = ?1", |country| {
+        vec![json_list(&country.calling_codes).into(), json_list(&country.tlds).into()]
+    })
+}
+
+fn backfill_timezones(conn: &Connection) -> rusqlite::Result<()> {
+    backfill(conn, "UPDATE countries SET timezones = ?2 WHERE code = ?1", |country| {
+        vec![json_list(&country.timezones).into()]
+    })
+}
+
+fn backfill_working_week(conn: &Connection) -> rusqlite::Result<()> {
+    backfill(
+        conn,
+        "UPDATE countries SET weekend = ?2, first_day_of_week = ?3, driving_side = ?4 WHERE code = ?1",
+        |country| {
+            vec![
+                json_list(&country.weekend).into(),
+                country.first_day_of_week.map(Weekday::as_str).map(String::from).into(),
+                country.driving_side.map(DrivingSide::as_str).map(String::from).into(),
+            ]
+        },
+    )
+}
+
+/// Schema version of a fully migrated database
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+/// Errors raised while migrating a database
+#[derive(Debug)]
+pub enum MigrationError {
+    /// A migration or the version bookkeeping failed
+    Sqlite(rusqlite::Error),
+    /// The database was migrated by a newer build of the API
+    TooNew { found: u32, latest: u32 },
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::Sqlite(e) => write!(f, "migration failed: {}", e),
+            MigrationError::TooNew { found, latest } => write!(
+                f,
+                "database schema version {} is newer than the latest supported version {}",
+                found, latest
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<rusqlite::Error> for MigrationError {
+    fn from(e: rusqlite::Error) -> Self {
+        MigrationError::Sqlite(e)
+    }
+}
+
+/// Returns the schema version of the database
+///
+/// For an unversioned database this is the version its existing schema
+/// corresponds to, or 0 if it is empty.
+pub fn current_version(conn: &Connection) -> rusqlite::Result<u32> {
+    let version: u32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version > 0 {
+        return Ok(version);
+    }
+    inferred_version(conn)
+}
+
+/// Infers the version of a database created before migrations were tracked
+fn inferred_version(conn: &Connection) -> rusqlite::Result<u32> {
+    let has_table = |name: &str| -> rusqlite::Result<bool> {
+        conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            [name],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|count| count > 0)
+    };
+
+    if !has_table("countries")? {
+        return Ok(0);
+    }
+    let has_column = |name: &str| -> rusqlite::Result<bool> {
+        conn.query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('countries') WHERE name = ?1",
+            [name],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|count| count > 0)
+    };
+    if !has_column("alpha3")? {
+        return Ok(1);
+    }
+    if !has_table("countries_fts")? {
+        return Ok(2);
+    }
+    if !has_table("currencies")? {
+        return Ok(3);
+    }
+    if !has_table("languages")? {
+        return Ok(4);
+    }
+    if !has_table("regions")? {
+        return Ok(5);
+    }
+    if !has_column("area_km2")? {
+        return Ok(6);
+    }
+    if !has_table("boundary_polygons")? {
+        return Ok(7);
+    }
+    if !has_table("borders")? {
+        return Ok(8);
+    }
+    if !has_column("calling_codes")? {
+        return Ok(9);
+    }
+    if !has_column("timezones")? {
+        return Ok(10);
+    }
+    if !has_table("holiday_rules")? {
+        return Ok(11);
+    }
+    if !has_column("weekend")? {
+        return Ok(12);
+    }
+    Ok(13)
+}
+
+/// Applies every pending migration, returning the versions that were applied
+///
+/// Each migration takes an immediate transaction and re-reads the version
+/// first, so concurrent processes migrating the same file apply it only once.
+pub fn migrate(conn: &Connection) -> Result<Vec<u32>, MigrationError> {
+    let latest = latest_version();
+    let mut applied = Vec::new();
+
+    for migration in MIGRATIONS {
+        let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+        let version = current_version(&tx)?;
+        if version > latest {
+            return Err(MigrationError::TooNew { found: version, latest });
+        }
+        if version >= migration.version {
+            // Record an inferred version so later runs don't have to guess
+            tx.pragma_update(None, "user_version", version)?;
+            tx.commit()?;
+            continue;
+        }
+
+        log::info!("Applying migration {:04} {}", migration.version, migration.name);
+        tx.execute_batch(migration.sql)?;
+        if let Some(backfill) = migration.backfill {
+            backfill(&tx)?;
+        }
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+        applied.push(migration.version);
+    }
+
+    Ok(applied)
+}
@@ -0,0 +1,193 @@
+//! International calling codes and phone number validation
+//!
+//! Calling codes are written in E.164 form: a `+` and the ITU country code,
+//! followed after a space by the area code that sets a country apart when it
+//! shares its country code with others (e.g. `+1 809` for the Dominican
+//! Republic within the North American Numbering Plan). ITU country codes are
+//! prefix-free, so the first one to three digits of a number in
+//! international format always name exactly one of them.
+
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::Country;
+
+/// Most digits in a phone number in international format (E.164)
+pub const MAX_DIGITS: usize = 15;
+
+/// Lengths of the national significant numbers used under each ITU country
+/// code, after the country code itself
+const NATIONAL_NUMBER_LENGTHS: &[(&str, usize, usize)] = &[
+    ("1", 10, 10), ("7", 10, 10), ("20", 8, 10), ("27", 9, 9), ("30", 10, 10), ("31", 9, 9), ("32", 8, 9),
+    ("33", 9, 9), ("34", 9, 9), ("36", 8, 9), ("39", 6, 11), ("40", 9, 9), ("41", 9, 9), ("43", 4, 13),
+    ("44", 9, 10), ("45", 8, 8), ("46", 7, 10), ("47", 8, 8), ("48", 9, 9), ("49", 6, 13), ("51", 8, 9),
+    ("52", 10, 10), ("53", 6, 8), ("54", 10, 11), ("55", 10, 11), ("56", 9, 9), ("57", 8, 10), ("58", 10, 10),
+    ("60", 8, 10), ("61", 9, 9), ("62", 8, 12), ("63", 8, 10), ("64", 8, 10), ("65", 8, 8), ("66", 8, 9),
+    ("81", 9, 10), ("82", 8, 10), ("84", 9, 10), ("86", 10, 11), ("90", 10, 10), ("91", 10, 10), ("92", 9, 10),
+    ("93", 9, 9), ("94", 9, 9), ("95", 7, 10), ("98", 10, 10), ("211", 9, 9), ("212", 9, 9), ("213", 8, 9),
+    ("216", 8, 8), ("218", 9, 9), ("220", 7, 7), ("221", 9, 9), ("222", 8, 8), ("223", 8, 8), ("224", 8, 9),
+    ("225", 10, 10), ("226", 8, 8), ("227", 8, 8), ("228", 8, 8), ("229", 8, 10), ("230", 7, 8), ("231", 7, 9),
+    ("232", 8, 8), ("233", 9, 9), ("234", 8, 10), ("235", 8, 8), ("236", 8, 8), ("237", 9, 9), ("238", 7, 7),
+    ("239", 7, 7), ("240", 9, 9), ("241", 7, 8), ("242", 9, 9), ("243", 9, 9), ("244", 9, 9), ("245", 7, 9),
+    ("246", 7, 7), ("248", 7, 7), ("249", 9, 9), ("250", 9, 9), ("251", 9, 9), ("252", 7, 9), ("253", 8, 8),
+    ("254", 9, 10), ("255", 9, 9), ("256", 9, 9), ("257", 8, 8), ("258", 8, 9), ("260", 9, 9), ("261", 9, 9),
+    ("262", 9, 9), ("263", 9, 9), ("264", 8, 9), ("265", 7, 9), ("266", 8, 8), ("267", 7, 8), ("268", 8, 8),
+    ("269", 7, 7), ("290", 4, 5), ("291", 7, 7), ("297", 7, 7), ("298", 6, 6), ("299", 6, 6), ("350", 8, 8),
+    ("351", 9, 9), ("352", 4, 11), ("353", 7, 9), ("354", 7, 7), ("355", 8, 9), ("356", 8, 8), ("357", 8, 8),
+    ("358", 5, 12), ("359", 8, 9), ("370", 8, 8), ("371", 8, 8), ("372", 7, 8), ("373", 8, 8), ("374", 8, 8),
+    ("375", 9, 9), ("376", 6, 6), ("377", 8, 9), ("378", 6, 10), ("380", 9, 9), ("381", 8, 10), ("382", 8, 8),
+    ("385", 8, 9), ("386", 8, 8), ("387", 8, 9), ("389", 8, 8), ("420", 9, 9), ("421", 9, 9), ("423", 7, 7),
+    ("500", 5, 5), ("501", 7, 7), ("502", 8, 8), ("503", 8, 8), ("504", 8, 8), ("505", 8, 8), ("506", 8, 8),
+    ("507", 7, 8), ("508", 6, 6), ("509", 8, 8), ("590", 9, 9), ("591", 8, 8), ("592", 7, 7), ("593", 8, 9),
+    ("594", 9, 9), ("595", 9, 9), ("596", 9, 9), ("597", 6, 7), ("598", 8, 8), ("599", 7, 7), ("670", 7, 8),
+    ("672", 6, 6), ("673", 7, 7), ("674", 7, 7), ("675", 7, 8), ("676", 5, 7), ("677", 5, 7), ("678", 5, 7),
+    ("679", 7, 7), ("680", 7, 7), ("681", 6, 6), ("682", 5, 5), ("683", 4, 7), ("685", 5, 7), ("686", 8, 8),
+    ("687", 6, 6), ("688", 5, 6), ("689", 8, 8), ("690", 4, 7), ("691", 7, 7), ("692", 7, 7), ("850", 8, 10),
+    ("852", 8, 8), ("853", 8, 8), ("855", 8, 9), ("856", 8, 10), ("880", 10, 10), ("886", 8, 9), ("960", 7, 7),
+    ("961", 7, 8), ("962", 8, 9), ("963", 9, 9), ("964", 10, 10), ("965", 8, 8), ("966", 9, 9), ("967", 7, 9),
+    ("968", 8, 8), ("970", 9, 9), ("971", 8, 9), ("972", 8, 9), ("973", 8, 8), ("974", 8, 8), ("975", 7, 8),
+    ("976", 8, 8), ("977", 8, 10), ("992", 9, 9), ("993", 8, 8), ("994", 9, 9), ("995", 9, 9), ("996", 9, 9),
+    ("998", 9, 9),
+];
+
+/// Normalizes a calling code received from a client
+///
+/// Surrounding whitespace is trimmed, a missing `+` is added and the country
+/// and area code are separated by a single space, so "44", "+44" and
+/// " +1  809" become "+44", "+44" and "+1 809".
+pub fn normalize_calling_code(code: &str) -> String {
+    let parts: Vec<&str> = code.trim().trim_start_matches('+').split_whitespace().collect();
+    format!("+{}", parts.join(" "))
+}
+
+/// Whether a normalized calling code is well formed: a country code of one
+/// to three digits not starting with 0, optionally followed by a space and
+/// an area code of up to six digits
+pub fn is_calling_code(code: &str) -> bool {
+    let Some(rest) = code.strip_prefix('+') else {
+        return false;
+    };
+    let (country_code, area_code) = rest.split_once(' ').unwrap_or((rest, ""));
+    let digits = |text: &str, max: usize| text.len() <= max && text.chars().all(|c| c.is_ascii_digit());
+    !country_code.is_empty()
+        && !country_code.starts_with('0')
+        && digits(country_code, 3)
+        && digits(area_code, 6)
+        && (area_code.is_empty() != rest.contains(' '))
+}
+
+/// The digits of a calling code, e.g. `1809` for `+1 809`
+pub fn calling_code_digits(code: &str) -> String {
+    code.chars().filter(char::is_ascii_digit).collect()
+}
+
+/// The digits of the ITU country code of a calling code, e.g. `1` for `+1 809`
+pub fn country_code_digits(code: &str) -> &str {
+    let code = code.trim_start_matches('+');
+    code.split(' ').next().unwrap_or(code)
+}
+
+/// Whether a country is reached through `digits`, either its full calling
+/// code (e.g. `1809`) or its ITU country code (e.g. `1`)
+pub fn has_calling_code(country: &Country, digits: &str) -> bool {
+    country
+        .calling_codes
+        .iter()
+        .any(|code| calling_code_digits(code) == digits || country_code_digits(code) == digits)
+}
+
+/// Lengths a national significant number may have under an ITU country code
+///
+/// Country codes missing from the table accept any length that keeps the
+/// whole number within [`MAX_DIGITS`].
+pub fn national_number_lengths(country_code: &str) -> RangeInclusive<usize> {
+    NATIONAL_NUMBER_LENGTHS
+        .iter()
+        .find(|(code, _, _)| *code == country_code)
+        .map_or(4..=MAX_DIGITS - country_code.len(), |(_, min, max)| *min..=*max)
+}
+
+/// Reads the digits of a phone number in international format
+///
+/// The number must start with `+` or the international prefix `00`, and may
+/// separate its digits with spaces, hyphens, dots, slashes and parentheses.
+pub fn parse_number(number: &str) -> Result<String, String> {
+    let number = number.trim();
+    let rest = number
+        .strip_prefix('+')
+        .or_else(|| number.strip_prefix("00"))
+        .ok_or_else(|| String::from("must be in international format, starting with + or 00"))?;
+    if !rest.chars().all(|c| c.is_ascii_digit() || " -./()".contains(c)) {
+        return Err(String::from("may only contain digits, spaces, hyphens, dots, slashes and parentheses"));
+    }
+
+    let digits: String = rest.chars().filter(char::is_ascii_digit).collect();
+    if digits.starts_with('0') {
+        return Err(String::from("must not start its country code with 0"));
+    }
+    if digits.len() < 4 || digits.len() > MAX_DIGITS {
+        return Err(format!("must have between 4 and {} digits", MAX_DIGITS));
+    }
+    Ok(digits)
+}
+
+/// The country a number's digits belong to, and its matching calling code
+///
+/// `countries` are the countries sharing the number's ITU country code. The
+/// longest calling code the number starts with wins; countries sharing a
+/// whole numbering plan, such as Guadeloupe and Saint Martin under `+590`,
+/// resolve to the largest of them.
+pub fn match_country<'a>(countries: &'a [Country], digits: &str) -> Option<(&'a Country, &'a str)> {
+    countries
+        .iter()
+        .flat_map(|country| country.calling_codes.iter().map(move |code| (country, code.as_str())))
+        .filter(|(_, code)| digits.starts_with(&calling_code_digits(code)))
+        .max_by(|(a, first), (b, second)| {
+            calling_code_digits(first)
+                .len()
+                .cmp(&calling_code_digits(second).len())
+                .then_with(|| a.area_km2.unwrap_or(0.0).total_cmp(&b.area_km2.unwrap_or(0.0)))
+                .then_with(|| b.code.cmp(&a.code))
+        })
+}
+
+/// A phone number to validate
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct PhoneNumberRequest {
+    /// The number in international format, e.g. `+49 30 901820`
+    pub number: String,
+}
+
+/// The country of a phone number and the plausibility of its length
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct PhoneValidation {
+    /// The number in E.164 format, e.g. `+4930901820`
+    pub number: String,
+    /// The calling code of the country the number belongs to, e.g. `+49`
+    pub calling_code: String,
+    /// The national significant number, following the ITU country code
+    pub national_number: String,
+    /// Whether the national number has a length in use in the country
+    pub plausible_length: bool,
+    /// The country the number belongs to
+    pub country: Country,
+}
+
+impl PhoneValidation {
+    /// Validates the digits of a number against the countries sharing its
+    /// ITU country code, or returns `None` if none of them matches
+    pub fn of(digits: &str, countries: &[Country]) -> Option<Self> {
+        let (country, calling_code) = match_country(countries, digits)?;
+        let country_code = country_code_digits(calling_code);
+        let national_number = &digits[country_code.len()..];
+        Some(PhoneValidation {
+            number: format!("+{}", digits),
+            calling_code: calling_code.to_string(),
+            national_number: national_number.to_string(),
+            plausible_length: national_number_lengths(country_code).contains(&national_number.len()),
+            country: country.clone(),
+        })
+    }
+}
@@ -0,0 +1,146 @@
+//! UN M49 geographic regions
+//!
+//! The hierarchy (world → region → subregion → intermediate region) and the
+//! region every country belongs to are compiled into the binary from
+//! `data/regions.json`. The regions are loaded into the `regions` table when
+//! a database is seeded; countries without an explicit `region_code` get the
+//! one listed there.
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::Country;
+
+/// Level of a region in the M49 hierarchy
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum RegionLevel {
+    /// The whole world, the root of the hierarchy
+    World,
+    /// A continental region such as Africa or the Americas
+    Region,
+    /// A subregion such as Western Europe
+    Subregion,
+    /// An intermediate region such as the Caribbean or the Channel Islands
+    IntermediateRegion,
+}
+
+impl RegionLevel {
+    /// Name of the level as stored and serialized
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RegionLevel::World => "world",
+            RegionLevel::Region => "region",
+            RegionLevel::Subregion => "subregion",
+            RegionLevel::IntermediateRegion => "intermediate-region",
+        }
+    }
+
+    /// Parses a stored level name
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "world" => Some(RegionLevel::World),
+            "region" => Some(RegionLevel::Region),
+            "subregion" => Some(RegionLevel::Subregion),
+            "intermediate-region" => Some(RegionLevel::IntermediateRegion),
+            _ => None,
+        }
+    }
+}
+
+/// A UN M49 region
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct Region {
+    /// The M49 code (three digits, zero-padded)
+    pub code: String,
+    /// The English name of the region
+    pub name: String,
+    /// The M49 code of the enclosing region; `None` for the world
+    pub parent: Option<String>,
+    /// Level of the region in the hierarchy
+    pub level: RegionLevel,
+}
+
+/// A region together with the regions and countries directly or indirectly in it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct RegionDetails {
+    /// The M49 code (three digits, zero-padded)
+    pub code: String,
+    /// The English name of the region
+    pub name: String,
+    /// The M49 code of the enclosing region; `None` for the world
+    pub parent: Option<String>,
+    /// Level of the region in the hierarchy
+    pub level: RegionLevel,
+    /// The regions directly below this one, ordered by code
+    pub children: Vec<Region>,
+    /// Every country in this region or one of its descendants, ordered by code
+    pub countries: Vec<Country>,
+}
+
+impl RegionDetails {
+    /// Combines a region with its children and member countries
+    pub fn new(region: Region, children: Vec<Region>, countries: Vec<Country>) -> Self {
+        RegionDetails {
+            code: region.code,
+            name: region.name,
+            parent: region.parent,
+            level: region.level,
+            children,
+            countries,
+        }
+    }
+}
+
+/// One entry of the embedded region list
+#[derive(Debug, Deserialize)]
+struct RegionEntry {
+    #[serde(flatten)]
+    region: Region,
+    /// Alpha-2 codes of the countries whose most specific region this is
+    countries: Vec<String>,
+}
+
+/// Regions shipped with the API
+const REGION_DATA: &str = include_str!("../data/regions.json");
+
+fn region_entries() -> &'static [RegionEntry] {
+    static REGIONS: OnceLock<Vec<RegionEntry>> = OnceLock::new();
+    REGIONS.get_or_init(|| serde_json::from_str(REGION_DATA).expect("data/regions.json is a valid region list"))
+}
+
+/// Every region shipped with the API, parents before their children
+pub fn region_data() -> impl Iterator<Item = &'static Region> {
+    region_entries().iter().map(|entry| &entry.region)
+}
+
+/// Looks a region up by its M49 code, as normalized by
+/// [`crate::normalize_code`], or by its name, compared case-insensitively
+pub fn find_region(key: &str) -> Option<&'static Region> {
+    region_data().find(|region| region.code == key || region.name.eq_ignore_ascii_case(key))
+}
+
+/// The M49 code of the most specific region of the country with alpha-2 code
+/// `code`, if it belongs to one
+pub fn region_of(code: &str) -> Option<&'static str> {
+    region_entries()
+        .iter()
+        .find(|entry| entry.countries.iter().any(|country| country == code))
+        .map(|entry| entry.region.code.as_str())
+}
+
+/// The M49 codes of the region `code` and of every region below it
+pub fn subtree(code: &str) -> Vec<&'static str> {
+    let mut codes: Vec<&'static str> = Vec::new();
+    // Parents are listed before their children, so one pass collects the subtree
+    for region in region_data() {
+        let inside = region.code == code
+            || region.parent.as_deref().is_some_and(|parent| codes.contains(&parent));
+        if inside {
+            codes.push(&region.code);
+        }
+    }
+    codes
+}
@@ -0,0 +1,403 @@
+//! In-memory country repository, mainly for tests
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::RwLock;
+
+use crate::borders::{self, BorderGraph};
+use crate::boundaries::{self, Boundary};
+use crate::calendar::{self, HolidayRule};
+use crate::currencies::{self, Currency};
+use crate::error::ApiError;
+use crate::geo::{self, NearbyCountry, Point};
+use crate::import::{self, ImportBatch, ImportReport, ImportTarget};
+use crate::languages::{self, Language};
+use crate::phone;
+use crate::regions::{self, Region};
+use crate::search::{self, SearchResult};
+use crate::{apply_country_patch, seed_data, Country};
+
+use super::{compare, CountryRepository, ListOptions, Page};
+
+#[derive(Default, Clone)]
+struct Store {
+    countries: BTreeMap<String, Country>,
+    alt_names: HashMap<String, Vec<String>>,
+    boundaries: HashMap<String, Boundary>,
+}
+
+impl Store {
+    fn find(&self, code: &str) -> Option<&Country> {
+        self.countries
+            .get(code)
+            .or_else(|| self.countries.values().find(|c| c.alpha3 == code || c.numeric == code))
+    }
+
+    /// The border graph of the stored countries
+    fn border_graph(&self) -> BorderGraph {
+        borders::graph(
+            borders::border_data()
+                .iter()
+                .filter(|[a, b]| self.countries.contains_key(a) && self.countries.contains_key(b))
+                .map(|[a, b]| (a.as_str(), b.as_str())),
+        )
+    }
+
+    /// Fails like SQLite's UNIQUE constraints if another country already uses
+    /// the alpha-3 or numeric code of `country`
+    fn check_unique(&self, country: &Country) -> Result<(), ApiError> {
+        let taken = self.countries.values().any(|c| {
+            c.code != country.code && (c.alpha3 == country.alpha3 || c.numeric == country.numeric)
+        });
+        if taken {
+            return Err(ApiError::Conflict(String::from(
+                "The request violates a uniqueness or integrity constraint",
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl ImportTarget for Store {
+    fn find(&mut self, code: &str) -> Result<Option<Country>, ApiError> {
+        Ok(self.countries.get(code).cloned())
+    }
+
+    fn codes(&mut self) -> Result<Vec<String>, ApiError> {
+        Ok(self.countries.keys().cloned().collect())
+    }
+
+    fn insert(&mut self, country: &Country) -> Result<(), ApiError> {
+        self.check_unique(country)?;
+        self.countries.insert(country.code.clone(), country.clone());
+        Ok(())
+    }
+
+    fn update(&mut self, country: &Country) -> Result<(), ApiError> {
+        self.insert(country)
+    }
+
+    fn delete(&mut self, code: &str) -> Result<(), ApiError> {
+        self.countries.remove(code);
+        self.alt_names.remove(code);
+        self.boundaries.remove(code);
+        Ok(())
+    }
+}
+
+/// Repository keeping countries in process memory
+///
+/// Nothing is persisted; every instance starts from the countries it was
+/// created with.
+#[derive(Default)]
+pub struct InMemoryRepository {
+    store: RwLock<Store>,
+}
+
+impl InMemoryRepository {
+    /// Creates an empty repository
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a repository holding the same countries and alternative names
+    /// as a freshly seeded database
+    pub fn seeded() -> Self {
+        let mut store = Store::default();
+        for entry in seed_data() {
+            store.alt_names.insert(entry.country.code.clone(), entry.alt_names);
+            store.countries.insert(entry.country.code.clone(), entry.country);
+        }
+        for boundary in boundaries::boundary_data() {
+            if store.countries.contains_key(&boundary.code) {
+                store.boundaries.insert(boundary.code.clone(), boundary.clone());
+            }
+        }
+        InMemoryRepository { store: RwLock::new(store) }
+    }
+
+    /// Creates a repository holding `countries`
+    pub fn with_countries(countries: Vec<Country>) -> Self {
+        let store = Store {
+            countries: countries.into_iter().map(|c| (c.code.clone(), c)).collect(),
+            alt_names: HashMap::new(),
+            boundaries: HashMap::new(),
+        };
+        InMemoryRepository { store: RwLock::new(store) }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Store> {
+        // A panic cannot leave the maps half-updated, so a poisoned lock is still usable
+        self.store.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Store> {
+        self.store.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl CountryRepository for InMemoryRepository {
+    fn list(&self, options: &ListOptions) -> Result<Page, ApiError> {
+        let store = self.read();
+        let mut countries: Vec<&Country> = store.countries.values().filter(|c| options.filter.matches(c)).collect();
+        countries.sort_by(|a, b| compare(&options.sort, a, b));
+
+        Ok(Page {
+            total: countries.len() as u32,
+            countries: countries
+                .into_iter()
+                .skip(options.offset as usize)
+                .take(options.limit as usize)
+                .cloned()
+                .collect(),
+        })
+    }
+
+    fn get(&self, code: &str) -> Result<Option<Country>, ApiError> {
+        Ok(self.read().find(code).cloned())
+    }
+
+    fn by_region(&self, region: &str) -> Result<Vec<Country>, ApiError> {
+        Ok(self
+            .read()
+            .countries
+            .values()
+            .filter(|c| c.region.eq_ignore_ascii_case(region))
+            .cloned()
+            .collect())
+    }
+
+    fn regions(&self) -> Result<Vec<String>, ApiError> {
+        let store = self.read();
+        let regions: BTreeSet<&String> = store.countries.values().map(|c| &c.region).collect();
+        Ok(regions.into_iter().cloned().collect())
+    }
+
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, ApiError> {
+        let store = self.read();
+
+        let documents: Vec<(&Country, Vec<String>)> = store
+            .countries
+            .values()
+            .map(|country| {
+                let alt_names = store.alt_names.get(&country.code).map(|names| names.join(" ")).unwrap_or_default();
+                (country, search::document_words(country, &alt_names))
+            })
+            .collect();
+
+        let vocabulary: BTreeSet<&str> = documents.iter().flat_map(|(_, words)| words.iter().map(String::as_str)).collect();
+        let expansion = search::expand_query(query, vocabulary.iter().copied());
+
+        let scored = |require_all: bool| -> Vec<SearchResult> {
+            documents
+                .iter()
+                .filter(|(_, words)| !require_all || search::matches_all(&expansion, words))
+                .map(|(country, words)| SearchResult {
+                    country: (*country).clone(),
+                    score: search::score(&expansion, words),
+                })
+                .filter(|result| result.score > 0.0)
+                .collect()
+        };
+
+        // Prefer countries matching every token, but fall back to any token
+        let mut results = scored(true);
+        if results.is_empty() {
+            results = scored(false);
+        }
+
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    fn insert(&self, country: &Country) -> Result<(), ApiError> {
+        let mut store = self.write();
+
+        if store.countries.contains_key(&country.code) {
+            return Err(ApiError::Conflict(format!("Country with code {} already exists", country.code)));
+        }
+        store.check_unique(country)?;
+
+        store.countries.insert(country.code.clone(), country.clone());
+        Ok(())
+    }
+
+    fn update(&self, country: &Country) -> Result<bool, ApiError> {
+        let mut store = self.write();
+        store.check_unique(country)?;
+        match store.countries.get_mut(&country.code) {
+            Some(existing) => {
+                *existing = country.clone();
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn patch(&self, code: &str, patch: &serde_json::Value) -> Result<Option<Country>, ApiError> {
+        let mut store = self.write();
+        let Some(current) = store.countries.get(code) else {
+            return Ok(None);
+        };
+
+        let patched = apply_country_patch(current, patch)?;
+        store.check_unique(&patched)?;
+        store.countries.insert(code.to_string(), patched.clone());
+        Ok(Some(patched))
+    }
+
+    fn delete(&self, code: &str) -> Result<bool, ApiError> {
+        let mut store = self.write();
+        store.alt_names.remove(code);
+        store.boundaries.remove(code);
+        Ok(store.countries.remove(code).is_some())
+    }
+
+    fn import(&self, batch: ImportBatch) -> Result<ImportReport, ApiError> {
+        let mut store = self.write();
+
+        // Work on a copy so that a failure leaves the stored countries untouched
+        let mut staged = store.clone();
+        let report = import::apply(&mut staged, batch)?;
+        *store = staged;
+        Ok(report)
+    }
+
+    fn currencies(&self) -> Result<Vec<Currency>, ApiError> {
+        Ok(currencies::currency_data().to_vec())
+    }
+
+    fn currency(&self, code: &str) -> Result<Option<Currency>, ApiError> {
+        Ok(currencies::find_currency(code).cloned())
+    }
+
+    fn by_currency(&self, code: &str) -> Result<Vec<Country>, ApiError> {
+        Ok(self
+            .read()
+            .countries
+            .values()
+            .filter(|c| c.currencies.iter().any(|currency| currency == code))
+            .cloned()
+            .collect())
+    }
+
+    fn languages(&self) -> Result<Vec<Language>, ApiError> {
+        Ok(languages::language_data().to_vec())
+    }
+
+    fn language(&self, code: &str) -> Result<Option<Language>, ApiError> {
+        Ok(languages::find_language(code).cloned())
+    }
+
+    fn by_language(&self, code: &str) -> Result<Vec<Country>, ApiError> {
+        Ok(self
+            .read()
+            .countries
+            .values()
+            .filter(|c| c.languages.iter().any(|language| language.code == code))
+            .cloned()
+            .collect())
+    }
+
+    fn region(&self, key: &str) -> Result<Option<Region>, ApiError> {
+        let code = crate::normalize_code(key);
+        Ok(regions::find_region(&code).or_else(|| regions::find_region(key.trim())).cloned())
+    }
+
+    fn subregions(&self, code: &str) -> Result<Vec<Region>, ApiError> {
+        let mut children: Vec<Region> = regions::region_data()
+            .filter(|region| region.parent.as_deref() == Some(code))
+            .cloned()
+            .collect();
+        children.sort_by(|a, b| a.code.cmp(&b.code));
+        Ok(children)
+    }
+
+    fn in_region(&self, code: &str) -> Result<Vec<Country>, ApiError> {
+        let subtree = regions::subtree(code);
+        Ok(self
+            .read()
+            .countries
+            .values()
+            .filter(|c| subtree.contains(&c.region_code.as_str()))
+            .cloned()
+            .collect())
+    }
+
+    fn nearest(&self, point: Point, limit: usize) -> Result<Vec<NearbyCountry>, ApiError> {
+        Ok(geo::nearest(self.read().countries.values().cloned(), point, limit))
+    }
+
+    fn load_boundaries(&self, boundaries: Vec<Boundary>) -> Result<Vec<String>, ApiError> {
+        let mut store = self.write();
+        let mut loaded = Vec::new();
+        for boundary in boundaries {
+            if store.countries.contains_key(&boundary.code) {
+                loaded.push(boundary.code.clone());
+                store.boundaries.insert(boundary.code.clone(), boundary);
+            }
+        }
+        Ok(loaded)
+    }
+
+    fn boundaries(&self, codes: &[String]) -> Result<Vec<Boundary>, ApiError> {
+        let store = self.read();
+        let mut boundaries: Vec<Boundary> = codes.iter().filter_map(|code| store.boundaries.get(code).cloned()).collect();
+        boundaries.sort_by(|a, b| a.code.cmp(&b.code));
+        boundaries.dedup_by(|a, b| a.code == b.code);
+        Ok(boundaries)
+    }
+
+    fn country_at(&self, point: Point) -> Result<Option<Country>, ApiError> {
+        let store = self.read();
+        Ok(store
+            .countries
+            .values()
+            .find(|c| store.boundaries.get(&c.code).is_some_and(|boundary| boundary.contains(point)))
+            .cloned())
+    }
+
+    fn neighbors(&self, code: &str) -> Result<Vec<Country>, ApiError> {
+        let store = self.read();
+        let graph = store.border_graph();
+        Ok(graph
+            .get(code)
+            .into_iter()
+            .flatten()
+            .filter_map(|neighbor| store.countries.get(neighbor).cloned())
+            .collect())
+    }
+
+    fn route(&self, from: &str, to: &str) -> Result<Option<Vec<Country>>, ApiError> {
+        let store = self.read();
+        if !store.countries.contains_key(from) || !store.countries.contains_key(to) {
+            return Ok(None);
+        }
+        let route = borders::shortest_route(&store.border_graph(), from, to);
+        Ok(route.map(|codes| codes.iter().filter_map(|code| store.countries.get(code).cloned()).collect()))
+    }
+
+    fn by_calling_code(&self, digits: &str) -> Result<Vec<Country>, ApiError> {
+        Ok(self
+            .read()
+            .countries
+            .values()
+            .filter(|c| phone::has_calling_code(c, digits))
+            .cloned()
+            .collect())
+    }
+
+    fn by_tld(&self, tld: &str) -> Result<Vec<Country>, ApiError> {
+        Ok(self
+            .read()
+            .countries
+            .values()
+            .filter(|c| c.tlds.iter().any(|listed| listed == tld))
+            .cloned()
+            .collect())
+    }
+
+    fn holiday_rules(&self, code: &str) -> Result<Vec<HolidayRule>, ApiError> {
+        Ok(calendar::holiday_data().countries.get(code).cloned().unwrap_or_default())
+    }
+}
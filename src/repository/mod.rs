@@ -0,0 +1,280 @@
+//! Storage of countries behind the [`CountryRepository`] trait
+//!
+//! Handlers only talk to the trait, so the API can be served from SQLite
+//! ([`SqliteRepository`]) or from memory ([`InMemoryRepository`]), e.g. in tests.
+
+use std::cmp::Ordering;
+
+use crate::boundaries::Boundary;
+use crate::calendar::{HolidayRule, Weekday};
+use crate::currencies::Currency;
+use crate::error::ApiError;
+use crate::geo::{NearbyCountry, Point};
+use crate::import::{ImportBatch, ImportReport};
+use crate::languages::Language;
+use crate::regions::Region;
+use crate::search::SearchResult;
+use crate::{Country, DrivingSide};
+
+mod memory;
+pub(crate) mod sqlite;
+
+pub use memory::InMemoryRepository;
+pub use sqlite::SqliteRepository;
+
+/// Country fields a listing can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Code,
+    Alpha3,
+    Numeric,
+    Name,
+    Capital,
+    Region,
+    Currency,
+}
+
+impl SortField {
+    /// Parses the field name used in the `sort` query parameter
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "code" => Some(SortField::Code),
+            "alpha3" => Some(SortField::Alpha3),
+            "numeric" => Some(SortField::Numeric),
+            "name" => Some(SortField::Name),
+            "capital" => Some(SortField::Capital),
+            "region" => Some(SortField::Region),
+            "currency" => Some(SortField::Currency),
+            _ => None,
+        }
+    }
+
+    /// Name of the `countries` column holding this field
+    pub fn column(self) -> &'static str {
+        match self {
+            SortField::Code => "code",
+            SortField::Alpha3 => "alpha3",
+            SortField::Numeric => "numeric",
+            SortField::Name => "name",
+            SortField::Capital => "capital",
+            SortField::Region => "region",
+            SortField::Currency => "currency",
+        }
+    }
+
+    /// Value of this field in `country`
+    pub fn value(self, country: &Country) -> &str {
+        match self {
+            SortField::Code => &country.code,
+            SortField::Alpha3 => &country.alpha3,
+            SortField::Numeric => &country.numeric,
+            SortField::Name => &country.name,
+            SortField::Capital => &country.capital,
+            SortField::Region => &country.region,
+            SortField::Currency => &country.currency,
+        }
+    }
+}
+
+/// One term of a listing's sort order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+/// Parses a `sort` query parameter such as `region,-name`
+///
+/// `code` is always appended as a final tie-breaker so that paging is stable.
+pub fn parse_sort(sort: Option<&str>) -> Result<Vec<SortKey>, String> {
+    let mut keys = Vec::new();
+
+    for term in sort.unwrap_or("code").split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        let (name, descending) = match term.strip_prefix('-') {
+            Some(name) => (name, true),
+            None => (term.strip_prefix('+').unwrap_or(term), false),
+        };
+
+        let field = SortField::parse(name).ok_or_else(|| format!("Cannot sort by unknown field {}", name))?;
+        keys.push(SortKey { field, descending });
+    }
+
+    if !keys.iter().any(|key| key.field == SortField::Code) {
+        keys.push(SortKey { field: SortField::Code, descending: false });
+    }
+
+    Ok(keys)
+}
+
+/// Compares two countries according to a sort order
+pub fn compare(sort: &[SortKey], a: &Country, b: &Country) -> Ordering {
+    sort.iter()
+        .map(|key| {
+            let ordering = key.field.value(a).cmp(key.field.value(b));
+            if key.descending { ordering.reverse() } else { ordering }
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Filters restricting a country listing; unset filters match everything
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CountryFilter {
+    /// Region name, compared case-insensitively
+    pub region: Option<String>,
+    /// Code of a currency that must be legal tender in the country, compared
+    /// case-insensitively
+    pub currency: Option<String>,
+    /// Text the country name must contain, compared case-insensitively
+    pub name_contains: Option<String>,
+    /// Days that must all be part of the country's weekend
+    pub weekend: Vec<Weekday>,
+    /// Day weeks must start on in the country
+    pub first_day_of_week: Option<Weekday>,
+    /// Side of the road traffic must keep to in the country
+    pub driving_side: Option<DrivingSide>,
+}
+
+impl CountryFilter {
+    /// Whether `country` passes every filter
+    pub fn matches(&self, country: &Country) -> bool {
+        self.region.as_ref().is_none_or(|region| country.region.eq_ignore_ascii_case(region))
+            && self.currency.as_ref().is_none_or(|currency| {
+                country.currencies.iter().any(|code| code.eq_ignore_ascii_case(currency))
+            })
+            && self.name_contains.as_ref().is_none_or(|text| {
+                country.name.to_lowercase().contains(&text.to_lowercase())
+            })
+            && self.weekend.iter().all(|day| country.weekend.contains(day))
+            && self.first_day_of_week.is_none_or(|day| country.first_day_of_week == Some(day))
+            && self.driving_side.is_none_or(|side| country.driving_side == Some(side))
+    }
+}
+
+/// Filtering, ordering and paging of a country listing
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListOptions {
+    pub filter: CountryFilter,
+    pub sort: Vec<SortKey>,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+/// One page of a country listing
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    /// Number of countries matching the filter across all pages
+    pub total: u32,
+    /// Countries on the requested page
+    pub countries: Vec<Country>,
+}
+
+/// Storage backend for countries
+///
+/// Implementations are blocking; handlers call them on actix's blocking
+/// thread pool through [`crate::AppState::with_repo`]. Codes passed to the
+/// methods are already normalized with [`crate::normalize_code`].
+pub trait CountryRepository: Send + Sync {
+    /// Returns one page of the countries matching `options.filter`
+    fn list(&self, options: &ListOptions) -> Result<Page, ApiError>;
+
+    /// Looks a country up by its alpha-2, alpha-3 or numeric code
+    fn get(&self, code: &str) -> Result<Option<Country>, ApiError>;
+
+    /// Returns every country of a region, compared case-insensitively
+    fn by_region(&self, region: &str) -> Result<Vec<Country>, ApiError>;
+
+    /// Returns the distinct regions countries belong to
+    fn regions(&self) -> Result<Vec<String>, ApiError>;
+
+    /// Searches names, capitals and alternative names, best match first
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, ApiError>;
+
+    /// Stores a new country, failing with [`ApiError::Conflict`] if its code exists
+    fn insert(&self, country: &Country) -> Result<(), ApiError>;
+
+    /// Replaces the country with the same alpha-2 code; `false` if there is none
+    fn update(&self, country: &Country) -> Result<bool, ApiError>;
+
+    /// Atomically applies a JSON Merge Patch to the country with alpha-2 code
+    /// `code`, returning the stored result, or `None` if there is no such country
+    fn patch(&self, code: &str, patch: &serde_json::Value) -> Result<Option<Country>, ApiError>;
+
+    /// Removes the country with alpha-2 code `code`; `false` if there is none
+    fn delete(&self, code: &str) -> Result<bool, ApiError>;
+
+    /// Writes a bulk import atomically: either every row of the report is
+    /// applied or, on an unexpected error, none is
+    fn import(&self, batch: ImportBatch) -> Result<ImportReport, ApiError>;
+
+    /// Returns every currency, ordered by code
+    fn currencies(&self) -> Result<Vec<Currency>, ApiError>;
+
+    /// Looks a currency up by its ISO 4217 alphabetic or numeric code
+    fn currency(&self, code: &str) -> Result<Option<Currency>, ApiError>;
+
+    /// Returns the countries where the currency with alphabetic code `code` is
+    /// legal tender, ordered by code
+    fn by_currency(&self, code: &str) -> Result<Vec<Country>, ApiError>;
+
+    /// Returns every language, ordered by code
+    fn languages(&self) -> Result<Vec<Language>, ApiError>;
+
+    /// Looks a language up by its ISO 639 code or alpha-3 code, in lowercase
+    fn language(&self, code: &str) -> Result<Option<Language>, ApiError>;
+
+    /// Returns the countries where the language with code `code` is official
+    /// or recognized, ordered by code
+    fn by_language(&self, code: &str) -> Result<Vec<Country>, ApiError>;
+
+    /// Looks a UN M49 region up by its code or by its name, compared
+    /// case-insensitively
+    fn region(&self, key: &str) -> Result<Option<Region>, ApiError>;
+
+    /// Returns the regions directly below the region with M49 code `code`,
+    /// ordered by code
+    fn subregions(&self, code: &str) -> Result<Vec<Region>, ApiError>;
+
+    /// Returns the countries in the region with M49 code `code` or in any
+    /// region below it, ordered by code
+    fn in_region(&self, code: &str) -> Result<Vec<Country>, ApiError>;
+
+    /// Returns the `limit` countries whose center is nearest to `point`,
+    /// nearest first
+    fn nearest(&self, point: Point, limit: usize) -> Result<Vec<NearbyCountry>, ApiError>;
+
+    /// Replaces the boundaries of the countries they belong to, returning the
+    /// codes of those countries; boundaries of unknown countries are ignored
+    fn load_boundaries(&self, boundaries: Vec<Boundary>) -> Result<Vec<String>, ApiError>;
+
+    /// Returns the loaded boundaries of the countries with the alpha-2 codes
+    /// `codes`, ordered by code; countries without a boundary are left out
+    fn boundaries(&self, codes: &[String]) -> Result<Vec<Boundary>, ApiError>;
+
+    /// Returns the country whose boundary contains `point`, if any; where
+    /// boundaries overlap, the country with the lowest code
+    fn country_at(&self, point: Point) -> Result<Option<Country>, ApiError>;
+
+    /// Returns the countries sharing a land border with the country with
+    /// alpha-2 code `code`, ordered by code
+    fn neighbors(&self, code: &str) -> Result<Vec<Country>, ApiError>;
+
+    /// Returns the countries along a land route from the country with alpha-2
+    /// code `from` to the one with code `to` that crosses as few borders as
+    /// possible, both ends included, or `None` if no land route joins them
+    fn route(&self, from: &str, to: &str) -> Result<Option<Vec<Country>>, ApiError>;
+
+    /// Returns the countries reached through the calling code with digits
+    /// `digits`, either their whole calling code (e.g. `1809`) or their ITU
+    /// country code (e.g. `1`), ordered by code
+    fn by_calling_code(&self, digits: &str) -> Result<Vec<Country>, ApiError>;
+
+    /// Returns the countries using the top-level domain `tld`, written in
+    /// lowercase with its leading dot, ordered by code
+    fn by_tld(&self, tld: &str) -> Result<Vec<Country>, ApiError>;
+
+    /// Returns the public holiday rules of the country with alpha-2 code
+    /// `code` in the order they are listed, or an empty list if its
+    /// holidays are not known
+    fn holiday_rules(&self, code: &str) -> Result<Vec<HolidayRule>, ApiError>;
+}
@@ -0,0 +1,755 @@
+//! SQLite-backed country repository
+
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row, Transaction, TransactionBehavior};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::borders;
+use crate::boundaries::{self, Boundary, Polygon};
+use crate::calendar::{HolidayRule, Observance, Weekday};
+use crate::db::DbPool;
+use crate::currencies::Currency;
+use crate::error::ApiError;
+use crate::geo::{self, NearbyCountry, Point};
+use crate::import::{self, ImportBatch, ImportReport, ImportTarget};
+use crate::languages::Language;
+use crate::regions::{Region, RegionLevel};
+use crate::search::{self, SearchResult};
+use crate::{apply_country_patch, Country, DrivingSide};
+
+use super::{CountryRepository, ListOptions, Page};
+
+/// Columns of the `countries` table written by [`insert_country`], in order
+pub(crate) const COUNTRY_COLUMNS: &str = "code, alpha3, numeric, name, capital, region, currency, region_code,
+    capital_latitude, capital_longitude, latitude, longitude, area_km2, bbox_west, bbox_south, bbox_east, bbox_north,
+    calling_codes, tlds, timezones, weekend, first_day_of_week, driving_side";
+
+/// Expressions selected by [`country_from_row`] from `countries`, in order:
+/// the table's columns followed by the comma-separated legal tenders and a
+/// JSON array of the languages ([`COUNTRY_SELECT_LEN`] expressions in all)
+pub(crate) const COUNTRY_SELECT: &str = "countries.code, countries.alpha3, countries.numeric, countries.name,
+    countries.capital, countries.region, countries.currency, countries.region_code,
+    countries.capital_latitude, countries.capital_longitude, countries.latitude, countries.longitude,
+    countries.area_km2, countries.bbox_west, countries.bbox_south, countries.bbox_east, countries.bbox_north,
+    countries.calling_codes, countries.tlds, countries.timezones,
+    countries.weekend, countries.first_day_of_week, countries.driving_side,
+    (SELECT GROUP_CONCAT(currency_code, ',') FROM (
+        SELECT currency_code FROM country_currencies
+        WHERE country_code = countries.code ORDER BY position
+    )),
+    (SELECT json_group_array(json_object('code', language_code, 'status', status)) FROM (
+        SELECT language_code, status FROM country_languages
+        WHERE country_code = countries.code ORDER BY position
+    ))";
+
+/// Number of expressions in [`COUNTRY_SELECT`], the index of the first
+/// column selected after them
+pub(crate) const COUNTRY_SELECT_LEN: usize = 25;
+
+/// Maps a row selected with [`COUNTRY_SELECT`] to a [`Country`]
+///
+/// Countries upgraded from the original schema may lack alpha-3 and numeric
+/// codes; those are returned as empty strings. The bounding box is only
+/// returned when all four of its edges are stored.
+pub(crate) fn country_from_row(row: &Row) -> rusqlite::Result<Country> {
+    let bbox = [row.get(13)?, row.get(14)?, row.get(15)?, row.get(16)?];
+    let currencies: Option<String> = row.get(23)?;
+    Ok(Country {
+        code: row.get(0)?,
+        alpha3: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+        numeric: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+        name: row.get(3)?,
+        capital: row.get(4)?,
+        region: row.get(5)?,
+        region_code: row.get(7)?,
+        currency: row.get(6)?,
+        currencies: currencies.map(|codes| codes.split(',').map(String::from).collect()).unwrap_or_default(),
+        languages: json_column(row, 24)?,
+        capital_latitude: row.get(8)?,
+        capital_longitude: row.get(9)?,
+        latitude: row.get(10)?,
+        longitude: row.get(11)?,
+        area_km2: row.get(12)?,
+        bbox: bbox.into_iter().collect::<Option<Vec<f64>>>(),
+        calling_codes: json_column(row, 17)?,
+        tlds: json_column(row, 18)?,
+        timezones: json_column(row, 19)?,
+        weekend: json_column(row, 20)?,
+        first_day_of_week: named_column(row, 21, "weekday", Weekday::parse)?,
+        driving_side: named_column(row, 22, "driving side", DrivingSide::parse)?,
+    })
+}
+
+fn currency_from_row(row: &Row) -> rusqlite::Result<Currency> {
+    Ok(Currency {
+        code: row.get(0)?,
+        numeric: row.get(1)?,
+        name: row.get(2)?,
+        symbol: row.get(3)?,
+        minor_units: row.get(4)?,
+    })
+}
+
+fn region_from_row(row: &Row) -> rusqlite::Result<Region> {
+    let level: String = row.get(3)?;
+    Ok(Region {
+        code: row.get(0)?,
+        name: row.get(1)?,
+        parent: row.get(2)?,
+        level: RegionLevel::parse(&level).ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, format!("unknown region level {}", level).into())
+        })?,
+    })
+}
+
+fn language_from_row(row: &Row) -> rusqlite::Result<Language> {
+    Ok(Language {
+        code: row.get(0)?,
+        alpha3: row.get(1)?,
+        name: row.get(2)?,
+    })
+}
+
+/// Reads a column holding JSON text, such as the languages of a country or
+/// the GeoJSON coordinates of a boundary polygon
+fn json_column<T: DeserializeOwned>(row: &Row, index: usize) -> rusqlite::Result<T> {
+    let text: String = row.get(index)?;
+    serde_json::from_str(&text)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, Box::new(e)))
+}
+
+/// Reads a nullable column holding the stored name of a value, such as the
+/// observance of a holiday rule
+fn named_column<T>(row: &Row, index: usize, kind: &str, parse: fn(&str) -> Option<T>) -> rusqlite::Result<Option<T>> {
+    let name: Option<String> = row.get(index)?;
+    name.map(|name| {
+        parse(&name).ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, format!("unknown {} {}", kind, name).into())
+        })
+    })
+    .transpose()
+}
+
+/// JSON array text stored for a list of values, such as calling codes or weekend days
+fn json_list<T: Serialize>(values: &[T]) -> String {
+    serde_json::json!(values).to_string()
+}
+
+/// Maps a row of `name, date_rule, observed, since, until` from
+/// `holiday_rules` to a [`HolidayRule`]
+fn holiday_rule_from_row(row: &Row) -> rusqlite::Result<HolidayRule> {
+    Ok(HolidayRule {
+        name: row.get(0)?,
+        date: json_column(row, 1)?,
+        observed: named_column(row, 2, "observance", Observance::parse)?,
+        since: row.get(3)?,
+        until: row.get(4)?,
+    })
+}
+
+/// Repository storing countries in a pooled SQLite database
+pub struct SqliteRepository {
+    pool: DbPool,
+}
+
+impl SqliteRepository {
+    /// Creates a repository on top of a connection pool whose database
+    /// schema has been migrated with [`crate::init_schema`]
+    pub fn new(pool: DbPool) -> Self {
+        SqliteRepository { pool }
+    }
+
+    /// The underlying connection pool
+    pub fn pool(&self) -> &DbPool {
+        &self.pool
+    }
+
+    fn conn(&self) -> Result<r2d2::PooledConnection<crate::db::SqliteConnectionManager>, ApiError> {
+        Ok(self.pool.get()?)
+    }
+}
+
+fn select_country(conn: &Connection, code: &str) -> rusqlite::Result<Option<Country>> {
+    conn.query_row(
+        &format!("SELECT {} FROM countries WHERE code = ?1", COUNTRY_SELECT),
+        params![code],
+        country_from_row,
+    )
+    .optional()
+}
+
+/// Stores a new country together with its legal tenders and languages
+pub(crate) fn insert_country(conn: &Connection, country: &Country) -> rusqlite::Result<()> {
+    conn.execute(
+        &format!(
+            "INSERT INTO countries ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)",
+            COUNTRY_COLUMNS
+        ),
+        params![
+            country.code,
+            country.alpha3,
+            country.numeric,
+            country.name,
+            country.capital,
+            country.region,
+            country.currency,
+            country.region_code,
+            country.capital_latitude,
+            country.capital_longitude,
+            country.latitude,
+            country.longitude,
+            country.area_km2,
+            bbox_edge(country, 0),
+            bbox_edge(country, 1),
+            bbox_edge(country, 2),
+            bbox_edge(country, 3),
+            json_list(&country.calling_codes),
+            json_list(&country.tlds),
+            json_list(&country.timezones),
+            json_list(&country.weekend),
+            country.first_day_of_week.map(Weekday::as_str),
+            country.driving_side.map(DrivingSide::as_str)
+        ],
+    )?;
+    write_currencies(conn, country)?;
+    write_languages(conn, country)
+}
+
+fn update_country(conn: &Connection, country: &Country) -> rusqlite::Result<usize> {
+    let rows = conn.execute(
+        "UPDATE countries SET alpha3 = ?1, numeric = ?2, name = ?3, capital = ?4, region = ?5, currency = ?6,
+             region_code = ?7, capital_latitude = ?8, capital_longitude = ?9, latitude = ?10, longitude = ?11,
+             area_km2 = ?12, bbox_west = ?13, bbox_south = ?14, bbox_east = ?15, bbox_north = ?16,
+             calling_codes = ?17, tlds = ?18, timezones = ?19, weekend = ?20, first_day_of_week = ?21,
+             driving_side = ?22
+         WHERE code = ?23",
+        params![
+            country.alpha3,
+            country.numeric,
+            country.name,
+            country.capital,
+            country.region,
+            country.currency,
+            country.region_code,
+            country.capital_latitude,
+            country.capital_longitude,
+            country.latitude,
+            country.longitude,
+            country.area_km2,
+            bbox_edge(country, 0),
+            bbox_edge(country, 1),
+            bbox_edge(country, 2),
+            bbox_edge(country, 3),
+            json_list(&country.calling_codes),
+            json_list(&country.tlds),
+            json_list(&country.timezones),
+            json_list(&country.weekend),
+            country.first_day_of_week.map(Weekday::as_str),
+            country.driving_side.map(DrivingSide::as_str),
+            country.code
+        ],
+    )?;
+    if rows > 0 {
+        write_currencies(conn, country)?;
+        write_languages(conn, country)?;
+    }
+    Ok(rows)
+}
+
+/// Edge `index` of the bounding box of a country, in `[west, south, east, north]` order
+fn bbox_edge(country: &Country, index: usize) -> Option<f64> {
+    country.bbox.as_ref().and_then(|bbox| bbox.get(index).copied())
+}
+
+/// Replaces the links between a country and its legal tenders
+fn write_currencies(conn: &Connection, country: &Country) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM country_currencies WHERE country_code = ?1", params![country.code])?;
+    for (position, currency) in country.currencies.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO country_currencies (country_code, currency_code, position) VALUES (?1, ?2, ?3)",
+            params![country.code, currency, position],
+        )?;
+    }
+    Ok(())
+}
+
+/// Replaces the links between a country and its languages
+fn write_languages(conn: &Connection, country: &Country) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM country_languages WHERE country_code = ?1", params![country.code])?;
+    for (position, language) in country.languages.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO country_languages (country_code, language_code, status, position) VALUES (?1, ?2, ?3, ?4)",
+            params![country.code, language.code, language.status.as_str(), position],
+        )?;
+    }
+    Ok(())
+}
+
+impl ImportTarget for Transaction<'_> {
+    fn find(&mut self, code: &str) -> Result<Option<Country>, ApiError> {
+        Ok(select_country(self, code)?)
+    }
+
+    fn codes(&mut self) -> Result<Vec<String>, ApiError> {
+        let mut stmt = self.prepare("SELECT code FROM countries ORDER BY code")?;
+        let codes = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(codes)
+    }
+
+    fn insert(&mut self, country: &Country) -> Result<(), ApiError> {
+        Ok(insert_country(self, country)?)
+    }
+
+    fn update(&mut self, country: &Country) -> Result<(), ApiError> {
+        update_country(self, country)?;
+        Ok(())
+    }
+
+    fn delete(&mut self, code: &str) -> Result<(), ApiError> {
+        self.execute("DELETE FROM countries WHERE code = ?1", params![code])?;
+        Ok(())
+    }
+}
+
+impl CountryRepository for SqliteRepository {
+    fn list(&self, options: &ListOptions) -> Result<Page, ApiError> {
+        let filter = &options.filter;
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+        if let Some(region) = &filter.region {
+            values.push(region.clone());
+            conditions.push(format!("LOWER(region) = LOWER(?{})", values.len()));
+        }
+        if let Some(currency) = &filter.currency {
+            values.push(currency.clone());
+            conditions.push(format!(
+                "EXISTS (SELECT 1 FROM country_currencies
+                         WHERE country_code = countries.code AND currency_code = UPPER(?{}))",
+                values.len()
+            ));
+        }
+        if let Some(name_contains) = &filter.name_contains {
+            values.push(name_contains.clone());
+            conditions.push(format!("INSTR(LOWER(name), LOWER(?{})) > 0", values.len()));
+        }
+        for day in &filter.weekend {
+            values.push(day.as_str().to_string());
+            conditions.push(format!("EXISTS (SELECT 1 FROM json_each(countries.weekend) WHERE value = ?{})", values.len()));
+        }
+        if let Some(day) = filter.first_day_of_week {
+            values.push(day.as_str().to_string());
+            conditions.push(format!("first_day_of_week = ?{}", values.len()));
+        }
+        if let Some(side) = filter.driving_side {
+            values.push(side.as_str().to_string());
+            conditions.push(format!("driving_side = ?{}", values.len()));
+        }
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
+        };
+
+        let order_by: Vec<String> = options
+            .sort
+            .iter()
+            .map(|key| format!("{} {}", key.field.column(), if key.descending { "DESC" } else { "ASC" }))
+            .collect();
+
+        let conn = self.conn()?;
+
+        let total: u32 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM countries{}", where_clause),
+            params_from_iter(values.iter()),
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM countries{} ORDER BY {} LIMIT {} OFFSET {}",
+            COUNTRY_SELECT,
+            where_clause,
+            order_by.join(", "),
+            options.limit,
+            options.offset
+        ))?;
+        let countries = stmt
+            .query_map(params_from_iter(values.iter()), country_from_row)?
+            .collect::<rusqlite::Result<Vec<Country>>>()?;
+
+        Ok(Page { total, countries })
+    }
+
+    fn get(&self, code: &str) -> Result<Option<Country>, ApiError> {
+        let conn = self.conn()?;
+        let country = conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM countries WHERE code = ?1 OR alpha3 = ?1 OR numeric = ?1",
+                    COUNTRY_SELECT
+                ),
+                params![code],
+                country_from_row,
+            )
+            .optional()?;
+        Ok(country)
+    }
+
+    fn by_region(&self, region: &str) -> Result<Vec<Country>, ApiError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM countries WHERE LOWER(region) = LOWER(?1) ORDER BY code",
+            COUNTRY_SELECT
+        ))?;
+        let countries = stmt
+            .query_map(params![region], country_from_row)?
+            .collect::<rusqlite::Result<Vec<Country>>>()?;
+        Ok(countries)
+    }
+
+    fn regions(&self) -> Result<Vec<String>, ApiError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare("SELECT DISTINCT region FROM countries ORDER BY region")?;
+        let regions = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(regions)
+    }
+
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>, ApiError> {
+        let conn = self.conn()?;
+        Ok(search::search_countries(&conn, query, limit)?)
+    }
+
+    fn insert(&self, country: &Country) -> Result<(), ApiError> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        // Check if country with this code already exists
+        if select_country(&tx, &country.code)?.is_some() {
+            return Err(ApiError::Conflict(format!("Country with code {} already exists", country.code)));
+        }
+
+        insert_country(&tx, country)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn update(&self, country: &Country) -> Result<bool, ApiError> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let rows = update_country(&tx, country)?;
+        tx.commit()?;
+        Ok(rows > 0)
+    }
+
+    fn patch(&self, code: &str, patch: &serde_json::Value) -> Result<Option<Country>, ApiError> {
+        let mut conn = self.conn()?;
+
+        // Read, merge and write back atomically so concurrent patches don't interleave
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        let Some(current) = select_country(&tx, code)? else {
+            return Ok(None);
+        };
+        let patched = apply_country_patch(&current, patch)?;
+
+        update_country(&tx, &patched)?;
+        tx.commit()?;
+
+        Ok(Some(patched))
+    }
+
+    fn delete(&self, code: &str) -> Result<bool, ApiError> {
+        let conn = self.conn()?;
+        let rows = conn.execute("DELETE FROM countries WHERE code = ?1", params![code])?;
+        Ok(rows > 0)
+    }
+
+    fn import(&self, batch: ImportBatch) -> Result<ImportReport, ApiError> {
+        let mut conn = self.conn()?;
+        let mut tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let report = import::apply(&mut tx, batch)?;
+        tx.commit()?;
+        Ok(report)
+    }
+
+    fn currencies(&self) -> Result<Vec<Currency>, ApiError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare("SELECT code, numeric, name, symbol, minor_units FROM currencies ORDER BY code")?;
+        let currencies = stmt
+            .query_map([], currency_from_row)?
+            .collect::<rusqlite::Result<Vec<Currency>>>()?;
+        Ok(currencies)
+    }
+
+    fn currency(&self, code: &str) -> Result<Option<Currency>, ApiError> {
+        let conn = self.conn()?;
+        let currency = conn
+            .query_row(
+                "SELECT code, numeric, name, symbol, minor_units FROM currencies WHERE code = ?1 OR numeric = ?1",
+                params![code],
+                currency_from_row,
+            )
+            .optional()?;
+        Ok(currency)
+    }
+
+    fn by_currency(&self, code: &str) -> Result<Vec<Country>, ApiError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM countries
+             JOIN country_currencies ON country_currencies.country_code = countries.code
+             WHERE country_currencies.currency_code = ?1
+             ORDER BY countries.code",
+            COUNTRY_SELECT
+        ))?;
+        let countries = stmt
+            .query_map(params![code], country_from_row)?
+            .collect::<rusqlite::Result<Vec<Country>>>()?;
+        Ok(countries)
+    }
+
+    fn languages(&self) -> Result<Vec<Language>, ApiError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare("SELECT code, alpha3, name FROM languages ORDER BY code")?;
+        let languages = stmt
+            .query_map([], language_from_row)?
+            .collect::<rusqlite::Result<Vec<Language>>>()?;
+        Ok(languages)
+    }
+
+    fn language(&self, code: &str) -> Result<Option<Language>, ApiError> {
+        let conn = self.conn()?;
+        let language = conn
+            .query_row(
+                "SELECT code, alpha3, name FROM languages WHERE code = ?1 OR alpha3 = ?1",
+                params![code],
+                language_from_row,
+            )
+            .optional()?;
+        Ok(language)
+    }
+
+    fn by_language(&self, code: &str) -> Result<Vec<Country>, ApiError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM countries
+             JOIN country_languages ON country_languages.country_code = countries.code
+             WHERE country_languages.language_code = ?1
+             ORDER BY countries.code",
+            COUNTRY_SELECT
+        ))?;
+        let countries = stmt
+            .query_map(params![code], country_from_row)?
+            .collect::<rusqlite::Result<Vec<Country>>>()?;
+        Ok(countries)
+    }
+
+    fn region(&self, key: &str) -> Result<Option<Region>, ApiError> {
+        let conn = self.conn()?;
+        let region = conn
+            .query_row(
+                "SELECT code, name, parent_code, level FROM regions WHERE code = ?1 OR name = ?2",
+                params![crate::normalize_code(key), key.trim()],
+                region_from_row,
+            )
+            .optional()?;
+        Ok(region)
+    }
+
+    fn subregions(&self, code: &str) -> Result<Vec<Region>, ApiError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare("SELECT code, name, parent_code, level FROM regions WHERE parent_code = ?1 ORDER BY code")?;
+        let regions = stmt
+            .query_map(params![code], region_from_row)?
+            .collect::<rusqlite::Result<Vec<Region>>>()?;
+        Ok(regions)
+    }
+
+    fn in_region(&self, code: &str) -> Result<Vec<Country>, ApiError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "WITH RECURSIVE subtree(code) AS (
+                 SELECT ?1
+                 UNION ALL
+                 SELECT regions.code FROM regions JOIN subtree ON regions.parent_code = subtree.code
+             )
+             SELECT {} FROM countries WHERE region_code IN subtree ORDER BY code",
+            COUNTRY_SELECT
+        ))?;
+        let countries = stmt
+            .query_map(params![code], country_from_row)?
+            .collect::<rusqlite::Result<Vec<Country>>>()?;
+        Ok(countries)
+    }
+
+    fn nearest(&self, point: Point, limit: usize) -> Result<Vec<NearbyCountry>, ApiError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM countries WHERE latitude IS NOT NULL AND longitude IS NOT NULL",
+            COUNTRY_SELECT
+        ))?;
+        let countries = stmt
+            .query_map([], country_from_row)?
+            .collect::<rusqlite::Result<Vec<Country>>>()?;
+        Ok(geo::nearest(countries, point, limit))
+    }
+
+    fn load_boundaries(&self, boundaries: Vec<Boundary>) -> Result<Vec<String>, ApiError> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let mut loaded = Vec::new();
+        for boundary in boundaries {
+            if select_country(&tx, &boundary.code)?.is_none() {
+                continue;
+            }
+            tx.execute("DELETE FROM boundary_polygons WHERE country_code = ?1", params![boundary.code])?;
+            for polygon in &boundary.polygons {
+                let coordinates = serde_json::to_string(polygon)
+                    .map_err(|e| ApiError::Internal(format!("Cannot serialize boundary: {}", e)))?;
+                tx.execute(
+                    "INSERT INTO boundary_polygons (country_code, coordinates) VALUES (?1, ?2)",
+                    params![boundary.code, coordinates],
+                )?;
+            }
+            loaded.push(boundary.code);
+        }
+        tx.commit()?;
+        Ok(loaded)
+    }
+
+    fn boundaries(&self, codes: &[String]) -> Result<Vec<Boundary>, ApiError> {
+        let conn = self.conn()?;
+        let codes = serde_json::to_string(codes)
+            .map_err(|e| ApiError::Internal(format!("Cannot serialize country codes: {}", e)))?;
+        let mut stmt = conn.prepare(
+            "SELECT country_code, coordinates FROM boundary_polygons
+             WHERE country_code IN (SELECT value FROM json_each(?1))
+             ORDER BY country_code, id",
+        )?;
+        let mut rows = stmt.query(params![codes])?;
+        let mut boundaries: Vec<Boundary> = Vec::new();
+        while let Some(row) = rows.next()? {
+            let code: String = row.get(0)?;
+            let polygon: Polygon = json_column(row, 1)?;
+            match boundaries.last_mut() {
+                Some(boundary) if boundary.code == code => boundary.polygons.push(polygon),
+                _ => boundaries.push(Boundary { code, polygons: vec![polygon] }),
+            }
+        }
+        Ok(boundaries)
+    }
+
+    fn country_at(&self, point: Point) -> Result<Option<Country>, ApiError> {
+        let conn = self.conn()?;
+        // The R*Tree narrows the search to the polygons whose box holds the point
+        let mut stmt = conn.prepare(
+            "SELECT boundary_polygons.country_code, boundary_polygons.coordinates
+             FROM boundary_index JOIN boundary_polygons ON boundary_polygons.id = boundary_index.id
+             WHERE boundary_index.min_lon <= ?1 AND boundary_index.max_lon >= ?1
+               AND boundary_index.min_lat <= ?2 AND boundary_index.max_lat >= ?2
+             ORDER BY boundary_polygons.country_code",
+        )?;
+        let mut rows = stmt.query(params![point.longitude, point.latitude])?;
+        while let Some(row) = rows.next()? {
+            let code: String = row.get(0)?;
+            let polygon: Polygon = json_column(row, 1)?;
+            if !boundaries::polygon_contains(&polygon, point) {
+                continue;
+            }
+            // Skip polygons left behind by a country deleted before foreign keys were enforced
+            if let Some(country) = select_country(&conn, &code)? {
+                return Ok(Some(country));
+            }
+        }
+        Ok(None)
+    }
+
+    fn neighbors(&self, code: &str) -> Result<Vec<Country>, ApiError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM countries
+             WHERE code IN (SELECT neighbor_code FROM borders WHERE country_code = ?1)
+             ORDER BY code",
+            COUNTRY_SELECT
+        ))?;
+        let countries = stmt
+            .query_map(params![code], country_from_row)?
+            .collect::<rusqlite::Result<Vec<Country>>>()?;
+        Ok(countries)
+    }
+
+    fn route(&self, from: &str, to: &str) -> Result<Option<Vec<Country>>, ApiError> {
+        let conn = self.conn()?;
+        // Only borders between stored countries can be crossed
+        let mut stmt = conn.prepare(
+            "SELECT borders.country_code, borders.neighbor_code FROM borders
+             JOIN countries AS country ON country.code = borders.country_code
+             JOIN countries AS neighbor ON neighbor.code = borders.neighbor_code",
+        )?;
+        let edges = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<rusqlite::Result<Vec<(String, String)>>>()?;
+        let graph = borders::graph(edges.iter().map(|(a, b)| (a.as_str(), b.as_str())));
+
+        let Some(route) = borders::shortest_route(&graph, from, to) else {
+            return Ok(None);
+        };
+        let mut countries = Vec::with_capacity(route.len());
+        for code in route {
+            match select_country(&conn, &code)? {
+                Some(country) => countries.push(country),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(countries))
+    }
+
+    fn by_calling_code(&self, digits: &str) -> Result<Vec<Country>, ApiError> {
+        let conn = self.conn()?;
+        // Calling codes are stored as "+1 809": the digits, and the country code before the space
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM countries
+             WHERE EXISTS (
+                 SELECT 1 FROM json_each(countries.calling_codes)
+                 WHERE replace(substr(value, 2), ' ', '') = ?1
+                    OR substr(value, 2, instr(value || ' ', ' ') - 2) = ?1
+             )
+             ORDER BY code",
+            COUNTRY_SELECT
+        ))?;
+        let countries = stmt
+            .query_map(params![digits], country_from_row)?
+            .collect::<rusqlite::Result<Vec<Country>>>()?;
+        Ok(countries)
+    }
+
+    fn by_tld(&self, tld: &str) -> Result<Vec<Country>, ApiError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM countries
+             WHERE EXISTS (SELECT 1 FROM json_each(countries.tlds) WHERE value = ?1)
+             ORDER BY code",
+            COUNTRY_SELECT
+        ))?;
+        let countries = stmt
+            .query_map(params![tld], country_from_row)?
+            .collect::<rusqlite::Result<Vec<Country>>>()?;
+        Ok(countries)
+    }
+
+    fn holiday_rules(&self, code: &str) -> Result<Vec<HolidayRule>, ApiError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT name, date_rule, observed, since, until FROM holiday_rules
+             WHERE country_code = ?1
+             ORDER BY position",
+        )?;
+        let rules = stmt
+            .query_map(params![code], holiday_rule_from_row)?
+            .collect::<rusqlite::Result<Vec<HolidayRule>>>()?;
+        Ok(rules)
+    }
+}
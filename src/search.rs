@@ -0,0 +1,208 @@
+//! Full-text and typo-tolerant country search
+//!
+//! Countries are indexed in the `countries_fts` FTS5 table, which triggers on
+//! `countries` and `country_alt_names` keep in sync (see
+//! `migrations/0003_search_index.sql`). A query is tokenized and every token is
+//! expanded to the indexed terms it could plausibly be a typo or prefix of
+//! (using the `countries_fts_vocab` table), so "germny" still finds "germany".
+//! Matches are then ranked by how closely the query tokens match the
+//! country's terms, with FTS5's bm25 rank as the tie-breaker.
+
+use rusqlite::{params, Connection, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::repository::sqlite::{country_from_row, COUNTRY_SELECT, COUNTRY_SELECT_LEN};
+use crate::Country;
+
+/// A country matching a search query, together with its relevance
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct SearchResult {
+    #[serde(flatten)]
+    pub country: Country,
+    /// Relevance of the match between 0 and 1, where 1 means every query word
+    /// matched a word of the country's name, capital or alternative names exactly
+    pub score: f64,
+}
+
+/// Lowercases `text`, strips common Latin diacritics and splits it into words
+///
+/// This mirrors the `unicode61 remove_diacritics` tokenizer closely enough for
+/// the terms stored in the index to compare equal to the query tokens.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.chars().flat_map(char::to_lowercase).map(fold_diacritic).collect())
+        .collect()
+}
+
+fn fold_diacritic(c: char) -> char {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => 'a',
+        'ç' | 'ć' | 'č' => 'c',
+        'ď' => 'd',
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => 'e',
+        'ğ' => 'g',
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' | 'ı' => 'i',
+        'ł' => 'l',
+        'ñ' | 'ń' | 'ň' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => 'o',
+        'ř' => 'r',
+        'ś' | 'š' | 'ş' | 'ș' => 's',
+        'ť' | 'ţ' | 'ț' => 't',
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' => 'u',
+        'ý' | 'ÿ' => 'y',
+        'ź' | 'ż' | 'ž' => 'z',
+        _ => c,
+    }
+}
+
+/// Returns how similar an indexed `term` is to a query `token`, or `None` if
+/// the term is not a plausible match
+///
+/// Exact matches score 1.0 and prefixes of at least two characters 0.9. Other
+/// terms are accepted when they are within one edit (two for tokens longer
+/// than five characters) and score by normalized edit distance.
+fn term_similarity(token: &str, term: &str) -> Option<f64> {
+    if token == term {
+        return Some(1.0);
+    }
+    if token.chars().count() >= 2 && term.starts_with(token) {
+        return Some(0.9);
+    }
+
+    let max_edits = match token.chars().count() {
+        0..=3 => 0,
+        4..=5 => 1,
+        _ => 2,
+    };
+    let edits = strsim::damerau_levenshtein(token, term);
+    if edits > max_edits {
+        return None;
+    }
+
+    let longest = token.chars().count().max(term.chars().count()) as f64;
+    Some(0.85 * (1.0 - edits as f64 / longest))
+}
+
+/// Similar indexed terms for each token of a query, with their similarity
+pub type QueryExpansion = Vec<Vec<(String, f64)>>;
+
+/// Expands every token of `query` to the terms of `vocabulary` it could
+/// plausibly stand for
+///
+/// The result holds one entry per query token, which is empty when nothing in
+/// the vocabulary resembles the token.
+pub fn expand_query<'a, I>(query: &str, vocabulary: I) -> QueryExpansion
+where
+    I: IntoIterator<Item = &'a str>,
+    I::IntoIter: Clone,
+{
+    let vocabulary = vocabulary.into_iter();
+    tokenize(query)
+        .iter()
+        .map(|token| {
+            vocabulary
+                .clone()
+                .filter_map(|term| term_similarity(token, term).map(|score| (term.to_string(), score)))
+                .collect()
+        })
+        .collect()
+}
+
+/// Scores how well a document made of `words` matches an expanded query
+///
+/// Each query token contributes the similarity of its best matching word, so
+/// the score is 1 when every token occurs exactly and 0 when none matches.
+pub fn score(expansion: &QueryExpansion, words: &[String]) -> f64 {
+    if expansion.is_empty() {
+        return 0.0;
+    }
+
+    let total: f64 = expansion
+        .iter()
+        .map(|terms| {
+            terms
+                .iter()
+                .filter(|(term, _)| words.contains(term))
+                .map(|(_, score)| *score)
+                .fold(0.0, f64::max)
+        })
+        .sum();
+
+    (total / expansion.len() as f64 * 1000.0).round() / 1000.0
+}
+
+/// Whether a document made of `words` matches every token of the query that
+/// matches anything at all
+pub fn matches_all(expansion: &QueryExpansion, words: &[String]) -> bool {
+    expansion
+        .iter()
+        .filter(|terms| !terms.is_empty())
+        .all(|terms| terms.iter().any(|(term, _)| words.contains(term)))
+}
+
+/// Words of a country's searchable text: name, capital and alternative names
+pub fn document_words(country: &Country, alt_names: &str) -> Vec<String> {
+    tokenize(&format!("{} {} {}", country.name, country.capital, alt_names))
+}
+
+/// Searches countries by name, capital and alternative names using the FTS5 index
+///
+/// Returns at most `limit` results ordered by descending score.
+pub fn search_countries(conn: &Connection, query: &str, limit: usize) -> SqliteResult<Vec<SearchResult>> {
+    let mut stmt = conn.prepare("SELECT term FROM countries_fts_vocab")?;
+    let vocabulary = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<SqliteResult<Vec<String>>>()?;
+
+    let expansion = expand_query(query, vocabulary.iter().map(String::as_str));
+
+    let groups: Vec<String> = expansion
+        .iter()
+        .filter(|terms| !terms.is_empty())
+        .map(|terms| {
+            let alternatives: Vec<String> = terms.iter().map(|(term, _)| format!("\"{}\"", term)).collect();
+            format!("({})", alternatives.join(" OR "))
+        })
+        .collect();
+
+    if groups.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Prefer countries matching every token, but fall back to any token
+    let mut results = run_match(conn, &groups.join(" AND "), &expansion)?;
+    if results.is_empty() {
+        results = run_match(conn, &groups.join(" OR "), &expansion)?;
+    }
+
+    results.truncate(limit);
+    Ok(results)
+}
+
+fn run_match(conn: &Connection, expression: &str, expansion: &QueryExpansion) -> SqliteResult<Vec<SearchResult>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}, countries_fts.alt_names
+         FROM countries_fts
+         JOIN countries ON countries.code = countries_fts.code
+         WHERE countries_fts MATCH ?1
+         ORDER BY countries_fts.rank",
+        COUNTRY_SELECT
+    ))?;
+
+    let rows = stmt.query_map(params![expression], |row| {
+        Ok((country_from_row(row)?, row.get::<_, Option<String>>(COUNTRY_SELECT_LEN)?.unwrap_or_default()))
+    })?;
+
+    let mut results = Vec::new();
+    for row in rows {
+        let (country, alt_names) = row?;
+        let score = score(expansion, &document_words(&country, &alt_names));
+        results.push(SearchResult { country, score });
+    }
+
+    // Stable sort keeps the bm25 order among equally scored countries
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(results)
+}
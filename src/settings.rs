@@ -0,0 +1,262 @@
+//! Server configuration
+//!
+//! Settings are layered, each source overriding the previous one:
+//!
+//! 1. built-in defaults
+//! 2. a TOML file given by `--config` or `COUNTRIES_API_CONFIG`
+//! 3. `COUNTRIES_API_*` environment variables
+//! 4. command-line flags
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Parser, Subcommand};
+use serde::Deserialize;
+
+use crate::import::{ImportFormat, ImportMode};
+
+/// Prefix of the environment variables read by [`Settings::from_sources`]
+pub const ENV_PREFIX: &str = "COUNTRIES_API_";
+
+/// Runtime configuration of the API server
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// Address the HTTP server binds to
+    pub host: String,
+    /// Port the HTTP server listens on
+    pub port: u16,
+    /// Path of the SQLite database file, or `:memory:` for an in-memory database
+    pub database: String,
+    /// Number of HTTP worker threads; defaults to the number of CPU cores
+    pub workers: Option<usize>,
+    /// Maximum number of pooled database connections
+    pub pool_size: u32,
+    /// Origins allowed to make cross-origin requests; empty or `*` allows any
+    pub cors_origins: Vec<String>,
+    /// Log filter in `env_logger` syntax, e.g. `info` or `world_countries_api=debug`
+    pub log_level: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            host: String::from("127.0.0.1"),
+            port: 8080,
+            database: String::from("countries.db"),
+            workers: None,
+            pool_size: 8,
+            cors_origins: Vec::new(),
+            log_level: String::from("info"),
+        }
+    }
+}
+
+/// Command-line flags overriding the configured settings
+#[derive(Debug, Clone, Default, Args)]
+pub struct SettingsArgs {
+    /// Path of a TOML configuration file
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+    /// Address to bind to
+    #[arg(long)]
+    pub host: Option<String>,
+    /// Port to listen on
+    #[arg(long)]
+    pub port: Option<u16>,
+    /// Path of the SQLite database file, or `:memory:`
+    #[arg(long, value_name = "PATH")]
+    pub database: Option<String>,
+    /// Number of HTTP worker threads
+    #[arg(long)]
+    pub workers: Option<usize>,
+    /// Maximum number of pooled database connections
+    #[arg(long)]
+    pub pool_size: Option<u32>,
+    /// Allowed CORS origin; repeat the flag for several origins
+    #[arg(long = "cors-origin", value_name = "ORIGIN")]
+    pub cors_origins: Vec<String>,
+    /// Log filter, e.g. `info` or `debug`
+    #[arg(long)]
+    pub log_level: Option<String>,
+}
+
+/// Command-line interface of the server binary
+#[derive(Debug, Parser)]
+#[command(name = "world_countries_api", version, about = "REST API providing information about countries")]
+pub struct Cli {
+    #[command(flatten)]
+    pub settings: SettingsArgs,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// What the server binary should do; serving the API is the default
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Run the HTTP server
+    Serve,
+    /// Apply pending database migrations and exit
+    Migrate,
+    /// Import a CSV, JSON or NDJSON dataset into the database and exit
+    Import {
+        /// Dataset to import
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+        /// How imported rows are combined with stored countries
+        #[arg(long, value_enum, default_value_t = ImportMode::InsertOnly)]
+        mode: ImportMode,
+        /// Format of the dataset; defaults to the one named by the file extension
+        #[arg(long, value_enum)]
+        format: Option<ImportFormat>,
+    },
+    /// Load country boundary polygons from a GeoJSON FeatureCollection, such
+    /// as Natural Earth's admin 0 countries, and exit
+    Boundaries {
+        /// GeoJSON file to load
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+    },
+}
+
+/// Errors raised while loading the settings
+#[derive(Debug)]
+pub enum SettingsError {
+    /// The configuration file could not be read
+    Io(PathBuf, std::io::Error),
+    /// The configuration file is not valid TOML or contains unknown keys
+    Toml(PathBuf, toml::de::Error),
+    /// A setting holds a value of the wrong type or out of range, named after
+    /// its environment variable
+    InvalidEnv(String, String),
+    /// A setting is out of range; `setting` names the flag, configuration
+    /// file key or environment variable it was taken from
+    Invalid { setting: String, value: String },
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::Io(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            SettingsError::Toml(path, e) => write!(f, "invalid configuration in {}: {}", path.display(), e),
+            SettingsError::InvalidEnv(name, value) => write!(f, "invalid value {:?} for {}", value, name),
+            SettingsError::Invalid { setting, value } => write!(f, "invalid value {:?} for {}", value, setting),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
+
+impl Settings {
+    /// Loads the settings from the process arguments and environment
+    pub fn load(args: &SettingsArgs) -> Result<Self, SettingsError> {
+        Self::from_sources(args, std::env::vars())
+    }
+
+    /// Builds the settings from command-line flags and environment variables,
+    /// reading the configuration file they point to, if any
+    pub fn from_sources<I>(args: &SettingsArgs, env: I) -> Result<Self, SettingsError>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let env: Vec<(String, String)> = env
+            .into_iter()
+            .filter_map(|(name, value)| name.strip_prefix(ENV_PREFIX).map(|name| (name.to_string(), value)))
+            .collect();
+        let env_var = |name: &str| env.iter().find(|(n, _)| n == name).map(|(_, value)| value.clone());
+
+        let config_path = args.config.clone().or_else(|| env_var("CONFIG").map(PathBuf::from));
+        let mut settings = match &config_path {
+            Some(path) => Self::from_file(path)?,
+            None => Settings::default(),
+        };
+
+        let invalid = |name: &str, value: &str| SettingsError::InvalidEnv(format!("{}{}", ENV_PREFIX, name), value.to_string());
+        // Where the worker and pool sizes were last set, for reporting a zero
+        let file_key = |key: &str| match &config_path {
+            Some(path) => format!("{} in {}", key, path.display()),
+            None => key.to_string(),
+        };
+        let mut workers_source = file_key("workers");
+        let mut pool_size_source = file_key("pool_size");
+
+        if let Some(host) = env_var("HOST") {
+            settings.host = host;
+        }
+        if let Some(port) = env_var("PORT") {
+            settings.port = port.parse().map_err(|_| invalid("PORT", &port))?;
+        }
+        if let Some(database) = env_var("DATABASE") {
+            settings.database = database;
+        }
+        if let Some(workers) = env_var("WORKERS") {
+            settings.workers = Some(workers.parse().map_err(|_| invalid("WORKERS", &workers))?);
+            workers_source = format!("{}WORKERS", ENV_PREFIX);
+        }
+        if let Some(pool_size) = env_var("POOL_SIZE") {
+            settings.pool_size = pool_size.parse().map_err(|_| invalid("POOL_SIZE", &pool_size))?;
+            pool_size_source = format!("{}POOL_SIZE", ENV_PREFIX);
+        }
+        if let Some(origins) = env_var("CORS_ORIGINS") {
+            settings.cors_origins = origins
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(String::from)
+                .collect();
+        }
+        if let Some(log_level) = env_var("LOG_LEVEL") {
+            settings.log_level = log_level;
+        }
+
+        if let Some(host) = &args.host {
+            settings.host = host.clone();
+        }
+        if let Some(port) = args.port {
+            settings.port = port;
+        }
+        if let Some(database) = &args.database {
+            settings.database = database.clone();
+        }
+        if let Some(workers) = args.workers {
+            settings.workers = Some(workers);
+            workers_source = String::from("--workers");
+        }
+        if let Some(pool_size) = args.pool_size {
+            settings.pool_size = pool_size;
+            pool_size_source = String::from("--pool-size");
+        }
+        if !args.cors_origins.is_empty() {
+            settings.cors_origins = args.cors_origins.clone();
+        }
+        if let Some(log_level) = &args.log_level {
+            settings.log_level = log_level.clone();
+        }
+
+        // Neither r2d2 nor actix accept an empty pool, whichever source asked for it
+        if settings.workers == Some(0) {
+            return Err(SettingsError::Invalid { setting: workers_source, value: String::from("0") });
+        }
+        if settings.pool_size == 0 {
+            return Err(SettingsError::Invalid { setting: pool_size_source, value: String::from("0") });
+        }
+
+        Ok(settings)
+    }
+
+    /// Reads settings from a TOML file, using defaults for missing keys
+    pub fn from_file(path: &Path) -> Result<Self, SettingsError> {
+        let contents = std::fs::read_to_string(path).map_err(|e| SettingsError::Io(path.to_path_buf(), e))?;
+        toml::from_str(&contents).map_err(|e| SettingsError::Toml(path.to_path_buf(), e))
+    }
+
+    /// Address and port the server binds to
+    pub fn bind_address(&self) -> (String, u16) {
+        (self.host.clone(), self.port)
+    }
+
+    /// Whether cross-origin requests are allowed from any origin
+    pub fn allows_any_origin(&self) -> bool {
+        self.cors_origins.is_empty() || self.cors_origins.iter().any(|origin| origin == "*")
+    }
+}
@@ -0,0 +1,83 @@
+//! IANA time zones of countries and their current offsets
+//!
+//! Offsets and daylight saving time are computed offline from the tz
+//! database compiled into the binary by `chrono-tz`. The zones of every
+//! country in `data/countries.json` follow the database's `zone.tab`, with
+//! the zone of the capital listed first.
+
+use chrono::{DateTime, Offset, SecondsFormat, Utc};
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::Country;
+
+/// Whether `name` is a zone of the bundled tz database, such as `Europe/Berlin`
+pub fn is_timezone(name: &str) -> bool {
+    name.parse::<Tz>().is_ok()
+}
+
+/// The zone of the capital of a country, the first of its zones
+pub fn capital_timezone(country: &Country) -> Option<&str> {
+    country.timezones.first().map(String::as_str)
+}
+
+/// The state of a time zone at an instant
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct TimeZoneInfo {
+    /// The IANA name of the zone, e.g. `Europe/Berlin`
+    pub timezone: String,
+    /// The abbreviation in use, e.g. `CEST`, or the offset for zones without one
+    pub abbreviation: String,
+    /// The offset from UTC as `±hh:mm`, e.g. `+02:00`
+    pub utc_offset: String,
+    /// The offset from UTC in seconds
+    pub utc_offset_seconds: i32,
+    /// Whether daylight saving time is in effect
+    pub dst: bool,
+}
+
+impl TimeZoneInfo {
+    /// The state of the zone named `name` at `instant`, or `None` if the
+    /// bundled tz database has no such zone
+    pub fn at(name: &str, instant: DateTime<Utc>) -> Option<Self> {
+        let local = instant.with_timezone(&name.parse::<Tz>().ok()?);
+        let offset = local.offset();
+        let utc_offset = offset.fix().to_string();
+        Some(TimeZoneInfo {
+            timezone: name.to_string(),
+            abbreviation: offset.abbreviation().unwrap_or(&utc_offset).to_string(),
+            utc_offset_seconds: offset.fix().local_minus_utc(),
+            utc_offset,
+            dst: !offset.dst_offset().is_zero(),
+        })
+    }
+}
+
+/// The local time in the capital of a country
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct LocalTime {
+    /// Alpha-2 code of the country
+    pub code: String,
+    /// Capital of the country
+    pub capital: String,
+    /// The local date and time in RFC 3339 format, e.g. `2027-07-01T14:30:00+02:00`
+    pub local_time: String,
+    #[serde(flatten)]
+    pub zone: TimeZoneInfo,
+}
+
+impl LocalTime {
+    /// The time at `instant` in the capital of a country, or `None` if the
+    /// country has no known time zone
+    pub fn at(country: &Country, instant: DateTime<Utc>) -> Option<Self> {
+        let name = capital_timezone(country)?;
+        let local = instant.with_timezone(&name.parse::<Tz>().ok()?);
+        Some(LocalTime {
+            code: country.code.clone(),
+            capital: country.capital.clone(),
+            local_time: local.to_rfc3339_opts(SecondsFormat::Secs, false),
+            zone: TimeZoneInfo::at(name, instant)?,
+        })
+    }
+}
@@ -0,0 +1,289 @@
+//! Validation of country payloads submitted by clients
+//!
+//! Payloads are first normalized (codes uppercased, text trimmed, regions
+//! given their canonical spelling) and then checked field by field, so a
+//! client receives every problem with its request at once.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::calendar::Weekday;
+use crate::currencies::is_currency_code;
+use crate::error::ApiError;
+use crate::languages::{find_language, normalize_language_code, CountryLanguage};
+use crate::phone::{is_calling_code, normalize_calling_code};
+use crate::regions::{find_region, region_of};
+use crate::timezones::is_timezone;
+use crate::{normalize_code, normalize_tld, Country};
+
+/// Geographical regions a country may belong to
+pub const REGIONS: &[&str] = &[
+    "Africa",
+    "Antarctica",
+    "Asia",
+    "Europe",
+    "North America",
+    "Oceania",
+    "South America",
+];
+
+/// A single invalid field in a request payload
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct FieldError {
+    /// Name of the offending field as it appears in the payload
+    pub field: String,
+    /// Human-readable description of what is wrong with the value
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: &str, message: &str) -> Self {
+        FieldError {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Normalizes a country payload before validation and storage
+///
+/// Codes are uppercased (numeric codes zero-padded), names are trimmed and
+/// regions matching a known region case-insensitively get its canonical spelling.
+/// The main currency is moved to the front of the legal tenders, or taken
+/// from them if it is missing, and duplicate legal tenders are dropped.
+/// Language codes are lowercased, alpha-3 codes of known languages replaced
+/// by their short code, and repeated languages dropped. A missing M49 region
+/// code is filled in for countries listed in `data/regions.json`. Calling
+/// codes get their leading `+` and TLDs their leading dot and lowercase
+/// spelling, and repeated ones are dropped, as are repeated time zones.
+pub fn normalize_country(country: Country) -> Country {
+    let region = country.region.trim();
+    let region = REGIONS
+        .iter()
+        .find(|known| known.eq_ignore_ascii_case(region))
+        .map_or_else(|| region.to_string(), |known| known.to_string());
+
+    let listed: Vec<String> = country
+        .currencies
+        .iter()
+        .map(|code| code.trim().to_uppercase())
+        .filter(|code| !code.is_empty())
+        .collect();
+    let mut currency = country.currency.trim().to_uppercase();
+    if currency.is_empty() {
+        currency = listed.first().cloned().unwrap_or_default();
+    }
+    let mut currencies: Vec<String> = Vec::new();
+    for code in std::iter::once(&currency).filter(|code| !code.is_empty()).chain(&listed) {
+        if !currencies.contains(code) {
+            currencies.push(code.clone());
+        }
+    }
+
+    let mut languages: Vec<CountryLanguage> = Vec::new();
+    for language in country.languages {
+        let code = normalize_language_code(&language.code);
+        let code = find_language(&code).map_or(code, |known| known.code.clone());
+        if !languages.iter().any(|listed| listed.code == code) {
+            languages.push(CountryLanguage { code, status: language.status });
+        }
+    }
+
+    let code = normalize_code(&country.code);
+    let region_code = match country.region_code.trim() {
+        "" => region_of(&code).unwrap_or_default().to_string(),
+        given => normalize_code(given),
+    };
+
+    Country {
+        name: country.name.trim().to_string(),
+        code,
+        alpha3: normalize_code(&country.alpha3),
+        numeric: normalize_code(&country.numeric),
+        capital: country.capital.trim().to_string(),
+        region,
+        region_code,
+        currency,
+        currencies,
+        languages,
+        calling_codes: normalize_list(&country.calling_codes, normalize_calling_code),
+        tlds: normalize_list(&country.tlds, normalize_tld),
+        timezones: normalize_list(&country.timezones, |zone| zone.trim().to_string()),
+        weekend: normalize_weekend(&country.weekend),
+        ..country
+    }
+}
+
+/// Puts the days of a weekend in week order, dropping repeated days
+fn normalize_weekend(days: &[Weekday]) -> Vec<Weekday> {
+    let mut weekend = days.to_vec();
+    weekend.sort();
+    weekend.dedup();
+    weekend
+}
+
+/// Normalizes every non-blank value of a list, dropping repeated values
+fn normalize_list(values: &[String], normalize: fn(&str) -> String) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for value in values.iter().filter(|value| !value.trim().is_empty()).map(|value| normalize(value)) {
+        if !normalized.contains(&value) {
+            normalized.push(value);
+        }
+    }
+    normalized
+}
+
+fn is_upper_alpha(value: &str, len: usize) -> bool {
+    value.len() == len && value.chars().all(|c| c.is_ascii_uppercase())
+}
+
+/// Whether `tld` is a dot followed by a DNS label of letters, digits and
+/// hyphens, such as `.de` or the internationalized `.xn--p1ai`
+fn is_tld(tld: &str) -> bool {
+    tld.strip_prefix('.').is_some_and(|label| {
+        (2..=63).contains(&label.len())
+            && label.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+    })
+}
+
+fn is_latitude(value: f64) -> bool {
+    (-90.0..=90.0).contains(&value)
+}
+
+fn is_longitude(value: f64) -> bool {
+    (-180.0..=180.0).contains(&value)
+}
+
+/// Checks a pair of coordinates, which must be given together
+fn validate_position(
+    errors: &mut Vec<FieldError>,
+    latitude_field: &str,
+    longitude_field: &str,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+) {
+    match (latitude, longitude) {
+        (Some(latitude), Some(longitude)) => {
+            if !is_latitude(latitude) {
+                errors.push(FieldError::new(latitude_field, "must be between -90 and 90 degrees"));
+            }
+            if !is_longitude(longitude) {
+                errors.push(FieldError::new(longitude_field, "must be between -180 and 180 degrees"));
+            }
+        }
+        (Some(_), None) => errors.push(FieldError::new(longitude_field, &format!("must be given with {}", latitude_field))),
+        (None, Some(_)) => errors.push(FieldError::new(latitude_field, &format!("must be given with {}", longitude_field))),
+        (None, None) => {}
+    }
+}
+
+/// Checks a normalized country payload, collecting every invalid field
+///
+/// Coordinates, area and bounding box are optional, but a latitude needs its
+/// longitude.
+pub fn validate_country(country: &Country) -> Result<(), ApiError> {
+    check_country(country, false, false)
+}
+
+/// Checks a normalized country payload replacing the stored country `stored`
+///
+/// Unlike [`validate_country`], `capital` and `currency` may stay empty when
+/// they are empty in `stored`, so that territories without either can still
+/// be changed.
+pub fn validate_country_update(country: &Country, stored: &Country) -> Result<(), ApiError> {
+    check_country(country, stored.capital.is_empty(), stored.currency.is_empty())
+}
+
+/// Checks a country of the bundled seed data
+///
+/// Unlike [`validate_country`], `capital` and `currency` may be empty, as
+/// they are for the territories that have none, such as Antarctica.
+pub fn validate_seed_country(country: &Country) -> Result<(), ApiError> {
+    check_country(country, true, true)
+}
+
+fn check_country(country: &Country, no_capital: bool, no_currency: bool) -> Result<(), ApiError> {
+    let mut errors = Vec::new();
+
+    if country.name.is_empty() {
+        errors.push(FieldError::new("name", "must not be empty"));
+    }
+    if !is_upper_alpha(&country.code, 2) {
+        errors.push(FieldError::new("code", "must be an ISO 3166-1 alpha-2 code of two letters"));
+    }
+    if !is_upper_alpha(&country.alpha3, 3) {
+        errors.push(FieldError::new("alpha3", "must be an ISO 3166-1 alpha-3 code of three letters"));
+    }
+    if country.numeric.len() != 3 || !country.numeric.chars().all(|c| c.is_ascii_digit()) {
+        errors.push(FieldError::new("numeric", "must be an ISO 3166-1 numeric code of three digits"));
+    }
+    if country.capital.is_empty() && !no_capital {
+        errors.push(FieldError::new("capital", "must not be empty"));
+    }
+    if !REGIONS.contains(&country.region.as_str()) {
+        errors.push(FieldError::new(
+            "region",
+            &format!("must be one of: {}", REGIONS.join(", ")),
+        ));
+    }
+    let known_region = find_region(&country.region_code).is_some_and(|region| region.code == country.region_code);
+    if !country.region_code.is_empty() && !known_region {
+        errors.push(FieldError::new("region_code", "must be the three-digit code of a known UN M49 region"));
+    }
+    let uninhabited = country.currency.is_empty() && no_currency;
+    if !uninhabited && !is_currency_code(&country.currency) {
+        errors.push(FieldError::new("currency", "must be a known ISO 4217 currency code"));
+    }
+    // The main currency, listed first, is already reported above
+    if !country.currencies.iter().filter(|code| **code != country.currency).all(|code| is_currency_code(code)) {
+        errors.push(FieldError::new("currencies", "must only contain known ISO 4217 currency codes"));
+    }
+
+    if !country.languages.iter().all(|language| find_language(&language.code).is_some()) {
+        errors.push(FieldError::new("languages", "must only contain known ISO 639 language codes"));
+    }
+
+    validate_position(&mut errors, "capital_latitude", "capital_longitude", country.capital_latitude, country.capital_longitude);
+    validate_position(&mut errors, "latitude", "longitude", country.latitude, country.longitude);
+    if country.area_km2.is_some_and(|area| !area.is_finite() || area < 0.0) {
+        errors.push(FieldError::new("area_km2", "must be a non-negative number of square kilometres"));
+    }
+    if let Some(bbox) = &country.bbox {
+        let valid = match bbox[..] {
+            [west, south, east, north] => {
+                is_longitude(west) && is_longitude(east) && is_latitude(south) && is_latitude(north) && south <= north
+            }
+            _ => false,
+        };
+        if !valid {
+            errors.push(FieldError::new(
+                "bbox",
+                "must be [west, south, east, north] in decimal degrees, with south not above north",
+            ));
+        }
+    }
+
+    if !country.calling_codes.iter().all(|code| is_calling_code(code)) {
+        errors.push(FieldError::new(
+            "calling_codes",
+            "must only contain E.164 calling codes such as +49, or +1 809 with an area code",
+        ));
+    }
+    if !country.tlds.iter().all(|tld| is_tld(tld)) {
+        errors.push(FieldError::new("tlds", "must only contain top-level domains such as .de"));
+    }
+    if !country.timezones.iter().all(|zone| is_timezone(zone)) {
+        errors.push(FieldError::new("timezones", "must only contain IANA time zones such as Europe/Berlin"));
+    }
+    if country.weekend.len() >= Weekday::ALL.len() {
+        errors.push(FieldError::new("weekend", "must leave at least one working day"));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ApiError::Validation(errors))
+    }
+}
@@ -1,11 +1,14 @@
+mod common;
+
 use actix_web::{test, web, App};
+use common::{app_state, seeded_pool};
 use world_countries_api::*;
 
 #[actix_web::test]
 async fn test_all_countries() {
     // Arrange
     let app = test::init_service(
-        App::new().configure(config)
+        App::new().app_data(app_state()).configure(config)
     ).await;
     
     // Act
@@ -18,16 +21,16 @@ async fn test_all_countries() {
     let body = test::read_body(resp).await;
     let countries: Vec<Country> = serde_json::from_slice(&body).unwrap();
     
-    assert_eq!(countries.len(), 10);
-    assert!(countries.iter().any(|c| c.code == "US"));
-    assert!(countries.iter().any(|c| c.code == "JP"));
+    assert_eq!(countries.len(), DEFAULT_PAGE_LIMIT as usize);
+    assert_eq!(countries[0].code, "AD");
+    assert!(countries.iter().any(|c| c.code == "CA"));
 }
 
 #[actix_web::test]
 async fn test_country_by_code_found() {
     // Arrange
     let app = test::init_service(
-        App::new().configure(config)
+        App::new().app_data(app_state()).configure(config)
     ).await;
     
     // Act
@@ -45,11 +48,91 @@ async fn test_country_by_code_found() {
     assert_eq!(country.capital, "Washington, D.C.");
 }
 
+#[actix_web::test]
+async fn test_country_by_code_working_week() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::get().uri("/countries/SA").to_request();
+    let saudi_arabia: Country = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/GB").to_request();
+    let uk: Country = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/BV").to_request();
+    let bouvet: Country = test::call_and_read_body_json(&app, req).await;
+    
+    // Assert
+    assert_eq!(saudi_arabia.weekend, vec![Weekday::Friday, Weekday::Saturday]);
+    assert_eq!(saudi_arabia.first_day_of_week, Some(Weekday::Sunday));
+    assert_eq!(saudi_arabia.driving_side, Some(DrivingSide::Right));
+    assert_eq!(uk.weekend, vec![Weekday::Saturday, Weekday::Sunday]);
+    assert_eq!(uk.first_day_of_week, Some(Weekday::Monday));
+    assert_eq!(uk.driving_side, Some(DrivingSide::Left));
+    assert!(bouvet.weekend.is_empty());
+    assert_eq!(bouvet.driving_side, None);
+}
+
+/// Every officially assigned ISO 3166-1 alpha-2 code
+const ISO_ALPHA2_CODES: &str = "
+    AD AE AF AG AI AL AM AO AQ AR AS AT AU AW AX AZ BA BB BD BE
+    BF BG BH BI BJ BL BM BN BO BQ BR BS BT BV BW BY BZ CA CC CD
+    CF CG CH CI CK CL CM CN CO CR CU CV CW CX CY CZ DE DJ DK DM
+    DO DZ EC EE EG EH ER ES ET FI FJ FK FM FO FR GA GB GD GE GF
+    GG GH GI GL GM GN GP GQ GR GS GT GU GW GY HK HM HN HR HT HU
+    ID IE IL IM IN IO IQ IR IS IT JE JM JO JP KE KG KH KI KM KN
+    KP KR KW KY KZ LA LB LC LI LK LR LS LT LU LV LY MA MC MD ME
+    MF MG MH MK ML MM MN MO MP MQ MR MS MT MU MV MW MX MY MZ NA
+    NC NE NF NG NI NL NO NP NR NU NZ OM PA PE PF PG PH PK PL PM
+    PN PR PS PT PW PY QA RE RO RS RU RW SA SB SC SD SE SG SH SI
+    SJ SK SL SM SN SO SR SS ST SV SX SY SZ TC TD TF TG TH TJ TK
+    TL TM TN TO TR TT TV TW TZ UA UG UM US UY UZ VA VC VE VG VI
+    VN VU WF WS YE YT ZA ZM ZW
+";
+
+#[actix_web::test]
+async fn test_every_iso_code_resolves() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    for code in ISO_ALPHA2_CODES.split_whitespace() {
+        // Act
+        let req = test::TestRequest::get().uri(&format!("/countries/{}", code)).to_request();
+        let resp = test::call_service(&app, req).await;
+        
+        // Assert
+        assert_eq!(resp.status(), 200, "{} did not resolve", code);
+        let country: Country = test::read_body_json(resp).await;
+        assert_eq!(country.code, code);
+        let req = test::TestRequest::get().uri(&format!("/countries/{}", country.alpha3)).to_request();
+        let by_alpha3: Country = test::call_and_read_body_json(&app, req).await;
+        let req = test::TestRequest::get().uri(&format!("/countries/{}", country.numeric)).to_request();
+        let by_numeric: Country = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(by_alpha3.code, code);
+        assert_eq!(by_numeric.code, code);
+    }
+    
+    let req = test::TestRequest::get().uri("/countries?limit=1").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "249");
+}
+
+#[actix_web::test]
+async fn test_seeded_countries_pass_validation() {
+    for country in default_countries() {
+        assert!(validation::validate_seed_country(&country).is_ok(), "{} is invalid", country.code);
+        assert_eq!(validation::normalize_country(country.clone()), country);
+    }
+}
+
 #[actix_web::test]
 async fn test_country_by_code_not_found() {
     // Arrange
     let app = test::init_service(
-        App::new().configure(config)
+        App::new().app_data(app_state()).configure(config)
     ).await;
     
     // Act
@@ -64,7 +147,7 @@ async fn test_country_by_code_not_found() {
 async fn test_country_by_code_case_insensitive() {
     // Arrange
     let app = test::init_service(
-        App::new().configure(config)
+        App::new().app_data(app_state()).configure(config)
     ).await;
     
     // Act
@@ -80,11 +163,54 @@ async fn test_country_by_code_case_insensitive() {
     assert_eq!(country.code, "US");
 }
 
+#[actix_web::test]
+async fn test_country_by_alpha3_code() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::get().uri("/countries/deu").to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert!(resp.status().is_success());
+    
+    let body = test::read_body(resp).await;
+    let country: Country = serde_json::from_slice(&body).unwrap();
+    
+    assert_eq!(country.code, "DE");
+    assert_eq!(country.alpha3, "DEU");
+    assert_eq!(country.numeric, "276");
+}
+
+#[actix_web::test]
+async fn test_country_by_numeric_code() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::get().uri("/countries/36").to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert!(resp.status().is_success());
+    
+    let body = test::read_body(resp).await;
+    let country: Country = serde_json::from_slice(&body).unwrap();
+    
+    assert_eq!(country.code, "AU");
+    assert_eq!(country.numeric, "036");
+}
+
 #[actix_web::test]
 async fn test_get_regions() {
     // Arrange
     let app = test::init_service(
-        App::new().configure(config)
+        App::new().app_data(app_state()).configure(config)
     ).await;
     
     // Act
@@ -100,14 +226,14 @@ async fn test_get_regions() {
     assert!(regions.contains(&"Europe".to_string()));
     assert!(regions.contains(&"Asia".to_string()));
     assert!(regions.contains(&"North America".to_string()));
-    assert_eq!(regions.len(), 5); // Europe, Asia, North America, Oceania, South America, Africa
+    assert_eq!(regions.len(), 7); // every region, including Antarctica
 }
 
 #[actix_web::test]
 async fn test_countries_by_region() {
     // Arrange
     let app = test::init_service(
-        App::new().configure(config)
+        App::new().app_data(app_state()).configure(config)
     ).await;
     
     // Act
@@ -130,7 +256,7 @@ async fn test_countries_by_region() {
 async fn test_countries_by_region_case_insensitive() {
     // Arrange
     let app = test::init_service(
-        App::new().configure(config)
+        App::new().app_data(app_state()).configure(config)
     ).await;
     
     // Act
@@ -151,7 +277,7 @@ async fn test_countries_by_region_case_insensitive() {
 async fn test_countries_by_region_not_found() {
     // Arrange
     let app = test::init_service(
-        App::new().configure(config)
+        App::new().app_data(app_state()).configure(config)
     ).await;
     
     // Act
@@ -161,3 +287,687 @@ async fn test_countries_by_region_not_found() {
     // Assert
     assert_eq!(resp.status(), 404);
 }
+
+#[actix_web::test]
+async fn test_all_countries_paging() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::get().uri("/countries?limit=3&offset=3").to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert!(resp.status().is_success());
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "249");
+    
+    let link = resp.headers().get("Link").unwrap().to_str().unwrap().to_string();
+    assert!(link.contains("</countries?limit=3&offset=0>; rel=\"prev\""));
+    assert!(link.contains("</countries?limit=3&offset=6>; rel=\"next\""));
+    assert!(link.contains("</countries?limit=3&offset=246>; rel=\"last\""));
+    
+    let body = test::read_body(resp).await;
+    let countries: Vec<Country> = serde_json::from_slice(&body).unwrap();
+    
+    let codes: Vec<&str> = countries.iter().map(|c| c.code.as_str()).collect();
+    assert_eq!(codes, vec!["AG", "AI", "AL"]);
+}
+
+#[actix_web::test]
+async fn test_all_countries_sorted_and_filtered() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::get()
+        .uri("/countries?region=north%20america&currency=xcd&sort=-name")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert!(resp.status().is_success());
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "8");
+    
+    let body = test::read_body(resp).await;
+    let countries: Vec<Country> = serde_json::from_slice(&body).unwrap();
+    
+    let codes: Vec<&str> = countries.iter().map(|c| c.code.as_str()).collect();
+    assert_eq!(codes, vec!["VC", "LC", "KN", "MS", "GD", "DM", "AG", "AI"]);
+}
+
+#[actix_web::test]
+async fn test_all_countries_name_contains() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::get().uri("/countries?name_contains=UNITED").to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert!(resp.status().is_success());
+    
+    let body = test::read_body(resp).await;
+    let countries: Vec<Country> = serde_json::from_slice(&body).unwrap();
+    
+    let codes: Vec<&str> = countries.iter().map(|c| c.code.as_str()).collect();
+    assert_eq!(codes, vec!["AE", "GB", "UM", "US", "VI"]);
+}
+
+#[actix_web::test]
+async fn test_all_countries_working_week_filters() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    let codes = |countries: &[Country]| countries.iter().map(|c| c.code.clone()).collect::<Vec<String>>();
+    
+    // Act
+    let req = test::TestRequest::get().uri("/countries?weekend=Friday,sat&limit=250").to_request();
+    let friday_saturday: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries?driving_side=left&region=europe").to_request();
+    let left_in_europe: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries?first_day_of_week=friday").to_request();
+    let friday_first: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries?weekend=funday").to_request();
+    let unknown_day = test::call_service(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries?driving_side=middle").to_request();
+    let unknown_side = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(codes(&friday_saturday), vec![
+        "BD", "BH", "DZ", "EG", "IL", "IQ", "JO", "KW", "LY", "MV", "OM", "PS", "QA", "SA", "SD", "SY", "YE",
+    ]);
+    assert_eq!(codes(&left_in_europe), vec!["GB", "GG", "IE", "IM", "JE", "MT"]);
+    assert_eq!(codes(&friday_first), vec!["MV"]);
+    assert_eq!(unknown_day.status(), 400);
+    assert_eq!(unknown_side.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_all_countries_invalid_sort() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::get().uri("/countries?sort=population").to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_search_exact_name() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::get().uri("/countries/search?q=Germany").to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert!(resp.status().is_success());
+    
+    let body = test::read_body(resp).await;
+    let results: Vec<SearchResult> = serde_json::from_slice(&body).unwrap();
+    
+    assert_eq!(results[0].country.code, "DE");
+    assert_eq!(results[0].score, 1.0);
+}
+
+#[actix_web::test]
+async fn test_search_with_typos() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    for (q, expected) in [("germny", "DE"), ("united%20kingdm", "GB"), ("tokio", "JP")] {
+        // Act
+        let req = test::TestRequest::get()
+            .uri(&format!("/countries/search?q={}", q))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        
+        // Assert
+        assert!(resp.status().is_success());
+        
+        let body = test::read_body(resp).await;
+        let results: Vec<SearchResult> = serde_json::from_slice(&body).unwrap();
+        
+        assert_eq!(results[0].country.code, expected, "query {}", q);
+        assert!(results[0].score > 0.0 && results[0].score < 1.0);
+    }
+}
+
+#[actix_web::test]
+async fn test_search_alternative_name() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::get().uri("/countries/search?q=deutschland").to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert!(resp.status().is_success());
+    
+    let body = test::read_body(resp).await;
+    let results: Vec<SearchResult> = serde_json::from_slice(&body).unwrap();
+    
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].country.code, "DE");
+}
+
+#[actix_web::test]
+async fn test_search_empty_query() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::get().uri("/countries/search?q=%20").to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_not_found_problem_details() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::get().uri("/countries/XX").to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(resp.status(), 404);
+    assert_eq!(resp.headers().get("content-type").unwrap(), "application/problem+json");
+    
+    let body = test::read_body(resp).await;
+    let problem: ProblemDetails = serde_json::from_slice(&body).unwrap();
+    
+    assert_eq!(problem.status, 404);
+    assert_eq!(problem.code, "not_found");
+    assert_eq!(problem.detail, "Country with code XX not found");
+}
+
+#[actix_web::test]
+async fn test_add_country_duplicate_code() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::post()
+        .uri("/countries")
+        .set_json(serde_json::json!({
+            "name": "United States",
+            "code": "US",
+            "alpha3": "USA",
+            "numeric": "840",
+            "capital": "Washington, D.C.",
+            "region": "North America",
+            "currency": "USD"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(resp.status(), 409);
+    
+    let body = test::read_body(resp).await;
+    let problem: ProblemDetails = serde_json::from_slice(&body).unwrap();
+    
+    assert_eq!(problem.code, "conflict");
+}
+
+#[actix_web::test]
+async fn test_add_country_malformed_json() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::post()
+        .uri("/countries")
+        .insert_header(("content-type", "application/json"))
+        .set_payload(r#"{"name": "Atlantis""#)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(resp.status(), 400);
+    assert_eq!(resp.headers().get("content-type").unwrap(), "application/problem+json");
+    
+    let body = test::read_body(resp).await;
+    let problem: ProblemDetails = serde_json::from_slice(&body).unwrap();
+    
+    assert_eq!(problem.code, "invalid_body");
+}
+
+#[actix_web::test]
+async fn test_add_country_validation_errors() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::post()
+        .uri("/countries")
+        .set_json(serde_json::json!({
+            "name": "  ",
+            "code": "ATLAN",
+            "alpha3": "ATL",
+            "numeric": "999",
+            "capital": "Poseidonia",
+            "region": "Atlantic",
+            "currency": "dollars"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(resp.status(), 422);
+    
+    let body = test::read_body(resp).await;
+    let problem: ProblemDetails = serde_json::from_slice(&body).unwrap();
+    
+    assert_eq!(problem.code, "validation_failed");
+    let fields: Vec<&str> = problem.errors.iter().map(|e| e.field.as_str()).collect();
+    assert_eq!(fields, vec!["name", "code", "region", "currency"]);
+}
+
+#[actix_web::test]
+async fn test_add_country_requires_capital_and_currency() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    let antarctica = default_countries().into_iter().find(|c| c.code == "AQ").unwrap();
+    
+    // Act
+    let req = test::TestRequest::post()
+        .uri("/countries")
+        .set_json(serde_json::json!({
+            "name": "Atlantis",
+            "code": "XA",
+            "alpha3": "XAT",
+            "numeric": "999",
+            "capital": " ",
+            "region": "Europe",
+            "currency": ""
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(resp.status(), 422);
+    let problem: ProblemDetails = test::read_body_json(resp).await;
+    let fields: Vec<&str> = problem.errors.iter().map(|e| e.field.as_str()).collect();
+    assert_eq!(fields, vec!["capital", "currency"]);
+    // Only the bundled territories may go without
+    assert!(validation::validate_country(&antarctica).is_err());
+    assert!(validation::validate_seed_country(&antarctica).is_ok());
+}
+
+#[actix_web::test]
+async fn test_normalize_country_payload() {
+    // Arrange
+    let country = Country {
+        name: String::from(" Germany "),
+        code: String::from("de"),
+        alpha3: String::from("deu"),
+        numeric: String::from("276"),
+        capital: String::from("Berlin"),
+        region: String::from("europe"),
+        region_code: String::new(),
+        currency: String::from("eur"),
+        currencies: vec![String::from(" chf"), String::from("EUR")],
+        languages: vec![
+            CountryLanguage { code: String::from("DEU"), status: LanguageStatus::Official },
+            CountryLanguage { code: String::from(" da"), status: LanguageStatus::Recognized },
+            CountryLanguage { code: String::from("de"), status: LanguageStatus::Recognized },
+        ],
+        capital_latitude: Some(52.52),
+        capital_longitude: Some(13.4),
+        latitude: None,
+        longitude: None,
+        area_km2: Some(357588.0),
+        bbox: None,
+        calling_codes: vec![String::from(" 49"), String::from("+49")],
+        tlds: vec![String::from("DE"), String::from(".de")],
+        timezones: vec![String::from(" Europe/Berlin"), String::from("Europe/Berlin"), String::from("Europe/Busingen")],
+        weekend: vec![Weekday::Sunday, Weekday::Saturday, Weekday::Sunday],
+        first_day_of_week: Some(Weekday::Monday),
+        driving_side: Some(DrivingSide::Right),
+    };
+    
+    // Act
+    let country = validation::normalize_country(country);
+    
+    // Assert
+    assert_eq!(country.name, "Germany");
+    assert_eq!(country.code, "DE");
+    assert_eq!(country.alpha3, "DEU");
+    assert_eq!(country.region, "Europe");
+    assert_eq!(country.region_code, "155");
+    assert_eq!(country.currency, "EUR");
+    assert_eq!(country.currencies, vec!["EUR", "CHF"]);
+    assert_eq!(
+        country.languages,
+        vec![
+            CountryLanguage { code: String::from("de"), status: LanguageStatus::Official },
+            CountryLanguage { code: String::from("da"), status: LanguageStatus::Recognized },
+        ]
+    );
+    assert_eq!(country.calling_codes, vec!["+49"]);
+    assert_eq!(country.tlds, vec![".de"]);
+    assert_eq!(country.timezones, vec!["Europe/Berlin", "Europe/Busingen"]);
+    assert_eq!(country.weekend, vec![Weekday::Saturday, Weekday::Sunday]);
+    assert!(validation::validate_country(&country).is_ok());
+}
+
+#[actix_web::test]
+async fn test_patch_country_merges_fields() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::patch()
+        .uri("/countries/us")
+        .insert_header(("content-type", "application/merge-patch+json"))
+        .set_payload(r#"{"currency": "usd"}"#)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert!(resp.status().is_success());
+    
+    let body = test::read_body(resp).await;
+    let country: Country = serde_json::from_slice(&body).unwrap();
+    
+    assert_eq!(country.code, "US");
+    assert_eq!(country.name, "United States");
+    assert_eq!(country.capital, "Washington, D.C.");
+    assert_eq!(country.currency, "USD");
+}
+
+#[actix_web::test]
+async fn test_patch_territory_without_capital_or_currency() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::patch()
+        .uri("/countries/AQ")
+        .insert_header(("content-type", "application/merge-patch+json"))
+        .set_payload(r#"{"name": "Antarctic Treaty Area"}"#)
+        .to_request();
+    let renamed = test::call_service(&app, req).await;
+    let req = test::TestRequest::patch()
+        .uri("/countries/AQ")
+        .insert_header(("content-type", "application/merge-patch+json"))
+        .set_payload(r#"{"currency": "XYZ"}"#)
+        .to_request();
+    let unknown_currency = test::call_service(&app, req).await;
+    let req = test::TestRequest::patch()
+        .uri("/countries/FR")
+        .insert_header(("content-type", "application/merge-patch+json"))
+        .set_payload(r#"{"capital": ""}"#)
+        .to_request();
+    let no_capital = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(renamed.status(), 200);
+    let country: Country = test::read_body_json(renamed).await;
+    assert_eq!(country.name, "Antarctic Treaty Area");
+    assert_eq!(country.capital, "");
+    assert_eq!(country.currency, "");
+    assert_eq!(unknown_currency.status(), 422);
+    assert_eq!(no_capital.status(), 422);
+}
+
+#[actix_web::test]
+async fn test_patch_country_cannot_change_code() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::patch()
+        .uri("/countries/US")
+        .insert_header(("content-type", "application/merge-patch+json"))
+        .set_payload(r#"{"code": "UM"}"#)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_patch_country_not_found() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::patch()
+        .uri("/countries/XX")
+        .insert_header(("content-type", "application/merge-patch+json"))
+        .set_payload(r#"{"capital": "Nowhere"}"#)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(resp.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_update_country_mismatching_code() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::put()
+        .uri("/countries/FR")
+        .set_json(serde_json::json!({
+            "name": "Germany",
+            "code": "DE",
+            "alpha3": "DEU",
+            "numeric": "276",
+            "capital": "Berlin",
+            "region": "Europe",
+            "currency": "EUR"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_write_endpoints_normalize_the_path_code() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::put()
+        .uri("/countries/%20deu%20")
+        .set_json(serde_json::json!({
+            "name": "Germany",
+            "code": "DE",
+            "alpha3": "DEU",
+            "numeric": "276",
+            "capital": "Bonn",
+            "region": "Europe",
+            "currency": "EUR"
+        }))
+        .to_request();
+    let updated = test::call_service(&app, req).await;
+    let req = test::TestRequest::patch()
+        .uri("/countries/250")
+        .insert_header(("content-type", "application/merge-patch+json"))
+        .set_payload(r#"{"capital": "Versailles"}"#)
+        .to_request();
+    let patched: Country = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::delete().uri("/countries/esp").to_request();
+    let deleted = test::call_service(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/ES").to_request();
+    let gone = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(updated.status(), 200);
+    assert_eq!(patched.code, "FR");
+    assert_eq!(patched.capital, "Versailles");
+    assert_eq!(deleted.status(), 204);
+    assert_eq!(gone.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_endpoints_reject_malformed_codes() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::get().uri("/countries/F1").to_request();
+    let get = test::call_service(&app, req).await;
+    let req = test::TestRequest::put()
+        .uri("/countries/FRANCE")
+        .set_json(serde_json::json!({
+            "name": "France",
+            "code": "FR",
+            "alpha3": "FRA",
+            "numeric": "250",
+            "capital": "Paris",
+            "region": "Europe",
+            "currency": "EUR"
+        }))
+        .to_request();
+    let put = test::call_service(&app, req).await;
+    let req = test::TestRequest::patch()
+        .uri("/countries/F-R")
+        .insert_header(("content-type", "application/merge-patch+json"))
+        .set_payload(r#"{"capital": "Lyon"}"#)
+        .to_request();
+    let patch = test::call_service(&app, req).await;
+    let req = test::TestRequest::delete().uri("/countries/1234").to_request();
+    let delete = test::call_service(&app, req).await;
+    
+    let mut sub_resources = Vec::new();
+    for uri in [
+        "/countries/F1.geojson",
+        "/countries/F1/distance/DE",
+        "/countries/DE/distance/F1",
+        "/countries/F1/neighbors",
+        "/countries/F1/route/DE",
+        "/countries/F1/timezones",
+        "/countries/F1/time",
+        "/countries/F1/holidays?year=2027",
+        "/countries/F1/is-holiday?date=2027-01-01",
+        "/countries/F1/business-days?days=1",
+        "/currencies/EU1",
+        "/currencies/EU1/countries",
+    ] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        sub_resources.push((uri, test::call_service(&app, req).await.status()));
+    }
+    
+    // Assert
+    assert_eq!(get.status(), 400);
+    assert_eq!(put.status(), 400);
+    assert_eq!(patch.status(), 400);
+    assert_eq!(delete.status(), 400);
+    for (uri, status) in sub_resources {
+        assert_eq!(status, 400, "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn test_merge_patch_removes_null_members() {
+    // Arrange
+    let mut target = serde_json::json!({"a": "b", "c": {"d": "e", "f": "g"}});
+    let patch = serde_json::json!({"a": "z", "c": {"f": null}});
+    
+    // Act
+    merge_patch(&mut target, &patch);
+    
+    // Assert
+    assert_eq!(target, serde_json::json!({"a": "z", "c": {"d": "e"}}));
+}
+
+#[actix_web::test]
+async fn test_panicking_request_does_not_break_the_pool() {
+    // Arrange
+    let state = app_state();
+    let app = test::init_service(
+        App::new().app_data(state.clone()).configure(config)
+    ).await;
+    
+    // Act
+    let result: Result<(), ApiError> = state
+        .with_repo(|_| panic!("simulated failure while handling a request"))
+        .await;
+    let req = test::TestRequest::get().uri("/countries/FR").to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert!(matches!(result, Err(ApiError::Internal(_))));
+    assert!(resp.status().is_success());
+}
+
+#[actix_web::test]
+async fn test_pooled_connections_share_the_database() {
+    // Arrange
+    let pool = seeded_pool();
+    let state = web::Data::new(AppState::new(SqliteRepository::new(pool.clone())));
+    let app = test::init_service(
+        App::new().app_data(state).configure(config)
+    ).await;
+    
+    // Act
+    let _held = pool.get().unwrap();
+    let req = test::TestRequest::delete().uri("/countries/ZA").to_request();
+    let deleted = test::call_service(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/ZA").to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(deleted.status(), 204);
+    assert_eq!(resp.status(), 404);
+}
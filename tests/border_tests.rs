@@ -0,0 +1,89 @@
+mod common;
+
+use actix_web::{test, App};
+use common::{app_state, codes};
+use world_countries_api::*;
+
+#[actix_web::test]
+async fn test_border_data_is_consistent() {
+    // Arrange
+    let known: Vec<String> = seed_data().into_iter().map(|entry| entry.country.code).collect();
+
+    // Act
+    let borders = borders::border_data();
+
+    // Assert
+    assert!(borders.len() > 300);
+    for [a, b] in borders {
+        assert!(a < b, "{} and {} are listed in order", a, b);
+        assert!(known.contains(a) && known.contains(b), "{} and {} are known countries", a, b);
+    }
+    assert!(borders.windows(2).all(|pair| pair[0] < pair[1]), "borders are sorted and listed once");
+}
+
+#[actix_web::test]
+async fn test_neighbors_of_a_country() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/DEU/neighbors").to_request();
+    let germany: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/JP/neighbors").to_request();
+    let japan: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/XX/neighbors").to_request();
+    let missing = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(codes(&germany), vec!["AT", "BE", "CH", "CZ", "DK", "FR", "LU", "NL", "PL"]);
+    assert!(japan.is_empty());
+    assert_eq!(missing.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_land_route_between_countries() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/PRT/route/cn").to_request();
+    let silk_road: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/DE/route/DE").to_request();
+    let stay: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/JP/route/KR").to_request();
+    let island = test::call_service(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/DE/route/XX").to_request();
+    let missing = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(codes(&silk_road), vec!["PT", "ES", "FR", "DE", "PL", "RU", "CN"]);
+    assert_eq!(codes(&stay), vec!["DE"]);
+    assert_eq!(island.status(), 404);
+    let problem: ProblemDetails = test::read_body_json(island).await;
+    assert_eq!(problem.detail, "There is no land route from Japan to South Korea");
+    assert_eq!(missing.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_routes_avoid_deleted_countries() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    let req = test::TestRequest::delete().uri("/countries/PL").to_request();
+    test::call_service(&app, req).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/DE/neighbors").to_request();
+    let germany: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/DE/route/LT").to_request();
+    let detour: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+
+    // Assert
+    assert!(!codes(&germany).contains(&"PL"));
+    assert_eq!(codes(&detour), vec!["DE", "AT", "HU", "UA", "BY", "LT"]);
+}
@@ -0,0 +1,24 @@
+//! Fixtures shared by the integration tests
+#![allow(dead_code)]
+
+use actix_web::web;
+use world_countries_api::*;
+
+/// Pool over a fresh in-memory database holding the seed data
+pub fn seeded_pool() -> DbPool {
+    let pool = create_pool(":memory:", 4).expect("failed to open database");
+    let mut conn = pool.get().expect("failed to get a connection");
+    init_schema(&conn).expect("failed to create schema");
+    seed_countries(&mut conn).expect("failed to seed database");
+    pool
+}
+
+/// Application state serving a seeded SQLite database
+pub fn app_state() -> web::Data<AppState> {
+    web::Data::new(AppState::new(SqliteRepository::new(seeded_pool())))
+}
+
+/// Alpha-2 codes of `countries`, in order
+pub fn codes(countries: &[Country]) -> Vec<&str> {
+    countries.iter().map(|c| c.code.as_str()).collect()
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use actix_web::{test, App};
+use common::{app_state, codes};
+use world_countries_api::*;
+
+#[actix_web::test]
+async fn test_all_currencies() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::get().uri("/currencies").to_request();
+    let currencies: Vec<Currency> = test::call_and_read_body_json(&app, req).await;
+    
+    // Assert
+    assert_eq!(currencies.len(), 156);
+    let yen = currencies.iter().find(|c| c.code == "JPY").unwrap();
+    assert_eq!(yen.numeric, "392");
+    assert_eq!(yen.minor_units, 0);
+    let dinar = currencies.iter().find(|c| c.code == "KWD").unwrap();
+    assert_eq!(dinar.minor_units, 3);
+}
+
+#[actix_web::test]
+async fn test_currency_by_alphabetic_and_numeric_code() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::get().uri("/currencies/gbp").to_request();
+    let pound: Currency = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/currencies/978").to_request();
+    let euro: Currency = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/currencies/XYZ").to_request();
+    let missing = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(pound.name, "Pound Sterling");
+    assert_eq!(pound.symbol, "£");
+    assert_eq!(euro.code, "EUR");
+    assert_eq!(missing.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_countries_by_currency_include_secondary_legal_tenders() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::get().uri("/currencies/INR/countries").to_request();
+    let rupee: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/currencies/840/countries").to_request();
+    let dollar: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/currencies/XYZ/countries").to_request();
+    let missing = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(codes(&rupee), vec!["BT", "IN"]);
+    let bhutan = &rupee[0];
+    assert_eq!(bhutan.currency, "BTN");
+    assert_eq!(bhutan.currencies, vec!["BTN", "INR"]);
+    for code in ["EC", "PA", "US", "ZW"] {
+        assert!(codes(&dollar).contains(&code), "{} uses USD", code);
+    }
+    assert_eq!(missing.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_currency_filter_matches_any_legal_tender() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::get().uri("/countries?currency=zar").to_request();
+    let countries: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    
+    // Assert
+    assert_eq!(codes(&countries), vec!["LS", "NA", "SZ", "ZA"]);
+}
+
+#[actix_web::test]
+async fn test_changing_the_main_currency() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::patch()
+        .uri("/countries/PA")
+        .set_json(serde_json::json!({ "currency": "usd" }))
+        .to_request();
+    let replaced: Country = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::patch()
+        .uri("/countries/PA")
+        .set_json(serde_json::json!({ "currencies": ["PAB", "USD", "EUR"] }))
+        .to_request();
+    let listed: Country = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::patch()
+        .uri("/countries/PA")
+        .set_json(serde_json::json!({ "currencies": ["PAB", "XYZ"] }))
+        .to_request();
+    let invalid: ProblemDetails = test::call_and_read_body_json(&app, req).await;
+    
+    // Assert
+    assert_eq!(replaced.currency, "USD");
+    assert_eq!(replaced.currencies, vec!["USD"]);
+    assert_eq!(listed.currency, "USD");
+    assert_eq!(listed.currencies, vec!["USD", "PAB", "EUR"]);
+    assert_eq!(invalid.status, 422);
+    assert_eq!(invalid.errors[0].field, "currencies");
+}
@@ -0,0 +1,223 @@
+use actix_web::http::header;
+use actix_web::{test, web, App};
+use utoipa::OpenApi;
+use world_countries_api::*;
+
+fn app_state() -> web::Data<AppState> {
+    web::Data::new(AppState::new(InMemoryRepository::seeded()))
+}
+
+fn header_value<'a>(resp: &'a actix_web::dev::ServiceResponse, name: &str) -> &'a str {
+    resp.headers().get(name).and_then(|value| value.to_str().ok()).unwrap_or_default()
+}
+
+#[actix_web::test]
+async fn test_csv_export_can_be_imported_back() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get()
+        .uri("/countries?limit=250")
+        .insert_header((header::ACCEPT, "text/csv"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let content_type = header_value(&resp, "Content-Type").to_string();
+    let total = header_value(&resp, "X-Total-Count").to_string();
+    let body = test::read_body(resp).await;
+    let csv = std::str::from_utf8(&body).unwrap();
+    let batch = import::parse(ImportFormat::Csv, ImportMode::Upsert, csv).unwrap();
+
+    // Assert
+    assert_eq!(content_type, "text/csv; charset=utf-8");
+    assert_eq!(total, "249");
+    assert!(csv.starts_with(
+        "name,code,alpha3,numeric,capital,region,region_code,currency,currencies,languages,\
+         capital_latitude,capital_longitude,latitude,longitude,area_km2,bbox,calling_codes,tlds,timezones,\
+         weekend,first_day_of_week,driving_side\r\n"
+    ));
+    assert!(csv.contains(
+        ",PAB,PAB;USD,\"[{\"\"code\"\":\"\"es\"\",\"\"status\"\":\"\"official\"\"}]\",\
+         8.98,-79.52,8.54,-80.78,75417.0,-83.05;7.2;-77.17;9.65,+507,.pa,America/Panama,\
+         saturday;sunday,sunday,right\r\n"
+    ));
+    assert!(csv.contains("\"Washington, D.C.\""));
+    assert!(batch.failed.is_empty());
+    assert_eq!(batch.rows.len(), 249);
+    assert_eq!(batch.rows[0].country.code, "AD");
+    assert_eq!(batch.rows[0].country.languages[0].code, "ca");
+    assert_eq!(batch.rows[0].country.bbox, Some(vec![1.41, 42.43, 1.79, 42.66]));
+    assert_eq!(batch.rows[0].country.calling_codes, vec!["+376"]);
+    assert_eq!(batch.rows[0].country.tlds, vec![".ad"]);
+    assert_eq!(batch.rows[0].country.timezones, vec!["Europe/Andorra"]);
+    assert_eq!(batch.rows[0].country.weekend, vec![Weekday::Saturday, Weekday::Sunday]);
+    assert_eq!(batch.rows[0].country.first_day_of_week, Some(Weekday::Monday));
+    assert_eq!(batch.rows[0].country.driving_side, Some(DrivingSide::Right));
+}
+
+#[actix_web::test]
+async fn test_format_parameter_overrides_accept_and_is_kept_in_links() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get()
+        .uri("/countries?limit=2&region=Europe&format=ndjson")
+        .insert_header((header::ACCEPT, "application/json"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let content_type = header_value(&resp, "Content-Type").to_string();
+    let link = header_value(&resp, "Link").to_string();
+    let body = test::read_body(resp).await;
+    let countries: Vec<Country> = std::str::from_utf8(&body)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    // Assert
+    assert_eq!(content_type, "application/x-ndjson");
+    assert_eq!(countries.iter().map(|c| c.code.as_str()).collect::<Vec<_>>(), vec!["AD", "AL"]);
+    assert!(link.contains("format=ndjson"));
+}
+
+#[actix_web::test]
+async fn test_single_country_as_xml_and_yaml() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get()
+        .uri("/countries/DEU")
+        .insert_header((header::ACCEPT, "application/json;q=0.5, application/xml"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let xml_type = header_value(&resp, "Content-Type").to_string();
+    let xml = test::read_body(resp).await;
+    let req = test::TestRequest::get().uri("/countries/DE?format=yaml").to_request();
+    let yaml = test::call_and_read_body(&app, req).await;
+
+    // Assert
+    assert_eq!(xml_type, "application/xml; charset=utf-8");
+    assert_eq!(
+        std::str::from_utf8(&xml).unwrap(),
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <country>\n  <name>Germany</name>\n  <code>DE</code>\n  <alpha3>DEU</alpha3>\n  \
+         <numeric>276</numeric>\n  <capital>Berlin</capital>\n  <region>Europe</region>\n  \
+         <region_code>155</region_code>\n  <currency>EUR</currency>\n  <currencies>\n    <currency>EUR</currency>\n  </currencies>\n  \
+         <languages>\n    <language>\n      <code>de</code>\n      <status>official</status>\n    </language>\n    \
+         <language>\n      <code>da</code>\n      <status>recognized</status>\n    </language>\n  </languages>\n  \
+         <capital_latitude>52.52</capital_latitude>\n  <capital_longitude>13.4</capital_longitude>\n  \
+         <latitude>51.17</latitude>\n  <longitude>10.45</longitude>\n  <area_km2>357588.0</area_km2>\n  \
+         <bbox>\n    <item>5.87</item>\n    <item>47.27</item>\n    <item>15.04</item>\n    <item>55.06</item>\n  </bbox>\n  \
+         <calling_codes>\n    <calling_code>+49</calling_code>\n  </calling_codes>\n  \
+         <tlds>\n    <tld>.de</tld>\n  </tlds>\n  \
+         <timezones>\n    <timezone>Europe/Berlin</timezone>\n    <timezone>Europe/Busingen</timezone>\n  </timezones>\n  \
+         <weekend>\n    <day>saturday</day>\n    <day>sunday</day>\n  </weekend>\n  \
+         <first_day_of_week>monday</first_day_of_week>\n  <driving_side>right</driving_side>\n\
+         </country>\n"
+    );
+    assert_eq!(
+        std::str::from_utf8(&yaml).unwrap(),
+        "name: \"Germany\"\ncode: \"DE\"\nalpha3: \"DEU\"\nnumeric: \"276\"\n\
+         capital: \"Berlin\"\nregion: \"Europe\"\nregion_code: \"155\"\ncurrency: \"EUR\"\ncurrencies:\n  - \"EUR\"\n\
+         languages:\n  - code: \"de\"\n    status: \"official\"\n  - code: \"da\"\n    status: \"recognized\"\n\
+         capital_latitude: 52.52\ncapital_longitude: 13.4\nlatitude: 51.17\nlongitude: 10.45\narea_km2: 357588.0\n\
+         bbox:\n  - 5.87\n  - 47.27\n  - 15.04\n  - 55.06\n\
+         calling_codes:\n  - \"+49\"\ntlds:\n  - \".de\"\n\
+         timezones:\n  - \"Europe/Berlin\"\n  - \"Europe/Busingen\"\n\
+         weekend:\n  - \"saturday\"\n  - \"sunday\"\nfirst_day_of_week: \"monday\"\ndriving_side: \"right\"\n"
+    );
+}
+
+#[actix_web::test]
+async fn test_regions_and_search_results_in_other_formats() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get()
+        .uri("/regions")
+        .insert_header((header::ACCEPT, "text/*"))
+        .to_request();
+    let regions = test::call_and_read_body(&app, req).await;
+    let req = test::TestRequest::get()
+        .uri("/countries/search?q=germany&limit=1")
+        .insert_header((header::ACCEPT, "application/yaml"))
+        .to_request();
+    let results = test::call_and_read_body(&app, req).await;
+
+    // Assert
+    let regions = std::str::from_utf8(&regions).unwrap();
+    assert!(regions.starts_with("region\r\nAfrica\r\nAntarctica\r\n"));
+    assert_eq!(regions.lines().count(), 8);
+    let results = std::str::from_utf8(&results).unwrap();
+    assert!(results.starts_with("- name: \"Germany\"\n  code: \"DE\"\n"));
+    assert!(results.contains("\n  score: "));
+}
+
+#[actix_web::test]
+async fn test_unsupported_accept_is_not_acceptable() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get()
+        .uri("/countries/region/Europe")
+        .insert_header((header::ACCEPT, "image/png, text/csv;q=0"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(resp.status().as_u16(), 406);
+    assert_eq!(header_value(&resp, "Content-Type"), "application/problem+json");
+    let problem: ProblemDetails = test::read_body_json(resp).await;
+    assert_eq!(problem.code, "not_acceptable");
+}
+
+#[actix_web::test]
+async fn test_xml_escapes_text_and_names_list_items() {
+    // Arrange
+    let value = serde_json::json!([{ "name": "Trinidad & <Tobago>", "tags": ["a", "b"] }]);
+
+    // Act
+    let xml = export::render(ExportFormat::Xml, export::COUNTRIES, &value);
+
+    // Assert
+    assert!(xml.contains("<countries>\n  <country>\n"));
+    assert!(xml.contains("<name>Trinidad &amp; &lt;Tobago&gt;</name>"));
+    assert!(xml.contains("<tags>\n      <tag>a</tag>\n      <tag>b</tag>\n    </tags>"));
+}
+
+#[actix_web::test]
+async fn test_openapi_lists_export_media_types_on_reads_only() {
+    // Arrange
+    let openapi = ApiDoc::openapi();
+    let content_types = |path: &str, operation: utoipa::openapi::PathItemType| -> Vec<String> {
+        let item = &openapi.paths.paths[path];
+        match &item.operations[&operation].responses.responses["200"] {
+            utoipa::openapi::RefOr::T(response) => response.content.keys().cloned().collect(),
+            utoipa::openapi::RefOr::Ref(_) => Vec::new(),
+        }
+    };
+
+    // Act
+    let list = content_types("/countries", utoipa::openapi::PathItemType::Get);
+    let update = content_types("/countries/{code}", utoipa::openapi::PathItemType::Put);
+
+    // Assert
+    for media_type in ["application/json", "text/csv", "application/x-ndjson", "application/xml", "application/yaml"] {
+        assert!(list.iter().any(|t| t == media_type), "missing {}", media_type);
+    }
+    assert_eq!(update, vec!["application/json"]);
+}
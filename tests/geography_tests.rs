@@ -0,0 +1,319 @@
+mod common;
+
+use actix_web::{test, App};
+use common::app_state;
+use world_countries_api::*;
+
+/// Crude boundaries: South Africa with a hole for Lesotho, Lesotho, Fiji
+/// split at the antimeridian, France coded as Natural Earth does, a feature
+/// without a code and an unknown country
+const BOUNDARIES: &str = r#"{"type": "FeatureCollection", "features": [
+    {"type": "Feature", "properties": {"ISO_A2": "ZA"}, "geometry": {"type": "Polygon", "coordinates": [
+        [[16, -35], [33, -35], [33, -22], [16, -22], [16, -35]],
+        [[27, -30.7], [29.5, -30.7], [29.5, -28.5], [27, -28.5], [27, -30.7]]]}},
+    {"type": "Feature", "properties": {"ISO_A2": "LS"}, "geometry": {"type": "Polygon", "coordinates": [
+        [[27, -30.7], [29.5, -30.7], [29.5, -28.5], [27, -28.5], [27, -30.7]]]}},
+    {"type": "Feature", "properties": {"iso_a2": "fj"}, "geometry": {"type": "MultiPolygon", "coordinates": [
+        [[[177, -19], [180, -19], [180, -16], [177, -16], [177, -19]]],
+        [[[-180, -19], [-178, -19], [-178, -16], [-180, -16], [-180, -19]]]]}},
+    {"type": "Feature", "properties": {"ISO_A2": "-99", "ISO_A2_EH": "FR"}, "geometry": {"type": "Polygon", "coordinates": [
+        [[-5, 42], [8, 42], [8, 51], [-5, 51], [-5, 42]]]}},
+    {"type": "Feature", "properties": {"ISO_A2": "-99"}, "geometry": {"type": "Polygon", "coordinates": [
+        [[43, 8], [49, 8], [49, 11], [43, 11], [43, 8]]]}},
+    {"type": "Feature", "properties": {"code": "XX"}, "geometry": {"type": "Polygon", "coordinates": [
+        [[0, 0], [1, 0], [1, 1], [0, 1], [0, 0]]]}}
+]}"#;
+
+fn fields(problem: &ProblemDetails) -> Vec<&str> {
+    problem.errors.iter().map(|e| e.field.as_str()).collect()
+}
+
+#[actix_web::test]
+async fn test_countries_carry_coordinates_area_and_bbox() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/FR").to_request();
+    let france: Country = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/FJ").to_request();
+    let fiji: Country = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/AQ").to_request();
+    let antarctica: Country = test::call_and_read_body_json(&app, req).await;
+
+    // Assert
+    assert_eq!((france.capital_latitude, france.capital_longitude), (Some(48.86), Some(2.35)));
+    assert_eq!((france.latitude, france.longitude), (Some(46.23), Some(2.21)));
+    assert_eq!(france.area_km2, Some(551695.0));
+    assert_eq!(france.bbox, Some(vec![-5.14, 41.33, 9.56, 51.09]));
+    let bbox = fiji.bbox.unwrap();
+    assert!(bbox[0] > bbox[2], "Fiji's box spans the antimeridian");
+    assert_eq!(antarctica.capital_latitude, None);
+    assert_eq!(antarctica.bbox, Some(vec![-180.0, -90.0, 180.0, -60.0]));
+}
+
+#[actix_web::test]
+async fn test_geography_is_validated_and_can_be_cleared() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::patch()
+        .uri("/countries/DE")
+        .set_json(serde_json::json!({
+            "capital_latitude": 95.0,
+            "latitude": null,
+            "area_km2": -1.0,
+            "bbox": [5.87, 55.06, 15.04, 47.27]
+        }))
+        .to_request();
+    let invalid = test::call_service(&app, req).await;
+    let req = test::TestRequest::patch()
+        .uri("/countries/DE")
+        .set_json(serde_json::json!({ "capital_latitude": 50.73, "capital_longitude": 7.1, "bbox": null }))
+        .to_request();
+    let patched: Country = test::call_and_read_body_json(&app, req).await;
+
+    // Assert
+    assert_eq!(invalid.status(), 422);
+    let problem: ProblemDetails = test::read_body_json(invalid).await;
+    assert_eq!(fields(&problem), vec!["capital_latitude", "latitude", "area_km2", "bbox"]);
+    assert_eq!((patched.capital_latitude, patched.capital_longitude), (Some(50.73), Some(7.1)));
+    assert_eq!(patched.area_km2, Some(357588.0));
+    assert_eq!(patched.bbox, None);
+}
+
+#[actix_web::test]
+async fn test_csv_import_reads_geography_columns() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    let csv = "code,name,alpha3,numeric,capital,region,currency,capital_latitude,capital_longitude,area_km2,bbox\n\
+        XA,Atlantis,XAT,999,Poseidonia,Europe,EUR,36.42,25.43,76.19,25.33;36.33;25.49;36.47\n\
+        DE,Germany,DEU,276,Berlin,Europe,EUR,,,,\n\
+        XB,Lemuria,XBL,998,Mu,Asia,USD,north,80.1,,\n";
+
+    // Act
+    let req = test::TestRequest::post()
+        .uri("/countries/import")
+        .insert_header(("Content-Type", "text/csv"))
+        .set_payload(csv)
+        .to_request();
+    let report: ImportReport = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/XA").to_request();
+    let atlantis: Country = test::call_and_read_body_json(&app, req).await;
+
+    // Assert
+    assert_eq!(report.inserted.len(), 1);
+    assert_eq!(report.skipped[0].code, "DE");
+    assert_eq!(report.failed[0].line, 4);
+    assert_eq!(report.failed[0].message, "Invalid capital_latitude: invalid float literal");
+    assert_eq!((atlantis.capital_latitude, atlantis.capital_longitude), (Some(36.42), Some(25.43)));
+    assert_eq!(atlantis.latitude, None);
+    assert_eq!(atlantis.bbox, Some(vec![25.33, 36.33, 25.49, 36.47]));
+}
+
+#[actix_web::test]
+async fn test_nearest_countries_to_a_point() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/nearest?lat=0&lon=0&limit=3").to_request();
+    let nearest: Vec<NearbyCountry> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/nearest?lat=47&lon=8").to_request();
+    let default_limit: Vec<NearbyCountry> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/nearest?lat=91&lon=0").to_request();
+    let off_the_globe = test::call_service(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/nearest?lat=10").to_request();
+    let missing_lon = test::call_service(&app, req).await;
+
+    // Assert
+    let codes: Vec<&str> = nearest.iter().map(|c| c.country.code.as_str()).collect();
+    assert_eq!(codes, vec!["ST", "GH", "TG"]);
+    assert_eq!(nearest[0].distance_km, 735.3);
+    assert_eq!(default_limit.len(), 10);
+    assert_eq!(default_limit[0].country.code, "CH");
+    assert_eq!(off_the_globe.status(), 400);
+    assert_eq!(missing_lon.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_distance_between_capitals() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/FRA/distance/de").to_request();
+    let paris_berlin: CapitalDistance = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/NZ/distance/AU").to_request();
+    let tasman: CapitalDistance = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/DE/distance/AQ").to_request();
+    let no_capital = test::call_service(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/XX/distance/DE").to_request();
+    let missing = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!((paris_berlin.from.as_str(), paris_berlin.to.as_str()), ("FR", "DE"));
+    assert_eq!((paris_berlin.from_capital.as_str(), paris_berlin.to_capital.as_str()), ("Paris", "Berlin"));
+    assert_eq!((paris_berlin.km, paris_berlin.miles), (877.1, 545.0));
+    assert_eq!((tasman.km, tasman.miles), (2326.6, 1445.7));
+    assert_eq!(no_capital.status(), 404);
+    let problem: ProblemDetails = test::read_body_json(no_capital).await;
+    assert_eq!(problem.detail, "The capital of Antarctica has no known coordinates");
+    assert_eq!(missing.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_country_at_a_point_on_a_fresh_database() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    let memory = test::init_service(
+        App::new().app_data(actix_web::web::Data::new(AppState::new(InMemoryRepository::seeded()))).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/at?lat=48.85&lon=2.35").to_request();
+    let paris: Country = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/at?lat=-33.87&lon=151.21").to_request();
+    let sydney: Country = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/at?lat=48.85&lon=2.35").to_request();
+    let paris_in_memory: Country = test::call_and_read_body_json(&memory, req).await;
+    let req = test::TestRequest::get().uri("/countries/at?lat=0&lon=-30").to_request();
+    let atlantic = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(paris.code, "FR");
+    assert_eq!(sydney.code, "AU");
+    assert_eq!(paris_in_memory.code, "FR");
+    assert_eq!(atlantic.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_country_at_a_point_uses_loaded_boundaries() {
+    // Arrange
+    let state = app_state();
+    let (boundaries, skipped) = boundaries::parse_geojson(BOUNDARIES).unwrap();
+    let loaded = state.repo.load_boundaries(boundaries).unwrap();
+    let app = test::init_service(
+        App::new().app_data(state).configure(config)
+    ).await;
+
+    // Act
+    let point = |lat: f64, lon: f64| test::TestRequest::get().uri(&format!("/countries/at?lat={}&lon={}", lat, lon));
+    let lesotho: Country = test::call_and_read_body_json(&app, point(-29.31, 27.48).to_request()).await;
+    let south_africa: Country = test::call_and_read_body_json(&app, point(-25.75, 28.19).to_request()).await;
+    let fiji_east: Country = test::call_and_read_body_json(&app, point(-16.5, -179.9).to_request()).await;
+    let france: Country = test::call_and_read_body_json(&app, point(48.86, 2.35).to_request()).await;
+    let at_sea = test::call_service(&app, point(0.5, 0.5).to_request()).await;
+    let off_the_globe = test::call_service(&app, point(-95.0, 0.0).to_request()).await;
+
+    // Assert
+    assert_eq!(loaded, vec!["FJ", "FR", "LS", "ZA"]);
+    assert_eq!(skipped, 1);
+    assert_eq!(lesotho.code, "LS");
+    assert_eq!(south_africa.code, "ZA");
+    assert_eq!(fiji_east.code, "FJ");
+    assert_eq!(france.code, "FR");
+    assert_eq!(at_sea.status(), 404);
+    assert_eq!(off_the_globe.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_countries_as_geojson_features() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries.geojson").to_request();
+    let resp = test::call_service(&app, req).await;
+    let content_type = resp.headers().get("Content-Type").unwrap().to_str().unwrap().to_string();
+    let world: serde_json::Value = test::read_body_json(resp).await;
+    let req = test::TestRequest::get().uri("/countries.geojson?region=Western%20Europe").to_request();
+    let western_europe: FeatureCollection = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/deu.geojson").to_request();
+    let germany: Feature = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/deu.geojson?geometry=capital").to_request();
+    let berlin: Feature = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/DE").to_request();
+    let plain = test::call_service(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/XX.geojson").to_request();
+    let missing = test::call_service(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries.geojson?region=Atlantis").to_request();
+    let no_region = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(content_type, "application/geo+json");
+    assert_eq!(world["type"], "FeatureCollection");
+    assert_eq!(world["features"].as_array().unwrap().len(), 249);
+    assert_eq!(world["features"][0]["type"], "Feature");
+    assert_eq!(world["features"][0]["id"], "AD");
+    let bouvet = world["features"].as_array().unwrap().iter().find(|f| f["id"] == "BV").unwrap();
+    assert!(bouvet["geometry"].is_null(), "Bouvet Island has neither a bundled boundary nor a capital");
+    let codes: Vec<&str> = western_europe.features.iter().map(|f| f.id.as_str()).collect();
+    assert_eq!(codes, vec!["AT", "BE", "CH", "DE", "FR", "LI", "LU", "MC", "NL"]);
+    assert_eq!(germany.properties.name, "Germany");
+    assert!(matches!(germany.geometry, Some(geojson::Geometry::Polygon(_))), "bundled boundary");
+    assert_eq!(berlin.geometry, Some(geojson::Geometry::Point([13.4, 52.52])));
+    assert_eq!(plain.status(), 200);
+    assert_eq!(missing.status(), 404);
+    assert_eq!(no_region.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_geojson_uses_simplified_boundaries() {
+    // Arrange
+    let state = app_state();
+    let (mut boundaries, _) = boundaries::parse_geojson(BOUNDARIES).unwrap();
+    boundaries.push(Boundary {
+        code: String::from("CH"),
+        polygons: vec![
+            vec![vec![[6.0, 46.0], [7.0, 46.01], [8.0, 46.0], [10.0, 46.0], [10.0, 47.8], [6.0, 47.8], [6.0, 46.0]]],
+            vec![vec![[9.0, 47.9], [9.01, 47.9], [9.01, 47.91], [9.0, 47.9]]],
+        ],
+    });
+    state.repo.load_boundaries(boundaries).unwrap();
+    let app = test::init_service(
+        App::new().app_data(state).configure(config)
+    ).await;
+
+    // Act
+    let feature = |uri: &str| test::TestRequest::get().uri(uri).to_request();
+    let fiji: Feature = test::call_and_read_body_json(&app, feature("/countries/FJ.geojson")).await;
+    let south_africa: Feature = test::call_and_read_body_json(&app, feature("/countries/ZA.geojson")).await;
+    let capital: Feature = test::call_and_read_body_json(&app, feature("/countries/ZA.geojson?geometry=capital")).await;
+    let detailed: Feature = test::call_and_read_body_json(&app, feature("/countries/CH.geojson")).await;
+    let simplified: Feature = test::call_and_read_body_json(&app, feature("/countries/CH.geojson?simplify=0.1")).await;
+    let boundaries_only: FeatureCollection =
+        test::call_and_read_body_json(&app, feature("/countries.geojson?geometry=boundary&region=155")).await;
+    let negative = test::call_service(&app, feature("/countries/CH.geojson?simplify=-1")).await;
+    let unknown_geometry = test::call_service(&app, feature("/countries.geojson?geometry=centroid")).await;
+
+    // Assert
+    assert!(matches!(fiji.geometry, Some(geojson::Geometry::MultiPolygon(ref polygons)) if polygons.len() == 2));
+    assert!(matches!(south_africa.geometry, Some(geojson::Geometry::Polygon(ref rings)) if rings.len() == 2));
+    assert_eq!(capital.geometry, Some(geojson::Geometry::Point([28.19, -25.75])));
+    assert!(matches!(detailed.geometry, Some(geojson::Geometry::MultiPolygon(_))));
+    assert_eq!(
+        simplified.geometry,
+        Some(geojson::Geometry::Polygon(vec![vec![[6.0, 46.0], [10.0, 46.0], [10.0, 47.8], [6.0, 47.8], [6.0, 46.0]]]))
+    );
+    let with_geometry: Vec<&str> = boundaries_only.features.iter().filter(|f| f.geometry.is_some()).map(|f| f.id.as_str()).collect();
+    // Liechtenstein and Monaco are too small for the bundled boundaries
+    assert_eq!(with_geometry, vec!["AT", "BE", "CH", "DE", "FR", "LU", "NL"]);
+    assert_eq!(negative.status(), 400);
+    assert_eq!(unknown_geometry.status(), 400);
+}
@@ -0,0 +1,246 @@
+mod common;
+
+use actix_web::{test, App};
+use chrono::NaiveDate;
+use common::app_state;
+use world_countries_api::calendar::{self, Calendar, DateRule};
+use world_countries_api::*;
+
+fn date(text: &str) -> NaiveDate {
+    calendar::parse_date(text).unwrap()
+}
+
+fn calendar_of(code: &str) -> Calendar {
+    Calendar::new(calendar::holiday_data().countries[code].clone(), calendar::DEFAULT_WEEKEND)
+}
+
+#[actix_web::test]
+async fn test_holiday_data_is_consistent() {
+    // Arrange
+    let data = calendar::holiday_data();
+    let known: Vec<String> = default_countries().into_iter().map(|c| c.code).collect();
+
+    // Assert
+    assert!(data.countries.len() >= 20);
+    for (code, rules) in &data.countries {
+        assert!(known.contains(code), "{} is a known country", code);
+        for rule in rules {
+            match &rule.date {
+                DateRule::Lunar { event, .. } => assert!(data.lunar_dates.contains_key(event), "{} is tabulated", event),
+                DateRule::NthWeekday { n, .. } => assert_ne!(*n, 0, "{} counts weekdays", rule.name),
+                _ => {}
+            }
+            assert!(!rule.date.dates_in(2027).is_empty() || rule.until.is_some(), "{} of {} falls in 2027", rule.name, code);
+        }
+    }
+}
+
+#[actix_web::test]
+async fn test_easter_dates() {
+    assert_eq!(calendar::easter(2024, false), Some(date("2024-03-31")));
+    assert_eq!(calendar::easter(2024, true), Some(date("2024-05-05")));
+    assert_eq!(calendar::easter(2025, false), Some(date("2025-04-20")));
+    assert_eq!(calendar::easter(2025, true), Some(date("2025-04-20")));
+    assert_eq!(calendar::easter(2027, false), Some(date("2027-03-28")));
+    assert_eq!(calendar::easter(2027, true), Some(date("2027-05-02")));
+}
+
+#[actix_web::test]
+async fn test_holidays_moved_off_the_weekend() {
+    // Act
+    let us = calendar_of("US").holidays(2027);
+    let gb = calendar_of("GB").holidays(2022);
+    let observed = |holidays: &[Holiday], name: &str| {
+        holidays.iter().find(|holiday| holiday.name == name).map(|holiday| holiday.observed_date)
+    };
+
+    // Assert
+    assert_eq!(us.len(), 11);
+    assert_eq!(us[0].date, date("2027-01-01"));
+    assert_eq!(observed(&us, "Martin Luther King Jr. Day"), Some(date("2027-01-18")));
+    assert_eq!(observed(&us, "Memorial Day"), Some(date("2027-05-31")));
+    assert_eq!(observed(&us, "Juneteenth"), Some(date("2027-06-18")));
+    assert_eq!(observed(&us, "Independence Day"), Some(date("2027-07-05")));
+    assert_eq!(observed(&us, "Thanksgiving Day"), Some(date("2027-11-25")));
+    assert_eq!(observed(&us, "Christmas Day"), Some(date("2027-12-24")));
+    // Christmas Day falls on a Sunday and Boxing Day keeps its Monday
+    assert_eq!(observed(&gb, "Boxing Day"), Some(date("2022-12-26")));
+    assert_eq!(observed(&gb, "Christmas Day"), Some(date("2022-12-27")));
+}
+
+#[actix_web::test]
+async fn test_lunar_and_limited_holidays() {
+    // Act
+    let korea = calendar_of("KR").holidays(2027);
+    let saudi = calendar_of("SA").holidays(2027);
+    let denmark_2023 = calendar_of("DK").holidays(2023);
+    let denmark_2024 = calendar_of("DK").holidays(2024);
+    let korea_2040 = calendar_of("KR").holidays(2040);
+
+    // Assert
+    let chuseok: Vec<NaiveDate> = korea.iter().filter(|h| h.name == "Chuseok").map(|h| h.date).collect();
+    assert_eq!(chuseok, vec![date("2027-09-14"), date("2027-09-15"), date("2027-09-16")]);
+    assert!(saudi.iter().any(|h| h.name == "Eid al-Fitr" && h.date == date("2027-03-09")));
+    assert!(denmark_2023.iter().any(|h| h.name == "Great Prayer Day"));
+    assert!(!denmark_2024.iter().any(|h| h.name == "Great Prayer Day"));
+    assert!(!korea_2040.iter().any(|h| h.name == "Chuseok"));
+    assert!(korea_2040.iter().any(|h| h.name == "Hangul Day"));
+}
+
+#[actix_web::test]
+async fn test_country_holidays_endpoint() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/DEU/holidays?year=2027").to_request();
+    let germany: Vec<Holiday> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/DE/holidays?year=1800").to_request();
+    let out_of_range = test::call_service(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/JP/holidays?year=2027").to_request();
+    let unknown = test::call_service(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/XX/holidays").to_request();
+    let missing = test::call_service(&app, req).await;
+
+    // Assert
+    let dates: Vec<String> = germany.iter().map(|h| h.date.to_string()).collect();
+    assert_eq!(dates, vec![
+        "2027-01-01", "2027-03-26", "2027-03-29", "2027-05-01", "2027-05-06",
+        "2027-05-17", "2027-10-03", "2027-12-25", "2027-12-26",
+    ]);
+    assert_eq!(germany[1].name, "Good Friday");
+    assert_eq!(out_of_range.status(), 400);
+    assert_eq!(unknown.status(), 404);
+    assert_eq!(missing.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_is_holiday_endpoint() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/GB/is-holiday?date=2027-12-28").to_request();
+    let boxing_day_off: HolidayCheck = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/GB/is-holiday?date=2027-12-29").to_request();
+    let working_day: HolidayCheck = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/GB/is-holiday?date=2027-12-31").to_request();
+    let new_years_eve: HolidayCheck = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/GB/is-holiday?date=28-12-2027").to_request();
+    let malformed = test::call_service(&app, req).await;
+
+    // Assert
+    assert!(boxing_day_off.is_holiday);
+    assert!(!boxing_day_off.is_business_day);
+    assert_eq!(boxing_day_off.holidays[0].name, "Boxing Day");
+    assert_eq!(boxing_day_off.holidays[0].date, date("2027-12-26"));
+    assert!(!working_day.is_holiday);
+    assert!(working_day.is_business_day);
+    assert!(working_day.holidays.is_empty());
+    assert!(new_years_eve.is_business_day);
+    assert_eq!(malformed.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_business_days_endpoint() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/GB/business-days?start=2027-12-23&days=3").to_request();
+    let forward: BusinessDays = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/GB/business-days?start=2027-12-29&days=-1").to_request();
+    let backward: BusinessDays = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/US/business-days?start=2027-12-30&days=1").to_request();
+    let new_year: BusinessDays = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/GB/business-days?start=2027-12-23&days=100000").to_request();
+    let too_many = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(forward.date, date("2027-12-30"));
+    assert_eq!(forward.days, 3);
+    assert_eq!(backward.date, date("2027-12-24"));
+    // New Year's Day 2028 falls on a Saturday and is observed on the Friday before
+    assert_eq!(new_year.date, date("2028-01-03"));
+    assert_eq!(too_many.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_lunar_calendars_reject_years_outside_their_tables() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    let get = |uri: &str| test::TestRequest::get().uri(uri).to_request();
+
+    // Act
+    let last: Vec<Holiday> = test::call_and_read_body_json(&app, get("/countries/KR/holidays?year=2035")).await;
+    let first: Vec<Holiday> = test::call_and_read_body_json(&app, get("/countries/SA/holidays?year=2020")).await;
+    let after = test::call_service(&app, get("/countries/KR/holidays?year=2036")).await;
+    let before = test::call_service(&app, get("/countries/SA/holidays?year=2019")).await;
+    let is_holiday = test::call_service(&app, get("/countries/TR/is-holiday?date=2036-01-01")).await;
+    let crossing = test::call_service(&app, get("/countries/AE/business-days?start=2035-12-20&days=30")).await;
+    let germany = test::call_service(&app, get("/countries/DE/holidays?year=2036")).await;
+    let matariki = test::call_service(&app, get("/countries/NZ/holidays?year=2021")).await;
+
+    // Assert
+    assert!(last.iter().any(|h| h.name == "Chuseok"));
+    assert!(first.iter().any(|h| h.name == "Eid al-Fitr"));
+    assert_eq!(after.status(), 400);
+    let problem: ProblemDetails = test::read_body_json(after).await;
+    assert!(problem.detail.contains("between 2020 and 2035"), "{}", problem.detail);
+    assert_eq!(before.status(), 400);
+    assert_eq!(is_holiday.status(), 400);
+    assert_eq!(crossing.status(), 400);
+    assert_eq!(germany.status(), 200);
+    // Matariki is only kept since 2022, so earlier years need no lunar dates
+    assert_eq!(matariki.status(), 200);
+    assert_eq!(calendar_of("KR").years(), 2020..=2035);
+    assert_eq!(calendar_of("NZ").years(), calendar::MIN_YEAR..=2035);
+}
+
+#[actix_web::test]
+async fn test_business_days_at_the_limits_of_i64() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/GB/business-days?start=2027-12-23&days=-9223372036854775808").to_request();
+    let most_negative = test::call_service(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/GB/business-days?start=2027-12-23&days=9223372036854775807").to_request();
+    let most_positive = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(most_negative.status(), 400);
+    assert_eq!(most_positive.status(), 400);
+    assert_eq!(calendar_of("GB").add_business_days(date("2027-12-23"), i64::MIN), None);
+}
+
+#[actix_web::test]
+async fn test_business_days_follow_the_country_weekend() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/SA/is-holiday?date=2027-01-08").to_request();
+    let friday: HolidayCheck = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/SA/is-holiday?date=2027-01-10").to_request();
+    let sunday: HolidayCheck = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/SA/business-days?start=2027-01-07&days=1").to_request();
+    let after_thursday: BusinessDays = test::call_and_read_body_json(&app, req).await;
+
+    // Assert
+    assert!(!friday.is_business_day);
+    assert!(sunday.is_business_day);
+    assert_eq!(after_thursday.date, date("2027-01-10"));
+}
@@ -0,0 +1,241 @@
+mod common;
+
+use actix_web::{test, web, App};
+use common::app_state;
+use world_countries_api::*;
+
+fn lines(rows: &[ImportedRow]) -> Vec<(usize, &str)> {
+    rows.iter().map(|row| (row.line, row.code.as_str())).collect()
+}
+
+#[actix_web::test]
+async fn test_import_csv_upsert() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    let csv = "code,name,alpha3,numeric,capital,region,currency\n\
+        XA,Atlantis,XAT,999,Poseidonia,Europe,EUR\n\
+        DE,Germany,DEU,276,Bonn,Europe,EUR\n\
+        FR,France,FRA,250,Paris,Europe,EUR\n\
+        \"XB\",\"Lemuria, Land of \"\"Mu\"\"\",XBL,998,\"Two\nLines\",Asia,usd\n\
+        XC,Nowhere,XCC,997,Nowhere,Atlantic,EUR\n";
+    
+    // Act
+    let req = test::TestRequest::post()
+        .uri("/countries/import?mode=upsert")
+        .insert_header(("Content-Type", "text/csv; charset=utf-8"))
+        .set_payload(csv)
+        .to_request();
+    let report: ImportReport = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/XB").to_request();
+    let lemuria: Country = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/DE").to_request();
+    let germany: Country = test::call_and_read_body_json(&app, req).await;
+    
+    // Assert
+    assert_eq!(lines(&report.inserted), vec![(2, "XA"), (5, "XB")]);
+    assert_eq!(lines(&report.updated), vec![(3, "DE")]);
+    assert_eq!(lines(&report.skipped), vec![(4, "FR")]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].line, 7);
+    assert_eq!(report.failed[0].errors[0].field, "region");
+    assert_eq!(lemuria.name, "Lemuria, Land of \"Mu\"");
+    assert_eq!(lemuria.capital, "Two\nLines");
+    assert_eq!(lemuria.currency, "USD");
+    assert_eq!(germany.capital, "Bonn");
+}
+
+#[actix_web::test]
+async fn test_import_upsert_territory_without_capital_or_currency() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    let csv = "code,alpha3,numeric,name,capital,region,currency\n\
+        AQ,ATA,010,Antarctic Treaty Area,,Antarctica,\n\
+        XA,XAT,999,Atlantis,,Europe,\n";
+    
+    // Act
+    let req = test::TestRequest::get()
+        .uri("/countries?limit=250")
+        .insert_header(("Accept", "text/csv"))
+        .to_request();
+    let export = test::call_and_read_body(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri("/countries/import?mode=upsert")
+        .insert_header(("Content-Type", "text/csv"))
+        .set_payload(export)
+        .to_request();
+    let round_trip: ImportReport = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri("/countries/import?mode=upsert")
+        .insert_header(("Content-Type", "text/csv"))
+        .set_payload(csv)
+        .to_request();
+    let report: ImportReport = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/AQ").to_request();
+    let antarctica: Country = test::call_and_read_body_json(&app, req).await;
+    
+    // Assert
+    assert!(round_trip.failed.is_empty());
+    assert_eq!(round_trip.skipped.len(), 249);
+    assert_eq!(lines(&report.updated), vec![(2, "AQ")]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].code.as_deref(), Some("XA"));
+    let fields: Vec<&str> = report.failed[0].errors.iter().map(|error| error.field.as_str()).collect();
+    assert_eq!(fields, vec!["capital", "currency"]);
+    assert_eq!(antarctica.name, "Antarctic Treaty Area");
+    assert_eq!(antarctica.tlds, vec![".aq"]);
+}
+
+#[actix_web::test]
+async fn test_import_json_array_insert_only() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    let json = r#"[
+        {"code": "XA", "alpha3": "XAT", "numeric": "999", "name": "Atlantis",
+         "capital": "Poseidonia", "region": "Europe", "currency": "EUR"},
+        {"code": "DE", "alpha3": "DEU", "numeric": "276", "name": "Germany",
+         "capital": "Bonn", "region": "Europe", "currency": "EUR"},
+        {"code": "XB", "name": "Incomplete"}
+    ]"#;
+    
+    // Act
+    let req = test::TestRequest::post()
+        .uri("/countries/import")
+        .insert_header(("Content-Type", "application/json"))
+        .set_payload(json)
+        .to_request();
+    let report: ImportReport = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/DE").to_request();
+    let germany: Country = test::call_and_read_body_json(&app, req).await;
+    
+    // Assert
+    assert_eq!(lines(&report.inserted), vec![(2, "XA")]);
+    assert_eq!(lines(&report.skipped), vec![(4, "DE")]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].line, 6);
+    assert!(report.failed[0].message.contains("missing field"));
+    assert_eq!(germany.capital, "Berlin");
+}
+
+#[actix_web::test]
+async fn test_import_ndjson_reports_bad_lines() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    let ndjson = "{\"code\":\"XA\",\"alpha3\":\"XAT\",\"numeric\":\"999\",\"name\":\"Atlantis\",\"capital\":\"Poseidonia\",\"region\":\"Europe\",\"currency\":\"EUR\"}\n\
+        \n\
+        not json\n\
+        {\"code\":\"XA\",\"alpha3\":\"XAT\",\"numeric\":\"999\",\"name\":\"Atlantis\",\"capital\":\"Poseidonia\",\"region\":\"Europe\",\"currency\":\"EUR\"}\n\
+        {\"code\":\"XB\",\"alpha3\":\"DEU\",\"numeric\":\"998\",\"name\":\"Copycat\",\"capital\":\"Berlin\",\"region\":\"Europe\",\"currency\":\"EUR\"}\n";
+    
+    // Act
+    let req = test::TestRequest::post()
+        .uri("/countries/import")
+        .insert_header(("Content-Type", "application/x-ndjson"))
+        .set_payload(ndjson)
+        .to_request();
+    let report: ImportReport = test::call_and_read_body_json(&app, req).await;
+    
+    // Assert
+    assert_eq!(lines(&report.inserted), vec![(1, "XA")]);
+    let failed: Vec<usize> = report.failed.iter().map(|row| row.line).collect();
+    assert_eq!(failed, vec![3, 4, 5]);
+    assert!(report.failed[1].message.contains("line 1"));
+    assert!(report.failed[2].message.contains("alpha-3 code DEU"));
+}
+
+#[actix_web::test]
+async fn test_import_replace_all() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    let csv = "code,alpha3,numeric,name,capital,region,currency\n\
+        DE,DEU,276,Germany,Berlin,Europe,EUR\n\
+        FR,FRA,250,France,Versailles,Europe,EUR\n\
+        JP,JPN,392,Japan,Tokyo,Asia,yen\n";
+    
+    // Act
+    let req = test::TestRequest::post()
+        .uri("/countries/import?mode=replace-all&format=csv")
+        .insert_header(("Content-Type", "application/octet-stream"))
+        .set_payload(csv)
+        .to_request();
+    let report: ImportReport = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries").to_request();
+    let resp = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(lines(&report.skipped), vec![(2, "DE")]);
+    assert_eq!(lines(&report.updated), vec![(3, "FR")]);
+    assert_eq!(report.failed[0].line, 4);
+    assert_eq!(report.deleted.len(), 246);
+    assert!(!report.deleted.contains(&String::from("JP")));
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "3");
+}
+
+#[actix_web::test]
+async fn test_import_replace_all_in_memory() {
+    // Arrange
+    let state = web::Data::new(AppState::new(InMemoryRepository::seeded()));
+    let app = test::init_service(
+        App::new().app_data(state).configure(config)
+    ).await;
+    let ndjson = "{\"code\":\"de\",\"alpha3\":\"deu\",\"numeric\":\"276\",\"name\":\"Germany\",\"capital\":\"Bonn\",\"region\":\"europe\",\"currency\":\"eur\"}\n";
+    
+    // Act
+    let req = test::TestRequest::post()
+        .uri("/countries/import?mode=replace-all&format=ndjson")
+        .set_payload(ndjson)
+        .to_request();
+    let report: ImportReport = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries").to_request();
+    let countries: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    
+    // Assert
+    assert_eq!(lines(&report.updated), vec![(1, "DE")]);
+    assert_eq!(report.deleted.len(), 248);
+    assert_eq!(countries.len(), 1);
+    assert_eq!(countries[0].capital, "Bonn");
+}
+
+#[actix_web::test]
+async fn test_import_unreadable_documents() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+    
+    // Act
+    let req = test::TestRequest::post()
+        .uri("/countries/import")
+        .insert_header(("Content-Type", "application/xml"))
+        .set_payload("<countries/>")
+        .to_request();
+    let unsupported = test::call_service(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri("/countries/import")
+        .insert_header(("Content-Type", "text/csv"))
+        .set_payload("code,name\nDE,Germany\n")
+        .to_request();
+    let missing_columns = test::call_service(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri("/countries/import")
+        .insert_header(("Content-Type", "application/json"))
+        .set_payload(r#"{"code": "DE"}"#)
+        .to_request();
+    let not_array = test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(unsupported.status(), 415);
+    assert_eq!(missing_columns.status(), 400);
+    let problem: ProblemDetails = test::read_body_json(missing_columns).await;
+    assert!(problem.detail.contains("alpha3, numeric, capital, region, currency"));
+    assert_eq!(not_array.status(), 400);
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use actix_web::{test, App};
+use common::{app_state, codes};
+use world_countries_api::*;
+
+fn status_of(country: &Country, language: &str) -> Option<LanguageStatus> {
+    country.languages.iter().find(|l| l.code == language).map(|l| l.status)
+}
+
+#[actix_web::test]
+async fn test_language_by_short_and_alpha3_code() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/languages/FR").to_request();
+    let french: Language = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/languages/tet").to_request();
+    let tetum: Language = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/languages/nld").to_request();
+    let dutch: Language = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/languages/xx").to_request();
+    let missing = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(french.alpha3, "fra");
+    assert_eq!(french.name, "French");
+    assert_eq!(tetum.code, "tet");
+    assert_eq!(dutch.code, "nl");
+    assert_eq!(missing.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_countries_by_language_carry_their_status() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/languages/de/countries").to_request();
+    let german: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/languages/xx/countries").to_request();
+    let missing = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(codes(&german), vec!["AT", "BE", "CH", "DE", "DK", "IT", "LI", "LU", "NA"]);
+    let austria = &german[0];
+    assert_eq!(austria.languages[0].code, "de");
+    assert_eq!(status_of(austria, "de"), Some(LanguageStatus::Official));
+    assert_eq!(status_of(&german[4], "de"), Some(LanguageStatus::Recognized));
+    assert_eq!(missing.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_country_languages_are_validated_and_stored() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::patch()
+        .uri("/countries/DE")
+        .set_json(serde_json::json!({
+            "languages": [{ "code": "deu", "status": "official" }, { "code": "hsb", "status": "recognized" }]
+        }))
+        .to_request();
+    let invalid = test::call_service(&app, req).await;
+    let req = test::TestRequest::patch()
+        .uri("/countries/DE")
+        .set_json(serde_json::json!({
+            "languages": [{ "code": "deu", "status": "official" }, { "code": "DA", "status": "recognized" },
+                          { "code": "fy", "status": "recognized" }]
+        }))
+        .to_request();
+    let patched: Country = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/languages/fy/countries").to_request();
+    let frisian: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+
+    // Assert
+    assert_eq!(invalid.status(), 422);
+    let problem: ProblemDetails = test::read_body_json(invalid).await;
+    assert_eq!(problem.errors[0].field, "languages");
+    let languages: Vec<&str> = patched.languages.iter().map(|l| l.code.as_str()).collect();
+    assert_eq!(languages, vec!["de", "da", "fy"]);
+    assert_eq!(codes(&frisian), vec!["DE", "NL"]);
+}
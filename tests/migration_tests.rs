@@ -0,0 +1,315 @@
+use rusqlite::{params, Connection};
+use world_countries_api::migrations::{current_version, latest_version, migrate};
+use world_countries_api::*;
+
+/// Creates a database with the schema the API originally shipped with
+fn original_database() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "CREATE TABLE countries (
+            code TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            capital TEXT NOT NULL,
+            region TEXT NOT NULL,
+            currency TEXT NOT NULL
+        );
+        INSERT INTO countries VALUES ('DE', 'Germany', 'Berlin', 'Europe', 'EUR');
+        INSERT INTO countries VALUES ('JP', 'Japan', 'Tokyo', 'Asia', 'JPY');
+        INSERT INTO countries VALUES ('NZ', 'New Zealand', 'Wellington', 'Oceania', 'NZD');",
+    )
+    .unwrap();
+    conn
+}
+
+fn column_names(conn: &Connection) -> Vec<String> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info('countries') ORDER BY cid").unwrap();
+    stmt.query_map([], |row| row.get(0)).unwrap().collect::<rusqlite::Result<_>>().unwrap()
+}
+
+#[test]
+fn test_fresh_database_is_fully_migrated() {
+    // Arrange
+    let conn = Connection::open_in_memory().unwrap();
+    
+    // Act
+    let applied = migrate(&conn).unwrap();
+    
+    // Assert
+    assert_eq!(applied, vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13]);
+    assert_eq!(current_version(&conn).unwrap(), latest_version());
+    assert_eq!(column_names(&conn), vec![
+        "code", "name", "capital", "region", "currency", "alpha3", "numeric", "region_code",
+        "capital_latitude", "capital_longitude", "latitude", "longitude", "area_km2",
+        "bbox_west", "bbox_south", "bbox_east", "bbox_north", "calling_codes", "tlds",
+        "timezones", "weekend", "first_day_of_week", "driving_side",
+    ]);
+}
+
+#[test]
+fn test_upgrade_original_schema_keeps_data() {
+    // Arrange
+    let conn = original_database();
+    
+    // Act
+    let applied = migrate(&conn).unwrap();
+    
+    // Assert
+    assert_eq!(applied, vec![2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13]);
+    assert_eq!(current_version(&conn).unwrap(), latest_version());
+    let rows: Vec<(String, String, Option<String>, Option<String>)> = conn
+        .prepare("SELECT code, capital, alpha3, numeric FROM countries ORDER BY code")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap();
+    assert_eq!(rows, vec![
+        (String::from("DE"), String::from("Berlin"), Some(String::from("DEU")), Some(String::from("276"))),
+        (String::from("JP"), String::from("Tokyo"), Some(String::from("JPN")), Some(String::from("392"))),
+        (String::from("NZ"), String::from("Wellington"), Some(String::from("NZL")), Some(String::from("554"))),
+    ]);
+}
+
+#[test]
+fn test_upgrade_links_countries_to_their_currencies() {
+    // Arrange
+    let conn = original_database();
+    conn.execute("INSERT INTO countries VALUES ('PA', 'Panama', 'Panama City', 'North America', 'PAB')", params![])
+        .unwrap();
+    
+    // Act
+    migrate(&conn).unwrap();
+    
+    // Assert
+    let links: Vec<(String, String)> = conn
+        .prepare("SELECT country_code, currency_code FROM country_currencies ORDER BY country_code, position")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap();
+    let links: Vec<(&str, &str)> = links.iter().map(|(country, currency)| (country.as_str(), currency.as_str())).collect();
+    assert_eq!(links, vec![("DE", "EUR"), ("JP", "JPY"), ("NZ", "NZD"), ("PA", "PAB"), ("PA", "USD")]);
+}
+
+#[test]
+fn test_upgrade_gives_countries_their_languages() {
+    // Arrange
+    let conn = original_database();
+    
+    // Act
+    migrate(&conn).unwrap();
+    
+    // Assert
+    let languages: Vec<(String, String)> = conn
+        .prepare("SELECT language_code, status FROM country_languages WHERE country_code = 'NZ' ORDER BY position")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap();
+    let languages: Vec<(&str, &str)> = languages.iter().map(|(code, status)| (code.as_str(), status.as_str())).collect();
+    assert_eq!(languages, vec![("en", "official"), ("mi", "official")]);
+}
+
+#[test]
+fn test_upgrade_places_countries_in_their_m49_region() {
+    // Arrange
+    let conn = original_database();
+    
+    // Act
+    migrate(&conn).unwrap();
+    
+    // Assert
+    let regions: Vec<(String, String)> = conn
+        .prepare("SELECT code, region_code FROM countries ORDER BY code")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap();
+    let regions: Vec<(&str, &str)> = regions.iter().map(|(code, region)| (code.as_str(), region.as_str())).collect();
+    assert_eq!(regions, vec![("DE", "155"), ("JP", "030"), ("NZ", "053")]);
+}
+
+#[test]
+fn test_upgrade_fills_in_the_geography_of_countries() {
+    // Arrange
+    let conn = original_database();
+    conn.execute("INSERT INTO countries VALUES ('XA', 'Atlantis', 'Poseidonia', 'Europe', 'EUR')", params![])
+        .unwrap();
+    
+    // Act
+    migrate(&conn).unwrap();
+    
+    // Assert
+    let geography: Vec<(String, [Option<f64>; 4])> = conn
+        .prepare("SELECT code, capital_latitude, area_km2, bbox_west, bbox_east FROM countries ORDER BY code")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, [row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?])))
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap();
+    assert_eq!(geography, vec![
+        (String::from("DE"), [Some(52.52), Some(357588.0), Some(5.87), Some(15.04)]),
+        (String::from("JP"), [Some(35.68), Some(377975.0), Some(122.93), Some(153.99)]),
+        (String::from("NZ"), [Some(-41.29), Some(268838.0), Some(166.43), Some(-176.16)]),
+        (String::from("XA"), [None, None, None, None]),
+    ]);
+}
+
+#[test]
+fn test_upgrade_fills_in_calling_codes_and_tlds() {
+    // Arrange
+    let conn = original_database();
+    conn.execute("INSERT INTO countries VALUES ('XA', 'Atlantis', 'Poseidonia', 'Europe', 'EUR')", params![])
+        .unwrap();
+    
+    // Act
+    migrate(&conn).unwrap();
+    
+    // Assert
+    let codes: Vec<(String, String, String)> = conn
+        .prepare("SELECT code, calling_codes, tlds FROM countries ORDER BY code")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap();
+    let codes: Vec<(&str, &str, &str)> = codes
+        .iter()
+        .map(|(code, calling_codes, tlds)| (code.as_str(), calling_codes.as_str(), tlds.as_str()))
+        .collect();
+    assert_eq!(codes, vec![
+        ("DE", "[\"+49\"]", "[\".de\"]"),
+        ("JP", "[\"+81\"]", "[\".jp\"]"),
+        ("NZ", "[\"+64\"]", "[\".nz\"]"),
+        ("XA", "[]", "[]"),
+    ]);
+}
+
+#[test]
+fn test_upgrade_fills_in_time_zones() {
+    // Arrange
+    let conn = original_database();
+    conn.execute("INSERT INTO countries VALUES ('XA', 'Atlantis', 'Poseidonia', 'Europe', 'EUR')", params![])
+        .unwrap();
+    
+    // Act
+    migrate(&conn).unwrap();
+    
+    // Assert
+    let zones: Vec<(String, String)> = conn
+        .prepare("SELECT code, timezones FROM countries ORDER BY code")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap();
+    let zones: Vec<(&str, &str)> = zones.iter().map(|(code, zones)| (code.as_str(), zones.as_str())).collect();
+    assert_eq!(zones, vec![
+        ("DE", "[\"Europe/Berlin\",\"Europe/Busingen\"]"),
+        ("JP", "[\"Asia/Tokyo\"]"),
+        ("NZ", "[\"Pacific/Auckland\",\"Pacific/Chatham\"]"),
+        ("XA", "[]"),
+    ]);
+}
+
+#[test]
+fn test_upgrade_fills_in_the_working_week() {
+    // Arrange
+    let conn = original_database();
+    conn.execute("INSERT INTO countries VALUES ('XA', 'Atlantis', 'Poseidonia', 'Europe', 'EUR')", params![])
+        .unwrap();
+    
+    // Act
+    migrate(&conn).unwrap();
+    
+    // Assert
+    let weeks: Vec<(String, String, Option<String>, Option<String>)> = conn
+        .prepare("SELECT code, weekend, first_day_of_week, driving_side FROM countries ORDER BY code")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap();
+    let weeks: Vec<(&str, &str, Option<&str>, Option<&str>)> = weeks
+        .iter()
+        .map(|(code, weekend, first_day, side)| (code.as_str(), weekend.as_str(), first_day.as_deref(), side.as_deref()))
+        .collect();
+    assert_eq!(weeks, vec![
+        ("DE", "[\"saturday\",\"sunday\"]", Some("monday"), Some("right")),
+        ("JP", "[\"saturday\",\"sunday\"]", Some("sunday"), Some("left")),
+        ("NZ", "[\"saturday\",\"sunday\"]", Some("monday"), Some("left")),
+        ("XA", "[]", None, None),
+    ]);
+}
+
+#[test]
+fn test_upgraded_database_is_served_and_searchable() {
+    // Arrange
+    let pool = create_pool(":memory:", 2).unwrap();
+    let conn = pool.get().unwrap();
+    conn.execute_batch(
+        "CREATE TABLE countries (code TEXT PRIMARY KEY, name TEXT NOT NULL, capital TEXT NOT NULL,
+            region TEXT NOT NULL, currency TEXT NOT NULL);
+        INSERT INTO countries VALUES ('NZ', 'New Zealand', 'Wellington', 'Oceania', 'NZD');",
+    )
+    .unwrap();
+    init_schema(&conn).unwrap();
+    let repo = SqliteRepository::new(pool.clone());
+    
+    // Act
+    let country = repo.get("NZ").unwrap().unwrap();
+    let results = repo.search("welington", 5).unwrap();
+    
+    // Assert
+    assert_eq!(country.name, "New Zealand");
+    assert_eq!(country.alpha3, "NZL");
+    assert_eq!(results[0].country.code, "NZ");
+}
+
+#[test]
+fn test_unversioned_database_with_current_schema_is_adopted() {
+    // Arrange: a database created before migrations were tracked
+    let conn = Connection::open_in_memory().unwrap();
+    migrate(&conn).unwrap();
+    conn.pragma_update(None, "user_version", 0).unwrap();
+    conn.execute(
+        "INSERT INTO countries (code, alpha3, numeric, name, capital, region, currency)
+         VALUES ('FR', 'FRA', '250', 'France', 'Paris', 'Europe', 'EUR')",
+        params![],
+    )
+    .unwrap();
+    
+    // Act
+    let applied = migrate(&conn).unwrap();
+    
+    // Assert
+    let version: u32 = conn.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap();
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM countries", [], |row| row.get(0)).unwrap();
+    assert!(applied.is_empty());
+    assert_eq!(version, latest_version());
+    assert_eq!(count, 1);
+}
+
+#[test]
+fn test_migrating_twice_is_a_no_op() {
+    let conn = original_database();
+    migrate(&conn).unwrap();
+    assert!(migrate(&conn).unwrap().is_empty());
+}
+
+#[test]
+fn test_newer_database_is_rejected() {
+    // Arrange
+    let conn = Connection::open_in_memory().unwrap();
+    migrate(&conn).unwrap();
+    conn.pragma_update(None, "user_version", latest_version() + 1).unwrap();
+    
+    // Act
+    let result = migrate(&conn);
+    
+    // Assert
+    assert!(matches!(result, Err(MigrationError::TooNew { .. })));
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use actix_web::{test, App};
+use common::{app_state, codes};
+use serde_json::json;
+use world_countries_api::*;
+
+#[actix_web::test]
+async fn test_calling_codes_and_tlds_are_well_formed() {
+    for entry in seed_data() {
+        let country = entry.country;
+        assert!(
+            country.calling_codes.iter().all(|code| phone::is_calling_code(code)),
+            "{} has well-formed calling codes",
+            country.code
+        );
+        assert!(
+            country.tlds.iter().all(|tld| tld.starts_with('.') && *tld == tld.to_lowercase()),
+            "{} has lowercase TLDs",
+            country.code
+        );
+    }
+}
+
+#[actix_web::test]
+async fn test_countries_by_calling_code() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/by-calling-code/44").to_request();
+    let uk: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/by-calling-code/%2B1%20809").to_request();
+    let dominican: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/by-calling-code/1").to_request();
+    let nanp: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/by-calling-code/999").to_request();
+    let missing = test::call_service(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/by-calling-code/abc").to_request();
+    let malformed = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(codes(&uk), vec!["GB", "GG", "IM", "JE"]);
+    assert_eq!(codes(&dominican), vec!["DO"]);
+    assert!(nanp.len() > 20);
+    assert!(codes(&nanp).contains(&"US") && codes(&nanp).contains(&"CA"));
+    assert_eq!(missing.status(), 404);
+    assert_eq!(malformed.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_countries_by_tld() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/by-tld/.de").to_request();
+    let germany: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/by-tld/UK").to_request();
+    let uk: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/by-tld/.zz").to_request();
+    let missing = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(codes(&germany), vec!["DE"]);
+    assert_eq!(codes(&uk), vec!["GB"]);
+    assert_eq!(missing.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_literal_country_paths_win_over_country_routes() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/by-tld/time").to_request();
+    let tld = test::call_service(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/by-tld/timezones").to_request();
+    let timezones = test::call_service(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/region/time").to_request();
+    let region = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(tld.status(), 404);
+    let problem: ProblemDetails = test::read_body_json(tld).await;
+    assert_eq!(problem.detail, "No country uses the top-level domain .time");
+    assert_eq!(timezones.status(), 404);
+    assert_eq!(region.status(), 404);
+    let problem: ProblemDetails = test::read_body_json(region).await;
+    assert_eq!(problem.detail, "No countries found in region time");
+}
+
+#[actix_web::test]
+async fn test_validate_phone_number() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::post()
+        .uri("/validate/phone")
+        .set_json(json!({ "number": "+49 30 901820" }))
+        .to_request();
+    let berlin: PhoneValidation = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri("/validate/phone")
+        .set_json(json!({ "number": "001 (809) 555-0100" }))
+        .to_request();
+    let santo_domingo: PhoneValidation = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri("/validate/phone")
+        .set_json(json!({ "number": "+1 212 555 01" }))
+        .to_request();
+    let short: PhoneValidation = test::call_and_read_body_json(&app, req).await;
+
+    // Assert
+    assert_eq!(berlin.number, "+4930901820");
+    assert_eq!(berlin.calling_code, "+49");
+    assert_eq!(berlin.national_number, "30901820");
+    assert!(berlin.plausible_length);
+    assert_eq!(berlin.country.code, "DE");
+    assert_eq!(santo_domingo.calling_code, "+1 809");
+    assert_eq!(santo_domingo.country.code, "DO");
+    assert!(santo_domingo.plausible_length);
+    assert_eq!(short.country.code, "US");
+    assert!(!short.plausible_length);
+}
+
+#[actix_web::test]
+async fn test_validate_phone_number_errors() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::post()
+        .uri("/validate/phone")
+        .set_json(json!({ "number": "030 901820" }))
+        .to_request();
+    let national = test::call_service(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri("/validate/phone")
+        .set_json(json!({ "number": "+999 1234 5678" }))
+        .to_request();
+    let unknown = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(national.status(), 422);
+    assert_eq!(unknown.status(), 404);
+}
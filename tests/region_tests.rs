@@ -0,0 +1,96 @@
+mod common;
+
+use actix_web::{test, App};
+use common::{app_state, codes};
+use world_countries_api::*;
+
+#[actix_web::test]
+async fn test_region_with_children_and_countries() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/regions/150").to_request();
+    let europe: RegionDetails = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/regions/channel%20islands").to_request();
+    let channel_islands: RegionDetails = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/regions/001").to_request();
+    let world: RegionDetails = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/regions/999").to_request();
+    let missing = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(europe.name, "Europe");
+    assert_eq!(europe.level, RegionLevel::Region);
+    assert_eq!(europe.parent.as_deref(), Some("001"));
+    let children: Vec<&str> = europe.children.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(children, vec!["Southern Europe", "Eastern Europe", "Northern Europe", "Western Europe"]);
+    assert!(codes(&europe.countries).contains(&"GG"));
+    assert_eq!(europe.countries.len(), 51);
+    assert_eq!(channel_islands.code, "830");
+    assert_eq!(channel_islands.level, RegionLevel::IntermediateRegion);
+    assert_eq!(codes(&channel_islands.countries), vec!["GG", "JE"]);
+    assert!(channel_islands.children.is_empty());
+    assert_eq!(world.children.len(), 5);
+    assert!(!codes(&world.countries).contains(&"AQ"));
+    assert_eq!(missing.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_countries_by_region_accepts_m49_names_and_codes() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/region/Western%20Europe").to_request();
+    let western_europe: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/region/143").to_request();
+    let central_asia: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/region/202").to_request();
+    let sub_saharan: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/region/north%20america").to_request();
+    let north_america: Vec<Country> = test::call_and_read_body_json(&app, req).await;
+
+    // Assert
+    assert_eq!(codes(&western_europe), vec!["AT", "BE", "CH", "DE", "FR", "LI", "LU", "MC", "NL"]);
+    assert_eq!(codes(&central_asia), vec!["KG", "KZ", "TJ", "TM", "UZ"]);
+    assert_eq!(sub_saharan.len(), 53);
+    assert!(!codes(&sub_saharan).contains(&"EG"));
+    assert!(north_america.iter().all(|c| c.region == "North America"));
+}
+
+#[actix_web::test]
+async fn test_region_code_is_defaulted_and_validated() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::patch()
+        .uri("/countries/GB")
+        .set_json(serde_json::json!({ "region_code": "150" }))
+        .to_request();
+    let moved: Country = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::patch()
+        .uri("/countries/GB")
+        .set_json(serde_json::json!({ "region_code": "" }))
+        .to_request();
+    let restored: Country = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::patch()
+        .uri("/countries/GB")
+        .set_json(serde_json::json!({ "region_code": "998" }))
+        .to_request();
+    let invalid = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(moved.region_code, "150");
+    assert_eq!(restored.region_code, "154");
+    assert_eq!(invalid.status(), 422);
+    let problem: ProblemDetails = test::read_body_json(invalid).await;
+    assert_eq!(problem.errors[0].field, "region_code");
+}
@@ -0,0 +1,373 @@
+mod common;
+
+use actix_web::{web, App};
+use common::seeded_pool;
+use r2d2::ManageConnection;
+use world_countries_api::repository::{parse_sort, CountryFilter, ListOptions};
+use world_countries_api::*;
+
+fn sqlite_repository() -> SqliteRepository {
+    SqliteRepository::new(seeded_pool())
+}
+
+/// Both backends, seeded with the same countries
+fn repositories() -> Vec<(&'static str, Box<dyn CountryRepository>)> {
+    vec![
+        ("sqlite", Box::new(sqlite_repository())),
+        ("memory", Box::new(InMemoryRepository::seeded())),
+    ]
+}
+
+fn atlantis() -> Country {
+    Country {
+        name: String::from("Atlantis"),
+        code: String::from("XA"),
+        alpha3: String::from("XAT"),
+        numeric: String::from("999"),
+        capital: String::from("Poseidonia"),
+        region: String::from("Europe"),
+        region_code: String::from("039"),
+        currency: String::from("EUR"),
+        currencies: vec![String::from("EUR")],
+        languages: vec![CountryLanguage { code: String::from("el"), status: LanguageStatus::Official }],
+        capital_latitude: Some(36.42),
+        capital_longitude: Some(25.43),
+        latitude: Some(36.4),
+        longitude: Some(25.45),
+        area_km2: Some(76.19),
+        bbox: Some(vec![25.33, 36.33, 25.49, 36.47]),
+        calling_codes: vec![String::from("+30 22860")],
+        tlds: vec![String::from(".xa")],
+        timezones: vec![String::from("Europe/Athens")],
+        weekend: vec![Weekday::Saturday, Weekday::Sunday],
+        first_day_of_week: Some(Weekday::Monday),
+        driving_side: Some(DrivingSide::Right),
+    }
+}
+
+#[test]
+fn test_backends_list_the_same_page() {
+    for (name, repo) in repositories() {
+        // Arrange
+        let options = ListOptions {
+            filter: CountryFilter {
+                region: Some(String::from("north america")),
+                currency: Some(String::from("xcd")),
+                ..CountryFilter::default()
+            },
+            sort: parse_sort(Some("-name")).unwrap(),
+            limit: 2,
+            offset: 1,
+        };
+        
+        // Act
+        let page = repo.list(&options).unwrap();
+        
+        // Assert
+        let codes: Vec<&str> = page.countries.iter().map(|c| c.code.as_str()).collect();
+        assert_eq!(page.total, 8, "{}", name);
+        assert_eq!(codes, vec!["LC", "KN"], "{}", name);
+    }
+}
+
+#[test]
+fn test_backends_filter_by_working_week() {
+    for (name, repo) in repositories() {
+        // Arrange
+        let options = ListOptions {
+            filter: CountryFilter {
+                weekend: vec![Weekday::Saturday, Weekday::Friday],
+                first_day_of_week: Some(Weekday::Saturday),
+                driving_side: Some(DrivingSide::Right),
+                ..CountryFilter::default()
+            },
+            sort: parse_sort(None).unwrap(),
+            limit: 3,
+            offset: 0,
+        };
+        
+        // Act
+        let page = repo.list(&options).unwrap();
+        
+        // Assert
+        let codes: Vec<&str> = page.countries.iter().map(|c| c.code.as_str()).collect();
+        assert_eq!(page.total, 11, "{}", name);
+        assert_eq!(codes, vec!["BH", "DZ", "EG"], "{}", name);
+    }
+}
+
+#[test]
+fn test_backends_look_up_every_code_form() {
+    for (name, repo) in repositories() {
+        for code in ["JP", "JPN", "392"] {
+            let country = repo.get(code).unwrap();
+            assert_eq!(country.map(|c| c.name), Some(String::from("Japan")), "{} {}", name, code);
+        }
+        assert_eq!(repo.get("XX").unwrap(), None, "{}", name);
+    }
+}
+
+#[test]
+fn test_backends_agree_on_regions_and_search() {
+    for (name, repo) in repositories() {
+        let regions = repo.regions().unwrap();
+        let south_america: Vec<String> = repo.by_region("SOUTH AMERICA").unwrap().into_iter().map(|c| c.code).collect();
+        let results = repo.search("germny", 5).unwrap();
+        let alt_name = repo.search("Deutschland", 5).unwrap();
+        
+        assert_eq!(regions.len(), 7, "{}", name);
+        assert_eq!(south_america.len(), 16, "{}", name);
+        assert_eq!(south_america[0], "AR", "{}", name);
+        assert_eq!(results[0].country.code, "DE", "{}", name);
+        assert_eq!(alt_name[0].country.code, "DE", "{}", name);
+    }
+}
+
+#[test]
+fn test_backends_agree_on_currencies() {
+    for (name, repo) in repositories() {
+        let currencies = repo.currencies().unwrap();
+        let euro = repo.currency("978").unwrap().unwrap();
+        let rand: Vec<String> = repo.by_currency("ZAR").unwrap().into_iter().map(|c| c.code).collect();
+        let panama = repo.get("PA").unwrap().unwrap();
+        
+        assert_eq!(currencies.len(), 156, "{}", name);
+        assert_eq!(currencies[0].code, "AED", "{}", name);
+        assert_eq!(euro.code, "EUR", "{}", name);
+        assert_eq!(euro.symbol, "€", "{}", name);
+        assert_eq!(repo.currency("XXX").unwrap(), None, "{}", name);
+        assert_eq!(rand, vec!["LS", "NA", "SZ", "ZA"], "{}", name);
+        assert_eq!(panama.currencies, vec!["PAB", "USD"], "{}", name);
+    }
+}
+
+#[test]
+fn test_backends_agree_on_languages() {
+    for (name, repo) in repositories() {
+        let languages = repo.languages().unwrap();
+        let german = repo.language("deu").unwrap().unwrap();
+        let romansh: Vec<String> = repo.by_language("rm").unwrap().into_iter().map(|c| c.code).collect();
+        let switzerland = repo.get("CH").unwrap().unwrap();
+        
+        assert_eq!(languages.len(), 142, "{}", name);
+        assert_eq!(languages[0].code, "aa", "{}", name);
+        assert_eq!(german.code, "de", "{}", name);
+        assert_eq!(repo.language("xx").unwrap(), None, "{}", name);
+        assert_eq!(romansh, vec!["CH"], "{}", name);
+        let codes: Vec<&str> = switzerland.languages.iter().map(|l| l.code.as_str()).collect();
+        assert_eq!(codes, vec!["de", "fr", "it", "rm"], "{}", name);
+        assert_eq!(repo.get("AQ").unwrap().unwrap().languages, Vec::new(), "{}", name);
+    }
+}
+
+#[test]
+fn test_backends_agree_on_regions() {
+    for (name, repo) in repositories() {
+        let western_europe = repo.region("western europe").unwrap().unwrap();
+        let americas = repo.subregions("019").unwrap();
+        let caribbean: Vec<String> = repo.in_region("029").unwrap().into_iter().map(|c| c.code).collect();
+        let latin_america = repo.in_region("419").unwrap();
+        let world = repo.in_region("001").unwrap();
+        
+        assert_eq!(western_europe.code, "155", "{}", name);
+        assert_eq!(western_europe.parent.as_deref(), Some("150"), "{}", name);
+        assert_eq!(repo.region("5").unwrap().unwrap().name, "South America", "{}", name);
+        assert_eq!(repo.region("Atlantis").unwrap(), None, "{}", name);
+        let codes: Vec<&str> = americas.iter().map(|r| r.code.as_str()).collect();
+        assert_eq!(codes, vec!["021", "419"], "{}", name);
+        assert_eq!(caribbean.len(), 28, "{}", name);
+        assert!(caribbean.contains(&String::from("JM")), "{}", name);
+        assert_eq!(latin_america.len(), 52, "{}", name);
+        assert_eq!(world.len(), 248, "{}", name);
+    }
+}
+
+#[test]
+fn test_backends_agree_on_nearest_countries() {
+    for (name, repo) in repositories() {
+        let nearest = repo.nearest(geo::Point::new(47.0, 8.0), 3).unwrap();
+        let across_antimeridian = repo.nearest(geo::Point::new(-17.0, 179.9), 2).unwrap();
+        
+        let codes: Vec<&str> = nearest.iter().map(|c| c.country.code.as_str()).collect();
+        assert_eq!(codes, vec!["CH", "LI", "LU"], "{}", name);
+        assert_eq!(nearest[0].distance_km, 26.6, "{}", name);
+        let codes: Vec<&str> = across_antimeridian.iter().map(|c| c.country.code.as_str()).collect();
+        assert_eq!(codes, vec!["FJ", "WF"], "{}", name);
+    }
+}
+
+#[test]
+fn test_backends_agree_on_countries_at_a_point() {
+    let square = |west: f64, south: f64, east: f64, north: f64| {
+        vec![vec![[west, south], [east, south], [east, north], [west, north], [west, south]]]
+    };
+    for (name, repo) in repositories() {
+        let loaded = repo
+            .load_boundaries(vec![
+                Boundary { code: String::from("IT"), polygons: vec![square(6.6, 36.6, 18.5, 47.1)] },
+                Boundary { code: String::from("XX"), polygons: vec![square(0.0, 0.0, 1.0, 1.0)] },
+            ])
+            .unwrap();
+        
+        assert_eq!(loaded, vec!["IT"], "{}", name);
+        let rome = repo.country_at(geo::Point::new(41.9, 12.5)).unwrap();
+        assert_eq!(rome.map(|c| c.code).as_deref(), Some("IT"), "{}", name);
+        assert_eq!(repo.country_at(geo::Point::new(0.5, 0.5)).unwrap(), None, "{}", name);
+        let stored = repo.boundaries(&[String::from("IT"), String::from("XX")]).unwrap();
+        assert_eq!(stored, vec![Boundary { code: String::from("IT"), polygons: vec![square(6.6, 36.6, 18.5, 47.1)] }], "{}", name);
+        // Countries that were not loaded keep their bundled boundary
+        let paris = repo.country_at(geo::Point::new(48.85, 2.35)).unwrap();
+        assert_eq!(paris.map(|c| c.code).as_deref(), Some("FR"), "{}", name);
+        
+        // Boundaries go away with their country
+        repo.delete("IT").unwrap();
+        assert_eq!(repo.country_at(geo::Point::new(41.9, 12.5)).unwrap(), None, "{}", name);
+    }
+}
+
+#[test]
+fn test_backends_agree_on_borders() {
+    for (name, repo) in repositories() {
+        let codes = |countries: Vec<Country>| countries.into_iter().map(|c| c.code).collect::<Vec<_>>();
+        
+        assert_eq!(codes(repo.neighbors("ES").unwrap()), vec!["AD", "FR", "GI", "MA", "PT"], "{}", name);
+        assert_eq!(repo.neighbors("IS").unwrap(), vec![], "{}", name);
+        let route = repo.route("NL", "IT").unwrap().map(codes);
+        assert_eq!(route, Some(vec![String::from("NL"), String::from("BE"), String::from("FR"), String::from("IT")]), "{}", name);
+        assert_eq!(repo.route("GB", "FR").unwrap(), None, "{}", name);
+        
+        // Deleted countries can no longer be crossed
+        repo.delete("FR").unwrap();
+        let route = repo.route("NL", "IT").unwrap().map(codes);
+        assert_eq!(route, Some(vec![String::from("NL"), String::from("DE"), String::from("AT"), String::from("IT")]), "{}", name);
+    }
+}
+
+#[test]
+fn test_deleting_a_country_removes_its_rows() {
+    // Arrange
+    let pool = seeded_pool();
+    let conn = pool.get().expect("failed to get a connection");
+    let repo = SqliteRepository::new(pool.clone());
+    let rows = |table: &str, column: &str| -> i64 {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {} WHERE {} = 'PL'", table, column), [], |row| row.get(0))
+            .unwrap()
+    };
+    repo.load_boundaries(vec![Boundary {
+        code: String::from("PL"),
+        polygons: vec![vec![vec![[14.1, 49.0], [24.2, 49.0], [24.2, 54.9], [14.1, 54.9], [14.1, 49.0]]]],
+    }])
+    .unwrap();
+    
+    // Act
+    assert!(repo.delete("PL").unwrap());
+    
+    // Assert
+    assert_eq!(rows("country_currencies", "country_code"), 0);
+    assert_eq!(rows("country_languages", "country_code"), 0);
+    assert_eq!(rows("country_alt_names", "code"), 0);
+    assert_eq!(rows("boundary_polygons", "country_code"), 0);
+    let neighbors: Vec<String> = repo.neighbors("DE").unwrap().into_iter().map(|c| c.code).collect();
+    assert!(!neighbors.contains(&String::from("PL")));
+    assert!(repo.by_currency("PLN").unwrap().is_empty());
+    assert!(repo.by_language("pl").unwrap().is_empty());
+}
+
+#[test]
+fn test_in_memory_database_outlives_its_connections() {
+    // Arrange
+    let manager = db::SqliteConnectionManager::new(":memory:");
+    let mut conn = manager.connect().expect("failed to open database");
+    init_schema(&conn).expect("failed to create schema");
+    seed_countries(&mut conn).expect("failed to seed database");
+    
+    // Act
+    drop(conn);
+    let conn = manager.connect().expect("failed to reopen database");
+    
+    // Assert
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM countries", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 249);
+}
+
+#[test]
+fn test_country_at_skips_orphaned_boundaries() {
+    // Arrange
+    let pool = seeded_pool();
+    let conn = pool.get().expect("failed to get a connection");
+    let repo = SqliteRepository::new(pool.clone());
+    let square = r#"[[[6.6, 36.6], [18.5, 36.6], [18.5, 47.1], [6.6, 47.1], [6.6, 36.6]]]"#;
+    // Databases written before foreign keys were enforced can hold such rows
+    conn.execute_batch("PRAGMA foreign_keys = OFF").unwrap();
+    for code in ["AA", "IT"] {
+        conn.execute(
+            "INSERT INTO boundary_polygons (country_code, coordinates) VALUES (?1, ?2)",
+            [code, square],
+        )
+        .unwrap();
+    }
+    
+    // Act
+    let rome = repo.country_at(geo::Point::new(41.9, 12.5)).unwrap();
+    
+    // Assert
+    assert_eq!(rome.map(|c| c.code).as_deref(), Some("IT"));
+}
+
+#[test]
+fn test_backends_write_countries() {
+    for (name, repo) in repositories() {
+        // Insert, then reject a duplicate
+        repo.insert(&atlantis()).unwrap();
+        assert!(matches!(repo.insert(&atlantis()), Err(ApiError::Conflict(_))), "{}", name);
+        
+        // Update and patch
+        let mut renamed = atlantis();
+        renamed.name = String::from("Lost Atlantis");
+        assert!(repo.update(&renamed).unwrap(), "{}", name);
+        let patched = repo.patch("XA", &serde_json::json!({ "capital": "Atlantis City" })).unwrap().unwrap();
+        assert_eq!(patched.name, "Lost Atlantis", "{}", name);
+        assert_eq!(patched.capital, "Atlantis City", "{}", name);
+        let patched = repo.patch("XA", &serde_json::json!({ "currencies": ["EUR", "USD"] })).unwrap().unwrap();
+        assert_eq!(repo.get("XA").unwrap().unwrap().currencies, vec!["EUR", "USD"], "{}", name);
+        assert_eq!(patched.currencies, vec!["EUR", "USD"], "{}", name);
+        assert_eq!(repo.get("XA").unwrap().unwrap().languages, atlantis().languages, "{}", name);
+        assert_eq!(repo.patch("XX", &serde_json::json!({})).unwrap(), None, "{}", name);
+        
+        // Delete
+        assert!(repo.delete("XA").unwrap(), "{}", name);
+        assert!(!repo.delete("XA").unwrap(), "{}", name);
+        assert!(!repo.update(&atlantis()).unwrap(), "{}", name);
+    }
+}
+
+#[test]
+fn test_backends_reject_duplicate_alpha3() {
+    for (name, repo) in repositories() {
+        let mut clash = atlantis();
+        clash.alpha3 = String::from("AUS");
+        assert!(matches!(repo.insert(&clash), Err(ApiError::Conflict(_))), "{}", name);
+    }
+}
+
+#[actix_web::test]
+async fn test_api_served_from_memory() {
+    // Arrange
+    let state = web::Data::new(AppState::new(InMemoryRepository::seeded()));
+    let app = actix_web::test::init_service(
+        App::new().app_data(state).configure(config)
+    ).await;
+    
+    // Act
+    let req = actix_web::test::TestRequest::post().uri("/countries").set_json(atlantis()).to_request();
+    let created = actix_web::test::call_service(&app, req).await;
+    let req = actix_web::test::TestRequest::get().uri("/countries?limit=3&offset=3").to_request();
+    let resp = actix_web::test::call_service(&app, req).await;
+    
+    // Assert
+    assert_eq!(created.status(), 201);
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "250");
+    let countries: Vec<Country> = actix_web::test::read_body_json(resp).await;
+    let codes: Vec<&str> = countries.iter().map(|c| c.code.as_str()).collect();
+    assert_eq!(codes, vec!["AG", "AI", "AL"]);
+}
@@ -0,0 +1,136 @@
+use std::io::Write;
+
+use world_countries_api::settings::{SettingsArgs, SettingsError};
+use world_countries_api::Settings;
+
+fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+}
+
+#[test]
+fn test_defaults() {
+    // Act
+    let settings = Settings::from_sources(&SettingsArgs::default(), env(&[])).unwrap();
+    
+    // Assert
+    assert_eq!(settings, Settings::default());
+    assert_eq!(settings.bind_address(), (String::from("127.0.0.1"), 8080));
+    assert_eq!(settings.database, "countries.db");
+    assert!(settings.allows_any_origin());
+}
+
+#[test]
+fn test_layering_file_env_and_flags() {
+    // Arrange
+    let path = std::env::temp_dir().join(format!("countries-settings-{}.toml", std::process::id()));
+    let mut file = std::fs::File::create(&path).unwrap();
+    writeln!(file, "host = \"0.0.0.0\"\nport = 9000\ndatabase = \"/var/lib/countries.db\"\nworkers = 2\npool_size = 16").unwrap();
+    
+    let args = SettingsArgs {
+        config: Some(path.clone()),
+        port: Some(9100),
+        ..SettingsArgs::default()
+    };
+    let vars = env(&[
+        ("COUNTRIES_API_PORT", "9050"),
+        ("COUNTRIES_API_WORKERS", "4"),
+        ("COUNTRIES_API_CORS_ORIGINS", "https://a.example, https://b.example"),
+        ("UNRELATED", "ignored"),
+    ]);
+    
+    // Act
+    let settings = Settings::from_sources(&args, vars).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    
+    // Assert
+    assert_eq!(settings.host, "0.0.0.0");
+    assert_eq!(settings.database, "/var/lib/countries.db");
+    assert_eq!(settings.workers, Some(4));
+    assert_eq!(settings.pool_size, 16);
+    assert_eq!(settings.port, 9100);
+    assert_eq!(settings.cors_origins, vec!["https://a.example", "https://b.example"]);
+    assert!(!settings.allows_any_origin());
+    assert_eq!(settings.log_level, "info");
+}
+
+#[test]
+fn test_invalid_environment_value() {
+    // Act
+    let result = Settings::from_sources(&SettingsArgs::default(), env(&[("COUNTRIES_API_PORT", "http")]));
+    
+    // Assert
+    assert!(matches!(result, Err(SettingsError::InvalidEnv(name, _)) if name == "COUNTRIES_API_PORT"));
+}
+
+#[test]
+fn test_zero_pool_size_is_rejected() {
+    // Arrange
+    let path = std::env::temp_dir().join(format!("countries-settings-pool-{}.toml", std::process::id()));
+    std::fs::write(&path, "pool_size = 0\n").unwrap();
+    let file_args = SettingsArgs {
+        config: Some(path.clone()),
+        ..SettingsArgs::default()
+    };
+    let flag_args = SettingsArgs {
+        pool_size: Some(0),
+        ..SettingsArgs::default()
+    };
+    
+    // Act
+    let from_file = Settings::from_sources(&file_args, env(&[]));
+    let overridden = Settings::from_sources(&file_args, env(&[("COUNTRIES_API_POOL_SIZE", "2")]));
+    let from_env = Settings::from_sources(&SettingsArgs::default(), env(&[("COUNTRIES_API_POOL_SIZE", "0")]));
+    let from_flag = Settings::from_sources(&flag_args, env(&[("COUNTRIES_API_POOL_SIZE", "2")]));
+    std::fs::remove_file(&path).unwrap();
+    
+    // Assert
+    let file_key = format!("pool_size in {}", path.display());
+    assert!(matches!(from_file, Err(SettingsError::Invalid { setting, .. }) if setting == file_key));
+    assert_eq!(overridden.unwrap().pool_size, 2);
+    assert!(matches!(from_env, Err(SettingsError::Invalid { setting, .. }) if setting == "COUNTRIES_API_POOL_SIZE"));
+    assert!(matches!(from_flag, Err(SettingsError::Invalid { setting, .. }) if setting == "--pool-size"));
+}
+
+#[test]
+fn test_zero_workers_is_rejected() {
+    // Arrange
+    let path = std::env::temp_dir().join(format!("countries-settings-workers-{}.toml", std::process::id()));
+    std::fs::write(&path, "workers = 0\n").unwrap();
+    let file_args = SettingsArgs {
+        config: Some(path.clone()),
+        ..SettingsArgs::default()
+    };
+    let flag_args = SettingsArgs {
+        workers: Some(0),
+        ..SettingsArgs::default()
+    };
+    
+    // Act
+    let from_file = Settings::from_sources(&file_args, env(&[]));
+    let from_env = Settings::from_sources(&SettingsArgs::default(), env(&[("COUNTRIES_API_WORKERS", "0")]));
+    let from_flag = Settings::from_sources(&flag_args, env(&[]));
+    let one = Settings::from_sources(&SettingsArgs::default(), env(&[("COUNTRIES_API_WORKERS", "1")]));
+    std::fs::remove_file(&path).unwrap();
+    
+    // Assert
+    let file_key = format!("workers in {}", path.display());
+    assert!(matches!(from_file, Err(SettingsError::Invalid { setting, .. }) if setting == file_key));
+    assert!(matches!(from_env, Err(SettingsError::Invalid { setting, .. }) if setting == "COUNTRIES_API_WORKERS"));
+    assert!(matches!(from_flag, Err(SettingsError::Invalid { setting, .. }) if setting == "--workers"));
+    assert_eq!(one.unwrap().workers, Some(1));
+}
+
+#[test]
+fn test_missing_config_file() {
+    // Arrange
+    let args = SettingsArgs {
+        config: Some("/nonexistent/countries.toml".into()),
+        ..SettingsArgs::default()
+    };
+    
+    // Act
+    let result = Settings::from_sources(&args, env(&[]));
+    
+    // Assert
+    assert!(matches!(result, Err(SettingsError::Io(..))));
+}
@@ -0,0 +1,120 @@
+use actix_web::{test, web, App};
+use chrono::{TimeZone, Utc};
+use world_countries_api::*;
+
+fn app_state() -> web::Data<AppState> {
+    web::Data::new(AppState::new(InMemoryRepository::seeded()))
+}
+
+#[actix_web::test]
+async fn test_seeded_time_zones_are_in_the_tz_database() {
+    for country in default_countries() {
+        assert!(
+            country.timezones.iter().all(|zone| timezones::is_timezone(zone)),
+            "{} has known time zones",
+            country.code
+        );
+    }
+}
+
+#[actix_web::test]
+async fn test_time_zone_offsets_follow_daylight_saving_time() {
+    // Arrange
+    let winter = Utc.with_ymd_and_hms(2027, 1, 15, 12, 0, 0).unwrap();
+    let summer = Utc.with_ymd_and_hms(2027, 7, 15, 12, 0, 0).unwrap();
+
+    // Act
+    let berlin_winter = TimeZoneInfo::at("Europe/Berlin", winter).unwrap();
+    let berlin_summer = TimeZoneInfo::at("Europe/Berlin", summer).unwrap();
+    let auckland_winter = TimeZoneInfo::at("Pacific/Auckland", winter).unwrap();
+    let kolkata = TimeZoneInfo::at("Asia/Kolkata", summer).unwrap();
+
+    // Assert
+    assert_eq!(
+        berlin_winter,
+        TimeZoneInfo {
+            timezone: String::from("Europe/Berlin"),
+            abbreviation: String::from("CET"),
+            utc_offset: String::from("+01:00"),
+            utc_offset_seconds: 3600,
+            dst: false,
+        }
+    );
+    assert_eq!(berlin_summer.abbreviation, "CEST");
+    assert_eq!(berlin_summer.utc_offset, "+02:00");
+    assert!(berlin_summer.dst);
+    assert_eq!(auckland_winter.utc_offset, "+13:00");
+    assert!(auckland_winter.dst);
+    assert_eq!(kolkata.utc_offset_seconds, 19800);
+    assert!(TimeZoneInfo::at("Mars/Olympus_Mons", summer).is_none());
+}
+
+#[actix_web::test]
+async fn test_local_time_in_the_capital() {
+    // Arrange
+    let instant = Utc.with_ymd_and_hms(2027, 7, 1, 12, 30, 0).unwrap();
+    let countries = default_countries();
+    let country = |code: &str| countries.iter().find(|c| c.code == code).unwrap();
+
+    // Act
+    let washington = LocalTime::at(country("US"), instant).unwrap();
+    let moscow = LocalTime::at(country("RU"), instant).unwrap();
+    let bouvet = LocalTime::at(country("BV"), instant);
+
+    // Assert
+    assert_eq!(washington.capital, "Washington, D.C.");
+    assert_eq!(washington.zone.timezone, "America/New_York");
+    assert_eq!(washington.local_time, "2027-07-01T08:30:00-04:00");
+    assert_eq!(moscow.zone.timezone, "Europe/Moscow");
+    assert_eq!(moscow.local_time, "2027-07-01T15:30:00+03:00");
+    assert!(bouvet.is_none());
+}
+
+#[actix_web::test]
+async fn test_country_timezones_endpoint() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/JPN/timezones").to_request();
+    let japan: Vec<TimeZoneInfo> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/AU/timezones").to_request();
+    let australia: Vec<TimeZoneInfo> = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/XX/timezones").to_request();
+    let missing = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(japan.len(), 1);
+    assert_eq!(japan[0].timezone, "Asia/Tokyo");
+    assert_eq!(japan[0].utc_offset, "+09:00");
+    assert!(!japan[0].dst);
+    assert_eq!(australia[0].timezone, "Australia/Sydney");
+    assert!(australia.len() > 5);
+    assert_eq!(missing.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_country_time_endpoint() {
+    // Arrange
+    let app = test::init_service(
+        App::new().app_data(app_state()).configure(config)
+    ).await;
+
+    // Act
+    let req = test::TestRequest::get().uri("/countries/392/time").to_request();
+    let tokyo: LocalTime = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/HM/time").to_request();
+    let unzoned = test::call_service(&app, req).await;
+    let req = test::TestRequest::get().uri("/countries/XX/time").to_request();
+    let missing = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(tokyo.code, "JP");
+    assert_eq!(tokyo.capital, "Tokyo");
+    assert_eq!(tokyo.zone.timezone, "Asia/Tokyo");
+    assert!(tokyo.local_time.ends_with("+09:00"));
+    assert_eq!(unzoned.status(), 404);
+    assert_eq!(missing.status(), 404);
+}